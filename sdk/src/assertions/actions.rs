@@ -51,6 +51,10 @@ pub mod c2pa_action {
     pub const PLACED: &str = "c2pa.placed";
     /// Asset is released to a wider audience.
     pub const PUBLISHED: &str = "c2pa.published";
+    /// An assertion was redacted from an ingredient's manifest.
+    pub const REDACTED: &str = "c2pa.redacted";
+    /// A `componentOf` ingredient was removed from the asset.
+    pub const REMOVED: &str = "c2pa.removed";
     /// A conversion of one packaging or container format to another. Content may be repackaged without transcoding.
     /// Does not include any adjustments that would affect the "editorial" meaning of the content.
     pub const REPACKAGED: &str = "c2pa.repackaged";
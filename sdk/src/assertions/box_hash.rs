@@ -308,16 +308,19 @@ mod tests {
 
     use super::*;
     #[cfg(test)]
-    use crate::{jumbf_io::get_assetio_handler_from_path, utils::test::fixture_path};
+    use crate::{
+        asset_handlers::sfnt_io::SfntIO,
+        asset_io::{AssetIO, CAIWriter},
+        jumbf_io::get_assetio_handler_from_path,
+        utils::test::fixture_path,
+    };
 
     #[test]
     fn test_hash_verify_jpg() {
         let ap = fixture_path("CA.jpg");
 
-        let bhp = get_assetio_handler_from_path(&ap)
-            .unwrap()
-            .asset_box_hash_ref()
-            .unwrap();
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
 
         let mut input = File::open(&ap).unwrap();
 
@@ -336,10 +339,8 @@ mod tests {
     fn test_hash_verify_jpg_reduced() {
         let ap = fixture_path("CA.jpg");
 
-        let bhp = get_assetio_handler_from_path(&ap)
-            .unwrap()
-            .asset_box_hash_ref()
-            .unwrap();
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
 
         let mut input = File::open(&ap).unwrap();
 
@@ -358,10 +359,148 @@ mod tests {
     fn test_hash_verify_png() {
         let ap = fixture_path("libpng-test.png");
 
-        let bhp = get_assetio_handler_from_path(&ap)
-            .unwrap()
-            .asset_box_hash_ref()
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_gif() {
+        let ap = fixture_path("sample1.gif");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_jxl() {
+        let ap = fixture_path("sample1.jxl");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_flac() {
+        let ap = fixture_path("sample1.flac");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_aiff() {
+        let ap = fixture_path("sample1.aiff");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_webm() {
+        let ap = fixture_path("sample1.webm");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_ttf() {
+        let ap = fixture_path("sample1.ttf");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
             .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_psd() {
+        let ap = fixture_path("Purple Square.psd");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
 
         let mut input = File::open(&ap).unwrap();
 
@@ -376,14 +515,79 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_hash_verify_jp2() {
+        let ap = fixture_path("sample1.jp2");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_glb() {
+        let ap = fixture_path("sample1.glb");
+
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
+
+        let mut input = File::open(&ap).unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+
+        // generate box hashes
+        bh.generate_box_hash_from_stream(&mut input, "sha256", bhp, false)
+            .unwrap();
+
+        // see if they match reading
+        bh.verify_stream_hash(&mut input, Some("sha256"), bhp)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_hash_verify_ttf_store_replaced() {
+        let source = include_bytes!("../../tests/fixtures/sample1.ttf");
+        let sfnt_io = SfntIO::new("ttf");
+
+        let mut placeholder = Cursor::new(Vec::new());
+        sfnt_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut placeholder,
+                &[0; 64],
+            )
+            .unwrap();
+
+        let mut bh = BoxHash { boxes: Vec::new() };
+        bh.generate_box_hash_from_stream(&mut placeholder, "sha256", &sfnt_io, false)
+            .unwrap();
+
+        // table checksums must not invalidate the hashes when the final store is written
+        let mut output_stream = Cursor::new(Vec::new());
+        sfnt_io
+            .write_cai(&mut placeholder, &mut output_stream, &[0xff; 64])
+            .unwrap();
+        bh.verify_stream_hash(&mut output_stream, Some("sha256"), &sfnt_io)
+            .unwrap();
+    }
+
     #[test]
     fn test_hash_verify_no_pngh() {
         let ap = fixture_path("libpng-test.png");
 
-        let bhp = get_assetio_handler_from_path(&ap)
-            .unwrap()
-            .asset_box_hash_ref()
-            .unwrap();
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
 
         let mut input = File::open(&ap).unwrap();
 
@@ -404,10 +608,8 @@ mod tests {
     fn test_json_round_trop() {
         let ap = fixture_path("CA.jpg");
 
-        let bhp = get_assetio_handler_from_path(&ap)
-            .unwrap()
-            .asset_box_hash_ref()
-            .unwrap();
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
 
         let mut input = File::open(&ap).unwrap();
 
@@ -433,10 +635,8 @@ mod tests {
     fn test_cbor_round_trop() {
         let ap = fixture_path("CA.jpg");
 
-        let bhp = get_assetio_handler_from_path(&ap)
-            .unwrap()
-            .asset_box_hash_ref()
-            .unwrap();
+        let handler = get_assetio_handler_from_path(&ap).unwrap();
+        let bhp = handler.asset_box_hash_ref().unwrap();
 
         let mut input = File::open(&ap).unwrap();
 
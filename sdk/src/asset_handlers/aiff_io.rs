@@ -0,0 +1,725 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader,
+        CAIWriter, HashBlockObjectType, HashObjectPositions, RemoteRefEmbed, RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+static SUPPORTED_TYPES: [&str; 5] = ["aif", "aiff", "aifc", "audio/aiff", "audio/x-aiff"];
+
+const FORM_ID: [u8; 4] = *b"FORM";
+const AIFF_TYPE: [u8; 4] = *b"AIFF";
+const AIFC_TYPE: [u8; 4] = *b"AIFC";
+
+const C2PA_CHUNK_ID: [u8; 4] = *b"C2PA";
+
+// XMP is stored in an application specific chunk with the "XMP " signature
+const APPL_CHUNK_ID: [u8; 4] = *b"APPL";
+const XMP_SIGNATURE: [u8; 4] = *b"XMP ";
+
+// chunk id(4) + chunk size(4)
+const CHUNK_HDR_LEN: u64 = 8;
+
+// FORM chunk header + form type(4)
+const FORM_HDR_LEN: u64 = 12;
+
+#[derive(Clone, Debug)]
+struct IffChunkPos {
+    pub start: u64,
+    pub length: u64,
+    pub id: [u8; 4],
+    pub appl_signature: Option<[u8; 4]>,
+}
+
+impl IffChunkPos {
+    // chunks are padded to an even length
+    pub fn end(&self) -> u64 {
+        self.start + CHUNK_HDR_LEN + self.length + (self.length & 1)
+    }
+
+    fn is_xmp(&self) -> bool {
+        self.id == APPL_CHUNK_ID && self.appl_signature == Some(XMP_SIGNATURE)
+    }
+}
+
+struct IffForm {
+    form_type: [u8; 4],
+    chunks: Vec<IffChunkPos>,
+}
+
+fn aiff_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("AIFF out of range".to_string())
+}
+
+// get the top level chunks of the FORM
+fn get_iff_chunk_positions<R: Read + Seek + ?Sized>(f: &mut R) -> Result<IffForm> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut form_id = [0u8; 4];
+    f.read_exact(&mut form_id)
+        .map_err(|_err| Error::InvalidAsset("AIFF invalid".to_string()))?;
+    let form_size = f.read_u32::<BigEndian>().map_err(aiff_err)? as u64;
+    let mut form_type = [0u8; 4];
+    f.read_exact(&mut form_type).map_err(aiff_err)?;
+
+    if form_id != FORM_ID || (form_type != AIFF_TYPE && form_type != AIFC_TYPE) {
+        return Err(Error::InvalidAsset("AIFF invalid".to_string()));
+    }
+
+    // tolerate a FORM size that runs past the end of the stream
+    let form_end = std::cmp::min(CHUNK_HDR_LEN + form_size, stream_len);
+
+    let mut chunks = Vec::new();
+    let mut current_pos = FORM_HDR_LEN;
+    while current_pos + CHUNK_HDR_LEN <= form_end {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let mut id = [0u8; 4];
+        f.read_exact(&mut id).map_err(aiff_err)?;
+        let length = f.read_u32::<BigEndian>().map_err(aiff_err)? as u64;
+
+        if current_pos + CHUNK_HDR_LEN + length > stream_len {
+            return Err(Error::InvalidAsset("AIFF bad chunk size".to_string()));
+        }
+
+        let appl_signature = if id == APPL_CHUNK_ID && length >= 4 {
+            let mut signature = [0u8; 4];
+            f.read_exact(&mut signature).map_err(aiff_err)?;
+            Some(signature)
+        } else {
+            None
+        };
+
+        let chunk = IffChunkPos {
+            start: current_pos,
+            length,
+            id,
+            appl_signature,
+        };
+        current_pos = chunk.end();
+        chunks.push(chunk);
+    }
+
+    Ok(IffForm { form_type, chunks })
+}
+
+fn read_chunk_data<R: Read + Seek + ?Sized>(f: &mut R, chunk: &IffChunkPos) -> Result<Vec<u8>> {
+    let length = usize::value_from(chunk.length)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(chunk.start + CHUNK_HDR_LEN))?;
+    let mut data = vec![0u8; length];
+    f.read_exact(&mut data).map_err(aiff_err)?;
+
+    Ok(data)
+}
+
+fn write_chunk(output_stream: &mut dyn CAIReadWrite, id: &[u8; 4], data: &[u8]) -> Result<()> {
+    let length = u32::value_from(data.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    output_stream.write_all(id)?;
+    output_stream.write_u32::<BigEndian>(length)?;
+    output_stream.write_all(data)?;
+    if length & 1 == 1 {
+        output_stream.write_u8(0)?;
+    }
+
+    Ok(())
+}
+
+// Rewrite the FORM keeping the chunks not matched by `remove` and appending `new_chunks`
+// at the end.  Kept chunks are copied from the source without being loaded into memory.
+fn rewrite_form<P>(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    remove: P,
+    new_chunks: &[([u8; 4], Vec<u8>)],
+) -> Result<()>
+where
+    P: Fn(&IffChunkPos) -> bool,
+{
+    let form = get_iff_chunk_positions(input_stream)?;
+    let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+    let kept: Vec<&IffChunkPos> = form.chunks.iter().filter(|c| !remove(c)).collect();
+
+    let form_size = 4
+        + kept.iter().map(|c| c.end() - c.start).sum::<u64>()
+        + new_chunks
+            .iter()
+            .map(|(_, data)| {
+                let len = data.len() as u64;
+                CHUNK_HDR_LEN + len + (len & 1)
+            })
+            .sum::<u64>();
+    let form_size = u32::value_from(form_size)
+        .map_err(|_err| Error::InvalidAsset("AIFF too large".to_owned()))?;
+
+    output_stream.rewind()?;
+    output_stream.write_all(&FORM_ID)?;
+    output_stream.write_u32::<BigEndian>(form_size)?;
+    output_stream.write_all(&form.form_type)?;
+
+    for chunk in kept {
+        // the final pad byte may be missing at the end of the file
+        let copy_end = std::cmp::min(chunk.end(), stream_len);
+
+        input_stream.seek(SeekFrom::Start(chunk.start))?;
+        let copied = std::io::copy(
+            &mut input_stream.take(copy_end - chunk.start),
+            output_stream,
+        )?;
+        if copied < chunk.end() - chunk.start {
+            output_stream.write_u8(0)?;
+        }
+    }
+
+    for (id, data) in new_chunks {
+        write_chunk(output_stream, id, data)?;
+    }
+
+    Ok(())
+}
+
+fn get_manifest_chunk<R: Read + Seek + ?Sized>(f: &mut R) -> Result<IffChunkPos> {
+    let form = get_iff_chunk_positions(f)?;
+
+    let mut c2pa_chunks = form.chunks.into_iter().filter(|c| c.id == C2PA_CHUNK_ID);
+    let c2pa_chunk = c2pa_chunks.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_chunks.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    Ok(c2pa_chunk)
+}
+
+fn add_required_chunks(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let aio = AiffIO {};
+
+    match aio.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            aio.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct AiffIO {}
+
+impl CAIReader for AiffIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let c2pa_chunk = get_manifest_chunk(input_stream)?;
+        read_chunk_data(input_stream, &c2pa_chunk)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, input_stream: &mut dyn CAIRead) -> Option<String> {
+        let form = get_iff_chunk_positions(input_stream).ok()?;
+        let xmp_chunk = form.chunks.into_iter().find(|c| c.is_xmp())?;
+
+        let data = read_chunk_data(input_stream, &xmp_chunk).ok()?;
+        String::from_utf8(data[XMP_SIGNATURE.len()..].to_vec()).ok()
+    }
+}
+
+impl CAIWriter for AiffIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        // place at the end for maximum compatibility
+        rewrite_form(
+            input_stream,
+            output_stream,
+            |c| c.id == C2PA_CHUNK_ID,
+            &[(C2PA_CHUNK_ID, store_bytes.to_vec())],
+        )
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_chunks(input_stream, &mut output_stream)?;
+
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let c2pa_chunk = get_manifest_chunk(&mut output_stream)?;
+        let start = usize::value_from(c2pa_chunk.start)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let end = usize::value_from(c2pa_chunk.end())
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let file_end = output_stream.get_ref().len();
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of chunks before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_form(input_stream, output_stream, |c| c.id == C2PA_CHUNK_ID, &[])
+    }
+}
+
+impl AssetIO for AiffIO {
+    fn new(_asset_type: &str) -> Self {
+        AiffIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(AiffIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(AiffIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for AiffIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let c2pa_chunk = get_manifest_chunk(&mut asset)?;
+
+        if store_bytes.len() as u64 == c2pa_chunk.length {
+            asset.seek(SeekFrom::Start(c2pa_chunk.start + CHUNK_HDR_LEN))?; // skip 8 byte chunk header
+            asset.write_all(store_bytes)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+impl RemoteRefEmbed for AiffIO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.embed_reference_to_stream(&mut input_stream, &mut temp_file, embed_ref)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let xmp = match self.read_xmp(input_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+                let new_xmp = add_provenance(&xmp, &manifest_uri)?;
+
+                let mut xmp_data = XMP_SIGNATURE.to_vec();
+                xmp_data.extend_from_slice(new_xmp.as_bytes());
+                let mut new_chunks = vec![(APPL_CHUNK_ID, xmp_data)];
+
+                // keep the manifest store as the last chunk
+                match self.read_cai(input_stream) {
+                    Ok(manifest) => new_chunks.push((C2PA_CHUNK_ID, manifest)),
+                    Err(Error::JumbfNotFound) => (),
+                    Err(e) => return Err(e),
+                }
+
+                rewrite_form(
+                    input_stream,
+                    output_stream,
+                    |c| c.is_xmp() || c.id == C2PA_CHUNK_ID,
+                    &new_chunks,
+                )
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+impl AssetBoxHash for AiffIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        let form = get_iff_chunk_positions(input_stream)?;
+        let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+        let make_box_map = |name: String, start: u64, end: u64| -> Result<BoxMap> {
+            Ok(BoxMap {
+                names: vec![name],
+                alg: None,
+                hash: ByteBuf::from(Vec::new()),
+                pad: ByteBuf::from(Vec::new()),
+                range_start: usize::value_from(start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                range_len: usize::value_from(end - start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+            })
+        };
+
+        let mut box_maps = Vec::new();
+
+        // FORM header and form type
+        box_maps.push(make_box_map(
+            String::from_utf8_lossy(&FORM_ID).to_string(),
+            0,
+            FORM_HDR_LEN,
+        )?);
+
+        for chunk in &form.chunks {
+            let name = if chunk.id == C2PA_CHUNK_ID {
+                C2PA_BOXHASH.to_string()
+            } else {
+                String::from_utf8_lossy(&chunk.id).to_string()
+            };
+
+            box_maps.push(make_box_map(
+                name,
+                chunk.start,
+                std::cmp::min(chunk.end(), stream_len),
+            )?);
+        }
+
+        Ok(box_maps)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::{
+        test::{fixture_path, temp_dir_path},
+        xmp_inmemory_utils::extract_provenance,
+    };
+
+    fn chunk_ids(data: &[u8]) -> Vec<[u8; 4]> {
+        let form = get_iff_chunk_positions(&mut Cursor::new(data)).unwrap();
+        form.chunks.iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn test_aiff_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.aiff");
+        assert_eq!(chunk_ids(source), vec![*b"COMM", *b"SSND"]);
+
+        let source = include_bytes!("../../tests/fixtures/sample1.aifc");
+        let form = get_iff_chunk_positions(&mut Cursor::new(source)).unwrap();
+        assert_eq!(form.form_type, AIFC_TYPE);
+        assert_eq!(chunk_ids(source), vec![*b"FVER", *b"COMM", *b"SSND"]);
+    }
+
+    #[test]
+    fn test_write_aiff() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("sample1.aiff");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-aiff.aiff");
+        std::fs::copy(source, &output).unwrap();
+
+        let aiff_io = AiffIO::new("aiff");
+        aiff_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(aiff_io.read_cai_store(&output).unwrap(), more_data);
+
+        // odd length data is padded and the FORM size updated
+        let output_bytes = std::fs::read(&output).unwrap();
+        assert_eq!(output_bytes.len() % 2, 0);
+        let form_size = u32::from_be_bytes(output_bytes[4..8].try_into().unwrap());
+        assert_eq!(form_size as usize + 8, output_bytes.len());
+        assert_eq!(chunk_ids(&output_bytes), vec![*b"COMM", *b"SSND", *b"C2PA"]);
+    }
+
+    #[test]
+    fn test_write_aifc_stream() {
+        let source = include_bytes!("../../tests/fixtures/sample1.aifc");
+        let aiff_io = AiffIO::new("aifc");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        aiff_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert_eq!(aiff_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+
+        // replace the existing store
+        let mut replaced_stream = Cursor::new(Vec::new());
+        aiff_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &[4, 5])
+            .unwrap();
+        assert_eq!(aiff_io.read_cai(&mut replaced_stream).unwrap(), vec![4, 5]);
+        assert_eq!(
+            chunk_ids(replaced_stream.get_ref()),
+            vec![*b"FVER", *b"COMM", *b"SSND", *b"C2PA"]
+        );
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/sample1.wav");
+        let aiff_io = AiffIO::new("aiff");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            aiff_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_patch_write_aiff() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("sample1.aiff");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-aiff.aiff");
+        std::fs::copy(source, &output).unwrap();
+
+        let aiff_io = AiffIO::new("aiff");
+        aiff_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        aiff_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(aiff_io.read_cai_store(&output).unwrap(), new_data);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.aiff");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-aiff.aiff");
+        std::fs::copy(&source, &output).unwrap();
+
+        let aiff_io = AiffIO::new("aiff");
+        aiff_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        aiff_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match aiff_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let source = include_bytes!("../../tests/fixtures/sample1.aiff");
+        let aiff_io = AiffIO::new("aiff");
+
+        // manifest first so we can check that it stays the last chunk
+        let mut signed_stream = Cursor::new(Vec::new());
+        aiff_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut signed_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert!(aiff_io.read_xmp(&mut signed_stream).is_none());
+
+        let mut output_stream = Cursor::new(Vec::new());
+        aiff_io
+            .embed_reference_to_stream(
+                &mut signed_stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let mut updated_stream = Cursor::new(Vec::new());
+        aiff_io
+            .embed_reference_to_stream(
+                &mut output_stream,
+                &mut updated_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/updated.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let xmp = aiff_io.read_xmp(&mut updated_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/updated.c2pa"
+        );
+        assert_eq!(
+            chunk_ids(updated_stream.get_ref()),
+            vec![*b"COMM", *b"SSND", *b"APPL", *b"C2PA"]
+        );
+        assert_eq!(
+            aiff_io.read_cai(&mut updated_stream).unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.aifc");
+        let aiff_io = AiffIO::new("aifc");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        aiff_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let box_map = aiff_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(names, vec!["FORM", "FVER", "COMM", "SSND", C2PA_BOXHASH]);
+
+        // box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+}
@@ -28,7 +28,7 @@ use crate::{
     assertions::{BmffMerkleMap, ExclusionsMap},
     asset_io::{
         rename_or_copy, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
-        HashObjectPositions, RemoteRefEmbed, RemoteRefEmbedType,
+        HashObjectPositions, ManifestBinding, RemoteRefEmbed, RemoteRefEmbedType,
     },
     error::{Error, Result},
     utils::{
@@ -1267,6 +1267,10 @@ impl CAIReader for BmffIO {
 }
 
 impl AssetIO for BmffIO {
+    fn manifest_binding(&self) -> ManifestBinding {
+        ManifestBinding::BmffHash
+    }
+
     fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
         Some(self)
     }
@@ -16,7 +16,7 @@ use std::{fs::File, path::Path};
 use crate::{
     asset_io::{
         AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter, ComposedManifestRef,
-        HashBlockObjectType, HashObjectPositions,
+        HashBlockObjectType, HashObjectPositions, ManifestBinding,
     },
     error::{Error, Result},
 };
@@ -128,6 +128,10 @@ impl AssetIO for C2paIO {
         &SUPPORTED_TYPES
     }
 
+    fn manifest_binding(&self) -> ManifestBinding {
+        ManifestBinding::Sidecar
+    }
+
     fn composed_data_ref(&self) -> Option<&dyn ComposedManifestRef> {
         Some(self)
     }
@@ -0,0 +1,806 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! OpenEXR support for single and multi-part files.
+//!
+//! The manifest store is kept in a custom `c2pa` attribute at the end of the first part's
+//! header.  Readers skip attribute types they do not know.  The chunk offset tables that
+//! follow the headers hold absolute file positions, so every offset is moved by the change
+//! in header size whenever the attribute is added, replaced or removed.
+
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
+use tempfile::Builder;
+
+use crate::{
+    asset_io::{
+        rename_or_copy, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter, HashBlockObjectType,
+        HashObjectPositions,
+    },
+    error::{Error, Result},
+};
+
+static SUPPORTED_TYPES: [&str; 2] = ["exr", "image/x-exr"];
+
+const EXR_MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const EXR_VERSION: u32 = 2;
+const EXR_HDR_LEN: u64 = 8;
+
+// version field flags
+const VERSION_MASK: u32 = 0xff;
+const TILED_FLAG: u32 = 0x200;
+const NON_IMAGE_FLAG: u32 = 0x800;
+const MULTIPART_FLAG: u32 = 0x1000;
+
+// attribute names and types are limited to 255 bytes with the long names flag
+const MAX_NAME_LEN: usize = 256;
+
+const C2PA_ATTR_NAME: &str = "c2pa";
+const C2PA_ATTR_TYPE: &str = "c2paManifestStore";
+
+// tile level modes
+const ONE_LEVEL: u8 = 0;
+const MIPMAP_LEVELS: u8 = 1;
+const RIPMAP_LEVELS: u8 = 2;
+
+#[derive(Clone, Debug)]
+struct ExrAttribute {
+    pub start: u64,
+    pub name: String,
+    pub value_start: u64,
+    pub value_len: u64,
+}
+
+impl ExrAttribute {
+    pub fn end(&self) -> u64 {
+        self.value_start + self.value_len
+    }
+
+    fn is_c2pa(&self) -> bool {
+        self.name == C2PA_ATTR_NAME
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ExrPart {
+    pub attributes: Vec<ExrAttribute>,
+    pub chunk_count: u64,
+}
+
+#[derive(Clone, Debug)]
+struct ExrLayout {
+    pub flags: u32,
+    pub parts: Vec<ExrPart>,
+    pub headers_end: u64,
+    pub offset_tables_end: u64,
+}
+
+impl ExrLayout {
+    fn is_multipart(&self) -> bool {
+        self.flags & MULTIPART_FLAG != 0
+    }
+
+    fn c2pa_attributes(&self) -> impl Iterator<Item = &ExrAttribute> {
+        self.parts
+            .iter()
+            .flat_map(|p| p.attributes.iter())
+            .filter(|a| a.is_c2pa())
+    }
+}
+
+fn exr_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("EXR out of range".to_string())
+}
+
+fn read_null_terminated<R: Read + ?Sized>(f: &mut R) -> Result<String> {
+    let mut bytes = Vec::new();
+    loop {
+        let b = f.read_u8().map_err(exr_err)?;
+        if b == 0 {
+            break;
+        }
+        bytes.push(b);
+        if bytes.len() >= MAX_NAME_LEN {
+            return Err(Error::InvalidAsset("EXR bad attribute name".to_string()));
+        }
+    }
+
+    String::from_utf8(bytes)
+        .map_err(|_err| Error::InvalidAsset("EXR bad attribute name".to_string()))
+}
+
+fn read_attribute_value<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    attr: &ExrAttribute,
+) -> Result<Vec<u8>> {
+    let len = usize::value_from(attr.value_len)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(attr.value_start))?;
+    let mut value = vec![0u8; len];
+    f.read_exact(&mut value).map_err(exr_err)?;
+
+    Ok(value)
+}
+
+// read the attributes of one header, up to and including the terminating null byte
+fn read_header<R: Read + Seek + ?Sized>(f: &mut R, stream_len: u64) -> Result<Vec<ExrAttribute>> {
+    let mut attributes = Vec::new();
+
+    loop {
+        let start = f.stream_position()?;
+        let name = read_null_terminated(f)?;
+        if name.is_empty() {
+            break;
+        }
+
+        let _attr_type = read_null_terminated(f)?;
+        let size = f.read_i32::<LittleEndian>().map_err(exr_err)?;
+        let value_len = u64::try_from(size)
+            .map_err(|_err| Error::InvalidAsset("EXR bad attribute size".to_string()))?;
+        let value_start = f.stream_position()?;
+
+        if value_start + value_len > stream_len {
+            return Err(Error::InvalidAsset("EXR bad attribute size".to_string()));
+        }
+
+        attributes.push(ExrAttribute {
+            start,
+            name,
+            value_start,
+            value_len,
+        });
+
+        f.seek(SeekFrom::Start(value_start + value_len))?;
+    }
+
+    Ok(attributes)
+}
+
+// number of scan lines stored in each chunk for a compression method
+fn lines_per_chunk(compression: u8) -> Result<u64> {
+    match compression {
+        0..=2 => Ok(1),      // none, RLE, ZIPS
+        3 | 5 => Ok(16),     // ZIP, PXR24
+        4 | 6..=8 => Ok(32), // PIZ, B44, B44A, DWAA
+        9 => Ok(256),        // DWAB
+        _ => Err(Error::InvalidAsset("EXR unknown compression".to_string())),
+    }
+}
+
+fn level_count(size: u64, round_up: bool) -> u32 {
+    let floor_log2 = 63 - size.max(1).leading_zeros();
+    if round_up && !size.is_power_of_two() {
+        floor_log2 + 2
+    } else {
+        floor_log2 + 1
+    }
+}
+
+fn level_size(size: u64, level: u32, round_up: bool) -> u64 {
+    let mut level_size = size >> level;
+    if round_up && size & ((1 << level) - 1) != 0 {
+        level_size += 1;
+    }
+    level_size.max(1)
+}
+
+// number of tiles in a tiled part with the given tile description
+fn tile_count(width: u64, height: u64, tile_desc: &[u8]) -> Result<u64> {
+    if tile_desc.len() < 9 {
+        return Err(Error::InvalidAsset("EXR bad tile description".to_string()));
+    }
+
+    let mut desc = Cursor::new(tile_desc);
+    let tile_width = desc.read_u32::<LittleEndian>().map_err(exr_err)? as u64;
+    let tile_height = desc.read_u32::<LittleEndian>().map_err(exr_err)? as u64;
+    let mode = desc.read_u8().map_err(exr_err)?;
+    let level_mode = mode & 0x0f;
+    let round_up = mode & 0x10 != 0;
+
+    if tile_width == 0 || tile_height == 0 {
+        return Err(Error::InvalidAsset("EXR bad tile description".to_string()));
+    }
+
+    let tiles = |w: u64, h: u64| w.div_ceil(tile_width) * h.div_ceil(tile_height);
+
+    match level_mode {
+        ONE_LEVEL => Ok(tiles(width, height)),
+        MIPMAP_LEVELS => {
+            let levels = level_count(width.max(height), round_up);
+            Ok((0..levels)
+                .map(|l| {
+                    tiles(
+                        level_size(width, l, round_up),
+                        level_size(height, l, round_up),
+                    )
+                })
+                .sum())
+        }
+        RIPMAP_LEVELS => {
+            let x_levels = level_count(width, round_up);
+            let y_levels = level_count(height, round_up);
+            Ok((0..y_levels)
+                .flat_map(|ly| (0..x_levels).map(move |lx| (lx, ly)))
+                .map(|(lx, ly)| {
+                    tiles(
+                        level_size(width, lx, round_up),
+                        level_size(height, ly, round_up),
+                    )
+                })
+                .sum())
+        }
+        _ => Err(Error::InvalidAsset("EXR bad tile level mode".to_string())),
+    }
+}
+
+// number of entries in the offset table of a part
+fn chunk_count<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    attributes: &[ExrAttribute],
+    flags: u32,
+) -> Result<u64> {
+    let find = |name: &str| attributes.iter().find(|a| a.name == name);
+
+    // required for multi-part and deep files
+    if let Some(attr) = find("chunkCount") {
+        let value = read_attribute_value(f, attr)?;
+        let count = Cursor::new(value)
+            .read_i32::<LittleEndian>()
+            .map_err(exr_err)?;
+        return u64::try_from(count)
+            .map_err(|_err| Error::InvalidAsset("EXR bad chunk count".to_string()));
+    }
+
+    if flags & (MULTIPART_FLAG | NON_IMAGE_FLAG) != 0 {
+        return Err(Error::InvalidAsset("EXR missing chunkCount".to_string()));
+    }
+
+    let data_window =
+        find("dataWindow").ok_or(Error::InvalidAsset("EXR missing dataWindow".to_string()))?;
+    let mut window = Cursor::new(read_attribute_value(f, data_window)?);
+    let x_min = window.read_i32::<LittleEndian>().map_err(exr_err)? as i64;
+    let y_min = window.read_i32::<LittleEndian>().map_err(exr_err)? as i64;
+    let x_max = window.read_i32::<LittleEndian>().map_err(exr_err)? as i64;
+    let y_max = window.read_i32::<LittleEndian>().map_err(exr_err)? as i64;
+
+    let width = u64::try_from(x_max - x_min + 1)
+        .map_err(|_err| Error::InvalidAsset("EXR bad dataWindow".to_string()))?;
+    let height = u64::try_from(y_max - y_min + 1)
+        .map_err(|_err| Error::InvalidAsset("EXR bad dataWindow".to_string()))?;
+
+    if flags & TILED_FLAG != 0 {
+        let tiles = find("tiles").ok_or(Error::InvalidAsset("EXR missing tiles".to_string()))?;
+        tile_count(width, height, &read_attribute_value(f, tiles)?)
+    } else {
+        let compression = find("compression")
+            .ok_or(Error::InvalidAsset("EXR missing compression".to_string()))?;
+        let compression = *read_attribute_value(f, compression)?
+            .first()
+            .ok_or(Error::InvalidAsset("EXR bad compression".to_string()))?;
+        Ok(height.div_ceil(lines_per_chunk(compression)?))
+    }
+}
+
+// get the headers and location of the offset tables
+fn get_exr_layout<R: Read + Seek + ?Sized>(f: &mut R) -> Result<ExrLayout> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic)
+        .map_err(|_err| Error::InvalidAsset("EXR invalid".to_string()))?;
+    let flags = f.read_u32::<LittleEndian>().map_err(exr_err)?;
+    if magic != EXR_MAGIC || flags & VERSION_MASK != EXR_VERSION {
+        return Err(Error::InvalidAsset("EXR invalid".to_string()));
+    }
+
+    // a multi-part file ends its list of headers with an empty header
+    let mut headers = Vec::new();
+    loop {
+        let attributes = read_header(f, stream_len)?;
+        if attributes.is_empty() {
+            if flags & MULTIPART_FLAG == 0 || headers.is_empty() {
+                return Err(Error::InvalidAsset("EXR empty header".to_string()));
+            }
+            break;
+        }
+
+        headers.push(attributes);
+        if flags & MULTIPART_FLAG == 0 {
+            break;
+        }
+    }
+    let headers_end = f.stream_position()?;
+
+    let mut parts = Vec::new();
+    for attributes in headers {
+        let chunk_count = chunk_count(f, &attributes, flags)?;
+        parts.push(ExrPart {
+            attributes,
+            chunk_count,
+        });
+    }
+
+    let offset_tables_end = parts
+        .iter()
+        .try_fold(headers_end, |pos, p| {
+            p.chunk_count
+                .checked_mul(8)
+                .and_then(|len| pos.checked_add(len))
+        })
+        .filter(|end| *end <= stream_len)
+        .ok_or(Error::InvalidAsset("EXR bad offset table".to_string()))?;
+
+    Ok(ExrLayout {
+        flags,
+        parts,
+        headers_end,
+        offset_tables_end,
+    })
+}
+
+fn make_c2pa_attribute(store_bytes: &[u8]) -> Result<Vec<u8>> {
+    let size = i32::value_from(store_bytes.len())
+        .map_err(|_err| Error::InvalidAsset("EXR manifest too large".to_string()))?;
+
+    let mut attr = Vec::with_capacity(store_bytes.len() + 32);
+    attr.extend_from_slice(C2PA_ATTR_NAME.as_bytes());
+    attr.push(0);
+    attr.extend_from_slice(C2PA_ATTR_TYPE.as_bytes());
+    attr.push(0);
+    attr.write_i32::<LittleEndian>(size)?;
+    attr.extend_from_slice(store_bytes);
+
+    Ok(attr)
+}
+
+// rewrite the headers without C2PA attributes, adding the new attribute to the first part,
+// then shift the offset tables by the change in header size
+fn rewrite_exr(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    new_attribute: Option<&[u8]>,
+) -> Result<()> {
+    let layout = get_exr_layout(input_stream)?;
+
+    let mut headers = Vec::new();
+    input_stream.rewind()?;
+    input_stream
+        .take(EXR_HDR_LEN)
+        .read_to_end(&mut headers)
+        .map_err(exr_err)?;
+
+    for (index, part) in layout.parts.iter().enumerate() {
+        for attr in part.attributes.iter().filter(|a| !a.is_c2pa()) {
+            input_stream.seek(SeekFrom::Start(attr.start))?;
+            input_stream
+                .take(attr.end() - attr.start)
+                .read_to_end(&mut headers)
+                .map_err(exr_err)?;
+        }
+
+        if index == 0 {
+            if let Some(new_attribute) = new_attribute {
+                headers.extend_from_slice(new_attribute);
+            }
+        }
+        headers.push(0);
+    }
+    if layout.is_multipart() {
+        headers.push(0);
+    }
+
+    let delta = headers.len() as i64 - layout.headers_end as i64;
+
+    output_stream.rewind()?;
+    output_stream.write_all(&headers)?;
+
+    // chunk offsets are absolute, a zero offset marks a chunk that was never written
+    input_stream.seek(SeekFrom::Start(layout.headers_end))?;
+    let entries = (layout.offset_tables_end - layout.headers_end) / 8;
+    for _ in 0..entries {
+        let offset = input_stream.read_u64::<LittleEndian>().map_err(exr_err)?;
+        let new_offset = if offset == 0 {
+            0
+        } else {
+            offset
+                .checked_add_signed(delta)
+                .ok_or(Error::InvalidAsset("EXR bad chunk offset".to_string()))?
+        };
+        output_stream.write_u64::<LittleEndian>(new_offset)?;
+    }
+
+    // chunk data is unchanged
+    std::io::copy(input_stream, output_stream)?;
+
+    Ok(())
+}
+
+fn get_cai_data<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<u8>> {
+    let layout = get_exr_layout(f)?;
+
+    let mut c2pa_attributes = layout.c2pa_attributes();
+    let c2pa_attr = c2pa_attributes.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_attributes.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    read_attribute_value(f, c2pa_attr)
+}
+
+pub struct ExrIO {}
+
+impl CAIReader for ExrIO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let cai_data = get_cai_data(asset_reader)?;
+        Ok(cai_data)
+    }
+
+    fn read_xmp(&self, _asset_reader: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for ExrIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let attribute = make_c2pa_attribute(store_bytes)?;
+        rewrite_exr(input_stream, output_stream, Some(&attribute))
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        // Ensure the stream has a manifest store so we can generate the required offsets.
+        let has_manifest = match get_cai_data(input_stream) {
+            Ok(_) | Err(Error::TooManyManifestStores) => true,
+            Err(Error::JumbfNotFound) => false,
+            Err(e) => return Err(e),
+        };
+
+        let mut exr_stream = Cursor::new(Vec::new());
+        if has_manifest {
+            input_stream.rewind()?;
+            std::io::copy(input_stream, &mut exr_stream)?;
+        } else {
+            self.write_cai(input_stream, &mut exr_stream, &[1, 2, 3, 4])?;
+        }
+
+        let layout = get_exr_layout(&mut exr_stream)?;
+        let file_end = exr_stream.get_ref().len();
+
+        let c2pa_attr = layout
+            .c2pa_attributes()
+            .next()
+            .ok_or(Error::JumbfNotFound)?;
+
+        let start = usize::value_from(c2pa_attr.start)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+        let end = usize::value_from(c2pa_attr.end())
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of the header before the manifest store
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from the manifest store to end, including the offset tables
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_exr(input_stream, output_stream, None)
+    }
+}
+
+impl AssetIO for ExrIO {
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        self.get_object_locations_from_stream(&mut file)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn new(_asset_type: &str) -> Self
+    where
+        Self: Sized,
+    {
+        ExrIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(ExrIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(ExrIO::new(asset_type)))
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    // (part number, first scan line) of every chunk the offset tables point to
+    fn chunk_targets(exr: &[u8]) -> Vec<(i32, i32)> {
+        let mut stream = Cursor::new(exr);
+        let layout = get_exr_layout(&mut stream).unwrap();
+
+        stream.seek(SeekFrom::Start(layout.headers_end)).unwrap();
+        let offsets: Vec<u64> = (0..(layout.offset_tables_end - layout.headers_end) / 8)
+            .map(|_| stream.read_u64::<LittleEndian>().unwrap())
+            .collect();
+
+        offsets
+            .iter()
+            .map(|offset| {
+                stream.seek(SeekFrom::Start(*offset)).unwrap();
+                let part = if layout.is_multipart() {
+                    stream.read_i32::<LittleEndian>().unwrap()
+                } else {
+                    0
+                };
+                (part, stream.read_i32::<LittleEndian>().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exr_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.exr");
+        let layout = get_exr_layout(&mut Cursor::new(source)).unwrap();
+        assert_eq!(layout.parts.len(), 1);
+        assert_eq!(layout.parts[0].chunk_count, 2);
+        assert_eq!(chunk_targets(source), vec![(0, 0), (0, 1)]);
+
+        let source = include_bytes!("../../tests/fixtures/sample1_multipart.exr");
+        let layout = get_exr_layout(&mut Cursor::new(source)).unwrap();
+        assert_eq!(layout.parts.len(), 2);
+        assert!(layout.parts.iter().all(|p| p.chunk_count == 2));
+        assert_eq!(chunk_targets(source), vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn test_tile_count() {
+        let desc = |mode: u8| {
+            let mut desc = Vec::new();
+            desc.write_u32::<LittleEndian>(32).unwrap();
+            desc.write_u32::<LittleEndian>(32).unwrap();
+            desc.push(mode);
+            desc
+        };
+
+        // 100x50 image in 32x32 tiles
+        assert_eq!(tile_count(100, 50, &desc(ONE_LEVEL)).unwrap(), 8);
+
+        // levels 100x50, 50x25, 25x12, 12x6, 6x3, 3x1, 1x1
+        assert_eq!(
+            tile_count(100, 50, &desc(MIPMAP_LEVELS)).unwrap(),
+            8 + 2 + 5
+        );
+
+        // rounding up gives levels 100x50, 50x25, 25x13, 13x7, 7x4, 4x2, 2x1, 1x1
+        assert_eq!(
+            tile_count(100, 50, &desc(MIPMAP_LEVELS | 0x10)).unwrap(),
+            8 + 2 + 6
+        );
+
+        // 7 x levels by 6 y levels
+        let ripmap = tile_count(100, 50, &desc(RIPMAP_LEVELS)).unwrap();
+        assert_eq!(
+            ripmap,
+            (4 + 2 + 1 + 1 + 1 + 1 + 1) * (2 + 1 + 1 + 1 + 1 + 1)
+        );
+    }
+
+    #[test]
+    fn test_write_cai_shifts_offsets() {
+        let exr_io = ExrIO {};
+
+        for fixture in ["sample1.exr", "sample1_multipart.exr"] {
+            let source = std::fs::read(fixture_path(fixture)).unwrap();
+            let mut stream = Cursor::new(source.clone());
+
+            assert!(matches!(
+                exr_io.read_cai(&mut stream),
+                Err(Error::JumbfNotFound)
+            ));
+
+            let store = vec![0x5a; 100];
+            let mut output_stream = Cursor::new(Vec::new());
+            exr_io
+                .write_cai(&mut stream, &mut output_stream, &store)
+                .unwrap();
+            assert_eq!(exr_io.read_cai(&mut output_stream).unwrap(), store);
+
+            // the offset tables still point at the same chunks
+            let output = output_stream.get_ref();
+            assert_eq!(chunk_targets(output), chunk_targets(&source));
+
+            // the attribute goes in the first part
+            let layout = get_exr_layout(&mut output_stream).unwrap();
+            assert!(layout.parts[0].attributes.last().unwrap().is_c2pa());
+            assert_eq!(layout.c2pa_attributes().count(), 1);
+
+            // a smaller replacement moves them back
+            let mut replaced_stream = Cursor::new(Vec::new());
+            exr_io
+                .write_cai(&mut output_stream, &mut replaced_stream, &[1, 2, 3, 4])
+                .unwrap();
+            assert_eq!(
+                exr_io.read_cai(&mut replaced_stream).unwrap(),
+                vec![1, 2, 3, 4]
+            );
+            assert_eq!(
+                chunk_targets(replaced_stream.get_ref()),
+                chunk_targets(&source)
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mut stream = Cursor::new(source.to_vec());
+        let exr_io = ExrIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            exr_io.write_cai(&mut stream, &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_stream_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1_multipart.exr");
+        let mut stream = Cursor::new(source.to_vec());
+        let exr_io = ExrIO {};
+
+        let positions = exr_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+
+        let mut output_stream = Cursor::new(Vec::new());
+        exr_io
+            .write_cai(&mut stream, &mut output_stream, &[1, 2, 3, 4])
+            .unwrap();
+        let output = output_stream.into_inner();
+
+        let cai_pos = positions
+            .iter()
+            .find(|pos| pos.htype == HashBlockObjectType::Cai)
+            .unwrap();
+        let attr = make_c2pa_attribute(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(
+            &output[cai_pos.offset..cai_pos.offset + cai_pos.length],
+            &attr
+        );
+
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, output.len());
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let exr_io = ExrIO {};
+        let temp_dir = tempdir().unwrap();
+
+        for fixture in ["sample1.exr", "sample1_multipart.exr"] {
+            let output = temp_dir_path(&temp_dir, fixture);
+            std::fs::copy(fixture_path(fixture), &output).unwrap();
+
+            exr_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+            assert_eq!(exr_io.read_cai_store(&output).unwrap(), vec![1, 2, 3, 4]);
+
+            exr_io.remove_cai_store(&output).unwrap();
+
+            // read back in asset, JumbfNotFound is expected since it was removed
+            match exr_io.read_cai_store(&output) {
+                Err(Error::JumbfNotFound) => (),
+                _ => unreachable!(),
+            }
+
+            let source_bytes = std::fs::read(fixture_path(fixture)).unwrap();
+            assert_eq!(std::fs::read(&output).unwrap(), source_bytes);
+        }
+    }
+}
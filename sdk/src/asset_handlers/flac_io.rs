@@ -0,0 +1,747 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader,
+        CAIWriter, ComposedManifestRef, HashBlockObjectType, HashObjectPositions,
+    },
+    error::{Error, Result},
+};
+
+static SUPPORTED_TYPES: [&str; 3] = ["flac", "audio/flac", "audio/x-flac"];
+
+const FLAC_MARKER: [u8; 4] = *b"fLaC";
+const FLAC_MARKER_LEN: u64 = 4;
+
+// last-metadata-block flag (1 bit) + block type (7 bits) + length (24 bits)
+const BLOCK_HDR_LEN: u64 = 4;
+const MAX_BLOCK_LEN: usize = 0x00ff_ffff;
+const LAST_BLOCK_FLAG: u8 = 0x80;
+
+const STREAMINFO: u8 = 0;
+const PADDING: u8 = 1;
+const APPLICATION: u8 = 2;
+const SEEKTABLE: u8 = 3;
+const VORBIS_COMMENT: u8 = 4;
+const CUESHEET: u8 = 5;
+const PICTURE: u8 = 6;
+
+// application id of the APPLICATION block holding the manifest store
+const C2PA_APP_ID: [u8; 4] = *b"c2pa";
+const APP_ID_LEN: u64 = 4;
+
+#[derive(Clone, Debug)]
+struct FlacBlockPos {
+    pub start: u64,
+    pub length: u64,
+    pub block_type: u8,
+    pub app_id: Option<[u8; 4]>,
+}
+
+impl FlacBlockPos {
+    pub fn end(&self) -> u64 {
+        self.start + BLOCK_HDR_LEN + self.length
+    }
+
+    fn is_c2pa(&self) -> bool {
+        self.block_type == APPLICATION && self.app_id == Some(C2PA_APP_ID)
+    }
+
+    // name used for the block in a BoxHash map
+    fn box_name(&self) -> String {
+        if self.is_c2pa() {
+            return C2PA_BOXHASH.to_string();
+        }
+
+        match self.block_type {
+            STREAMINFO => "STREAMINFO",
+            PADDING => "PADDING",
+            APPLICATION => "APPLICATION",
+            SEEKTABLE => "SEEKTABLE",
+            VORBIS_COMMENT => "VORBIS_COMMENT",
+            CUESHEET => "CUESHEET",
+            PICTURE => "PICTURE",
+            _ => "METADATA",
+        }
+        .to_string()
+    }
+}
+
+fn flac_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("FLAC out of range".to_string())
+}
+
+// get the metadata blocks, the audio frames start at the end of the last block
+fn get_flac_block_positions<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<FlacBlockPos>> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    let mut block_positions: Vec<FlacBlockPos> = Vec::new();
+
+    // check FLAC signature
+    let mut marker = [0u8; 4];
+    f.rewind()?;
+    f.read_exact(&mut marker)
+        .map_err(|_err| Error::InvalidAsset("FLAC invalid".to_string()))?;
+    if marker != FLAC_MARKER {
+        return Err(Error::InvalidAsset("FLAC invalid".to_string()));
+    }
+
+    let mut current_pos = FLAC_MARKER_LEN;
+    loop {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let flags = f.read_u8().map_err(flac_err)?;
+        let length = f.read_u24::<BigEndian>().map_err(flac_err)? as u64;
+        let block_type = flags & !LAST_BLOCK_FLAG;
+
+        let app_id = if block_type == APPLICATION && length >= APP_ID_LEN {
+            let mut app_id = [0u8; 4];
+            f.read_exact(&mut app_id).map_err(flac_err)?;
+            Some(app_id)
+        } else {
+            None
+        };
+
+        let fbp = FlacBlockPos {
+            start: current_pos,
+            length,
+            block_type,
+            app_id,
+        };
+        current_pos = fbp.end();
+        if current_pos > stream_len {
+            return Err(Error::InvalidAsset("FLAC out of range".to_string()));
+        }
+
+        block_positions.push(fbp);
+
+        if flags & LAST_BLOCK_FLAG != 0 {
+            break;
+        }
+    }
+
+    // STREAMINFO is mandatory and must be the first block
+    if block_positions[0].block_type != STREAMINFO {
+        return Err(Error::InvalidAsset("FLAC missing STREAMINFO".to_string()));
+    }
+
+    Ok(block_positions)
+}
+
+fn read_block_data<R: Read + Seek + ?Sized>(f: &mut R, block: &FlacBlockPos) -> Result<Vec<u8>> {
+    let length = usize::value_from(block.length)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(block.start + BLOCK_HDR_LEN))?;
+    let mut data = vec![0u8; length];
+    f.read_exact(&mut data).map_err(flac_err)?;
+
+    Ok(data)
+}
+
+fn make_block(block_type: u8, data: &[u8], is_last: bool) -> Result<Vec<u8>> {
+    if data.len() > MAX_BLOCK_LEN {
+        return Err(Error::EmbeddingError);
+    }
+
+    let mut block = Vec::with_capacity(data.len() + BLOCK_HDR_LEN as usize);
+    block.push(if is_last {
+        block_type | LAST_BLOCK_FLAG
+    } else {
+        block_type
+    });
+    block.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+    block.extend_from_slice(data);
+
+    Ok(block)
+}
+
+fn make_c2pa_block_data(store_bytes: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(store_bytes.len() + APP_ID_LEN as usize);
+    data.extend_from_slice(&C2PA_APP_ID);
+    data.extend_from_slice(store_bytes);
+    data
+}
+
+// Rewrite the metadata blocks, dropping any manifest store and inserting the new one (if any)
+// after STREAMINFO.  The audio frames are copied through unchanged.
+fn write_metadata_blocks(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    store_bytes: Option<&[u8]>,
+) -> Result<()> {
+    let bp = get_flac_block_positions(input_stream)?;
+    let audio_start = bp.last().map(|b| b.end()).ok_or(Error::EmbeddingError)?;
+
+    let mut blocks: Vec<(u8, Vec<u8>)> = Vec::new();
+    for block in bp.iter().filter(|b| !b.is_c2pa()) {
+        blocks.push((block.block_type, read_block_data(input_stream, block)?));
+    }
+
+    if let Some(store_bytes) = store_bytes {
+        blocks.insert(1, (APPLICATION, make_c2pa_block_data(store_bytes)));
+    }
+
+    output_stream.rewind()?;
+    output_stream.write_all(&FLAC_MARKER)?;
+
+    let last_index = blocks.len() - 1;
+    for (index, (block_type, data)) in blocks.iter().enumerate() {
+        output_stream.write_all(&make_block(*block_type, data, index == last_index)?)?;
+    }
+
+    // copy the audio frames
+    input_stream.seek(SeekFrom::Start(audio_start))?;
+    std::io::copy(input_stream, output_stream)?;
+
+    Ok(())
+}
+
+fn get_manifest_block<R: Read + Seek + ?Sized>(f: &mut R) -> Result<FlacBlockPos> {
+    let bp = get_flac_block_positions(f)?;
+
+    let mut c2pa_blocks = bp.into_iter().filter(|b| b.is_c2pa());
+    let c2pa_block = c2pa_blocks.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_blocks.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    Ok(c2pa_block)
+}
+
+fn add_required_block(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    match get_manifest_block(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            write_metadata_blocks(input_stream, output_stream, Some(&[1, 2, 3, 4]))
+            // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct FlacIO {}
+
+impl CAIReader for FlacIO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let c2pa_block = get_manifest_block(asset_reader)?;
+
+        let mut data = read_block_data(asset_reader, &c2pa_block)?;
+        data.drain(..APP_ID_LEN as usize);
+
+        Ok(data)
+    }
+
+    // FLAC has no XMP container
+    fn read_xmp(&self, _asset_reader: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for FlacIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        write_metadata_blocks(input_stream, output_stream, Some(store_bytes))
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        // Ensure the stream has the required block so we can generate the required offsets.
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_block(input_stream, &mut output_stream)?;
+
+        let c2pa_block = get_manifest_block(&mut output_stream)?;
+        let file_end = output_stream.get_ref().len();
+
+        let start = usize::value_from(c2pa_block.start)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+        let end = usize::value_from(c2pa_block.end())
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of blocks before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        write_metadata_blocks(input_stream, output_stream, None)
+    }
+}
+
+impl AssetIO for FlacIO {
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut stream = OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        self.get_object_locations_from_stream(&mut file)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut stream = OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn new(_asset_type: &str) -> Self
+    where
+        Self: Sized,
+    {
+        FlacIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(FlacIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(FlacIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn composed_data_ref(&self) -> Option<&dyn ComposedManifestRef> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for FlacIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let c2pa_block = get_manifest_block(&mut asset)?;
+
+        if store_bytes.len() as u64 + APP_ID_LEN == c2pa_block.length {
+            asset.seek(SeekFrom::Start(
+                c2pa_block.start + BLOCK_HDR_LEN + APP_ID_LEN,
+            ))?;
+            asset.write_all(store_bytes)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+impl AssetBoxHash for FlacIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        let bp = get_flac_block_positions(input_stream)?;
+        let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+        let make_box_map = |name: String, start: u64, end: u64| -> Result<BoxMap> {
+            Ok(BoxMap {
+                names: vec![name],
+                alg: None,
+                hash: ByteBuf::from(Vec::new()),
+                pad: ByteBuf::from(Vec::new()),
+                range_start: usize::value_from(start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                range_len: usize::value_from(end - start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+            })
+        };
+
+        let mut box_maps = Vec::new();
+
+        // add the stream marker
+        box_maps.push(make_box_map(
+            String::from_utf8_lossy(&FLAC_MARKER).to_string(),
+            0,
+            FLAC_MARKER_LEN,
+        )?);
+
+        // one box per metadata block
+        for block in &bp {
+            box_maps.push(make_box_map(block.box_name(), block.start, block.end())?);
+        }
+
+        // the audio frames are hashed as a single box
+        let audio_start = bp.last().map(|b| b.end()).unwrap_or(FLAC_MARKER_LEN);
+        if audio_start < stream_len {
+            box_maps.push(make_box_map("AUDIO".to_string(), audio_start, stream_len)?);
+        }
+
+        Ok(box_maps)
+    }
+}
+
+impl ComposedManifestRef for FlacIO {
+    // The composed block is never the last metadata block since it is placed after STREAMINFO.
+    fn compose_manifest(&self, manifest_data: &[u8], _format: &str) -> Result<Vec<u8>> {
+        make_block(APPLICATION, &make_c2pa_block_data(manifest_data), false)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    #[test]
+    fn test_flac_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let bp = get_flac_block_positions(&mut stream).unwrap();
+        let types: Vec<u8> = bp.iter().map(|b| b.block_type).collect();
+        assert_eq!(types, vec![STREAMINFO, VORBIS_COMMENT, PADDING]);
+        assert_eq!(bp[0].start, 4);
+        assert_eq!(bp[0].length, 34);
+    }
+
+    #[test]
+    fn test_write_cai_using_stream() {
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let mut stream = Cursor::new(source.to_vec());
+        let flac_io = FlacIO {};
+
+        assert!(matches!(
+            flac_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        let data_to_write: Vec<u8> = vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34];
+        let mut output_stream = Cursor::new(Vec::new());
+        flac_io
+            .write_cai(&mut stream, &mut output_stream, &data_to_write)
+            .unwrap();
+
+        assert_eq!(flac_io.read_cai(&mut output_stream).unwrap(), data_to_write);
+
+        // the manifest store follows STREAMINFO
+        let bp = get_flac_block_positions(&mut output_stream).unwrap();
+        assert!(bp[1].is_c2pa());
+
+        // replace the existing data
+        let mut replaced_stream = Cursor::new(Vec::new());
+        flac_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &[1, 2, 3])
+            .unwrap();
+        assert_eq!(
+            flac_io.read_cai(&mut replaced_stream).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            replaced_stream.get_ref().len(),
+            source.len() + (BLOCK_HDR_LEN + APP_ID_LEN) as usize + 3
+        );
+
+        // the audio frames are unchanged
+        let source_bp = get_flac_block_positions(&mut stream).unwrap();
+        let audio_start = source_bp.last().unwrap().end() as usize;
+        assert!(output_stream.get_ref().ends_with(&source[audio_start..]));
+    }
+
+    #[test]
+    fn test_write_cai_sets_last_block_flag() {
+        // file with only a STREAMINFO block
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let mut minimal = Vec::new();
+        minimal.extend_from_slice(&source[..4]);
+        minimal.push(STREAMINFO | LAST_BLOCK_FLAG);
+        minimal.extend_from_slice(&source[5..42]);
+        let bp = get_flac_block_positions(&mut Cursor::new(source.to_vec())).unwrap();
+        let audio_start = bp.last().unwrap().end() as usize;
+        minimal.extend_from_slice(&source[audio_start..]);
+
+        let flac_io = FlacIO {};
+        let mut output_stream = Cursor::new(Vec::new());
+        flac_io
+            .write_cai(
+                &mut Cursor::new(minimal.clone()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let output = output_stream.get_ref();
+        assert_eq!(output[4], STREAMINFO);
+        assert_eq!(output[42], APPLICATION | LAST_BLOCK_FLAG);
+
+        // removal restores the original
+        let mut removed_stream = Cursor::new(Vec::new());
+        flac_io
+            .remove_cai_store_from_stream(&mut output_stream, &mut removed_stream)
+            .unwrap();
+        assert_eq!(removed_stream.into_inner(), minimal);
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mut stream = Cursor::new(source.to_vec());
+        let flac_io = FlacIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            flac_io.write_cai(&mut stream, &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_write_cai_too_large() {
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let flac_io = FlacIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            flac_io.write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &vec![0u8; MAX_BLOCK_LEN]
+            ),
+            Err(Error::EmbeddingError)
+        ));
+    }
+
+    #[test]
+    fn test_stream_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let mut stream = Cursor::new(source.to_vec());
+        let flac_io = FlacIO {};
+
+        let positions = flac_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_pos = positions
+            .iter()
+            .find(|pos| pos.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        // placeholder follows STREAMINFO
+        assert_eq!(cai_pos.offset, 42);
+        assert_eq!(cai_pos.length, (BLOCK_HDR_LEN + APP_ID_LEN) as usize + 4);
+
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, source.len() + cai_pos.length);
+    }
+
+    #[test]
+    fn test_patch_write_flac() {
+        let source = fixture_path("sample1.flac");
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-flac.flac");
+        std::fs::copy(source, &output).unwrap();
+
+        let flac_io = FlacIO {};
+        flac_io.save_cai_store(&output, b"some test data").unwrap();
+
+        // same size data is patched in place
+        let new_data = b"more test data".to_vec();
+        flac_io.patch_cai_store(&output, &new_data).unwrap();
+        assert_eq!(flac_io.read_cai_store(&output).unwrap(), new_data);
+
+        assert!(matches!(
+            flac_io.patch_cai_store(&output, b"too short"),
+            Err(Error::InvalidAsset(_))
+        ));
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.flac");
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-flac.flac");
+        std::fs::copy(&source, &output).unwrap();
+
+        let flac_io = FlacIO {};
+        flac_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        flac_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match flac_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let flac_io = FlacIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        flac_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let box_map = flac_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "fLaC",
+                "STREAMINFO",
+                C2PA_BOXHASH,
+                "VORBIS_COMMENT",
+                "PADDING",
+                "AUDIO"
+            ]
+        );
+
+        // box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+
+    #[test]
+    fn test_embeddable_manifest() {
+        let source = include_bytes!("../../tests/fixtures/sample1.flac");
+        let flac_io = FlacIO {};
+
+        let ol = flac_io
+            .get_object_locations_from_stream(&mut Cursor::new(source.to_vec()))
+            .unwrap();
+        let cai_loc = ol
+            .iter()
+            .find(|o| o.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        let manifest = vec![7u8; 100];
+        let em = flac_io
+            .composed_data_ref()
+            .unwrap()
+            .compose_manifest(&manifest, "flac")
+            .unwrap();
+
+        // insert new manifest
+        let mut out_stream = Cursor::new(Vec::new());
+        out_stream.write_all(&source[..cai_loc.offset]).unwrap();
+        out_stream.write_all(&em).unwrap();
+        out_stream.write_all(&source[cai_loc.offset..]).unwrap();
+
+        let restored_manifest = flac_io.read_cai(&mut out_stream).unwrap();
+        assert_eq!(&manifest, &restored_manifest);
+    }
+}
@@ -0,0 +1,975 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use byteorder::ReadBytesExt;
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        ComposedManifestRef, HashBlockObjectType, HashObjectPositions, RemoteRefEmbed,
+        RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+const GIF87A_HDR: [u8; 6] = *b"GIF87a";
+const GIF89A_HDR: [u8; 6] = *b"GIF89a";
+const GIF_HDR_LEN: u64 = 6;
+const GIF_LSD_LEN: u64 = 7;
+
+const EXTENSION_INTRODUCER: u8 = 0x21;
+const IMAGE_SEPARATOR: u8 = 0x2c;
+const GIF_TRAILER: u8 = 0x3b;
+
+const APPLICATION_EXT_LABEL: u8 = 0xff;
+const GRAPHIC_CONTROL_EXT_LABEL: u8 = 0xf9;
+const COMMENT_EXT_LABEL: u8 = 0xfe;
+const PLAIN_TEXT_EXT_LABEL: u8 = 0x01;
+
+// application identifier (8 bytes) + authentication code (3 bytes)
+const APP_ID_LEN: usize = 11;
+const C2PA_APP_ID: [u8; APP_ID_LEN] = *b"C2PA_GIF\x01\x00\x00";
+const XMP_APP_ID: [u8; APP_ID_LEN] = *b"XMP DataXMP";
+
+// introducer(1) + label(1) + block size(1) + application id(11)
+const APP_EXT_HDR_LEN: u64 = 14;
+
+// XMP is stored raw and followed by a 257 byte "magic trailer" so that readers
+// walking the data sub-blocks land on the block terminator.
+const XMP_MAGIC_TRAILER_LEN: usize = 257;
+
+// maximum length of a single data sub-block
+const MAX_SUB_BLOCK_LEN: usize = 255;
+
+static SUPPORTED_TYPES: [&str; 2] = ["gif", "image/gif"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GifBlockType {
+    Header,
+    LogicalScreenDescriptor,
+    ApplicationExtension([u8; APP_ID_LEN]),
+    GraphicControlExtension,
+    CommentExtension,
+    PlainTextExtension,
+    UnknownExtension(u8),
+    Image,
+    Trailer,
+}
+
+#[derive(Clone, Debug)]
+struct GifBlockPos {
+    pub start: u64,
+    pub length: u64,
+    pub block_type: GifBlockType,
+}
+
+impl GifBlockPos {
+    pub fn end(&self) -> u64 {
+        self.start + self.length
+    }
+
+    fn is_app_ext(&self, app_id: &[u8; APP_ID_LEN]) -> bool {
+        matches!(self.block_type, GifBlockType::ApplicationExtension(id) if &id == app_id)
+    }
+
+    // name used for the block in a BoxHash map
+    fn box_name(&self, gif_hdr: &[u8; 6]) -> String {
+        match self.block_type {
+            GifBlockType::Header => String::from_utf8_lossy(gif_hdr).to_string(),
+            GifBlockType::LogicalScreenDescriptor => "LSD".to_string(),
+            GifBlockType::ApplicationExtension(id) => {
+                if id == C2PA_APP_ID {
+                    C2PA_BOXHASH.to_string()
+                } else {
+                    "APP".to_string()
+                }
+            }
+            GifBlockType::GraphicControlExtension => "GCE".to_string(),
+            GifBlockType::CommentExtension => "COM".to_string(),
+            GifBlockType::PlainTextExtension => "PTE".to_string(),
+            GifBlockType::UnknownExtension(_) => "EXT".to_string(),
+            GifBlockType::Image => "IMG".to_string(),
+            GifBlockType::Trailer => "TRL".to_string(),
+        }
+    }
+}
+
+fn gif_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("GIF out of range".to_string())
+}
+
+// skip a chain of data sub-blocks including the block terminator
+fn skip_sub_blocks<R: Read + Seek + ?Sized>(f: &mut R) -> Result<()> {
+    loop {
+        let len = f.read_u8().map_err(gif_err)?;
+        if len == 0 {
+            break;
+        }
+        f.seek(SeekFrom::Current(len as i64)).map_err(gif_err)?;
+    }
+    Ok(())
+}
+
+// size in bytes of a color table given the packed field of its descriptor
+fn color_table_len(packed: u8) -> u64 {
+    if packed & 0x80 != 0 {
+        3 * (1u64 << ((packed & 0x07) + 1))
+    } else {
+        0
+    }
+}
+
+fn get_gif_block_positions<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<GifBlockPos>> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    let mut block_positions: Vec<GifBlockPos> = Vec::new();
+
+    // move to beginning of file
+    f.rewind()?;
+
+    // check GIF signature
+    let mut hdr = [0u8; 6];
+    f.read_exact(&mut hdr)
+        .map_err(|_err| Error::InvalidAsset("GIF invalid".to_string()))?;
+    if hdr != GIF87A_HDR && hdr != GIF89A_HDR {
+        return Err(Error::InvalidAsset("GIF invalid".to_string()));
+    }
+    block_positions.push(GifBlockPos {
+        start: 0,
+        length: GIF_HDR_LEN,
+        block_type: GifBlockType::Header,
+    });
+
+    // logical screen descriptor and the optional global color table
+    let mut lsd = [0u8; GIF_LSD_LEN as usize];
+    f.read_exact(&mut lsd).map_err(gif_err)?;
+    let lsd_len = GIF_LSD_LEN + color_table_len(lsd[4]);
+    if GIF_HDR_LEN + lsd_len > stream_len {
+        return Err(Error::InvalidAsset("GIF out of range".to_string()));
+    }
+    block_positions.push(GifBlockPos {
+        start: GIF_HDR_LEN,
+        length: lsd_len,
+        block_type: GifBlockType::LogicalScreenDescriptor,
+    });
+    f.seek(SeekFrom::Start(GIF_HDR_LEN + lsd_len))?;
+
+    loop {
+        let current_pos = f.stream_position()?;
+
+        // tolerate files that end without a trailer
+        if current_pos >= stream_len {
+            break;
+        }
+
+        let block_type = match f.read_u8().map_err(gif_err)? {
+            EXTENSION_INTRODUCER => {
+                let label = f.read_u8().map_err(gif_err)?;
+                match label {
+                    APPLICATION_EXT_LABEL => {
+                        let block_size = f.read_u8().map_err(gif_err)?;
+                        let mut app_id = [0u8; APP_ID_LEN];
+                        if block_size as usize == APP_ID_LEN {
+                            f.read_exact(&mut app_id).map_err(gif_err)?;
+                        } else {
+                            f.seek(SeekFrom::Current(block_size as i64))
+                                .map_err(gif_err)?;
+                        }
+                        skip_sub_blocks(f)?;
+                        GifBlockType::ApplicationExtension(app_id)
+                    }
+                    GRAPHIC_CONTROL_EXT_LABEL => {
+                        skip_sub_blocks(f)?;
+                        GifBlockType::GraphicControlExtension
+                    }
+                    COMMENT_EXT_LABEL => {
+                        skip_sub_blocks(f)?;
+                        GifBlockType::CommentExtension
+                    }
+                    PLAIN_TEXT_EXT_LABEL => {
+                        skip_sub_blocks(f)?;
+                        GifBlockType::PlainTextExtension
+                    }
+                    _ => {
+                        skip_sub_blocks(f)?;
+                        GifBlockType::UnknownExtension(label)
+                    }
+                }
+            }
+            IMAGE_SEPARATOR => {
+                // image descriptor: left(2) + top(2) + width(2) + height(2) + packed(1)
+                let mut desc = [0u8; 9];
+                f.read_exact(&mut desc).map_err(gif_err)?;
+
+                // skip the local color table and the LZW minimum code size
+                f.seek(SeekFrom::Current(color_table_len(desc[8]) as i64 + 1))
+                    .map_err(gif_err)?;
+                skip_sub_blocks(f)?;
+                GifBlockType::Image
+            }
+            GIF_TRAILER => GifBlockType::Trailer,
+            _ => return Err(Error::InvalidAsset("GIF bad block".to_string())),
+        };
+
+        let end = f.stream_position()?;
+        if end > stream_len {
+            return Err(Error::InvalidAsset("GIF out of range".to_string()));
+        }
+
+        block_positions.push(GifBlockPos {
+            start: current_pos,
+            length: end - current_pos,
+            block_type,
+        });
+
+        if block_type == GifBlockType::Trailer {
+            break;
+        }
+    }
+
+    Ok(block_positions)
+}
+
+// read the contents of an application extension's data sub-blocks
+fn read_sub_block_data<R: Read + Seek + ?Sized>(f: &mut R, block: &GifBlockPos) -> Result<Vec<u8>> {
+    f.seek(SeekFrom::Start(block.start + APP_EXT_HDR_LEN))?;
+
+    let mut data = Vec::new();
+    loop {
+        let len = f.read_u8().map_err(gif_err)?;
+        if len == 0 {
+            break;
+        }
+        let mut sub_block = vec![0u8; len as usize];
+        f.read_exact(&mut sub_block).map_err(gif_err)?;
+        data.extend_from_slice(&sub_block);
+    }
+
+    Ok(data)
+}
+
+fn get_cai_data<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<u8>> {
+    let bp = get_gif_block_positions(f)?;
+
+    let mut cai_blocks = bp.iter().filter(|b| b.is_app_ext(&C2PA_APP_ID));
+    let cai_block = cai_blocks.next().ok_or(Error::JumbfNotFound)?;
+    if cai_blocks.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    read_sub_block_data(f, cai_block)
+}
+
+// create an application extension block containing data in sub-blocks
+fn make_app_ext_block(app_id: &[u8; APP_ID_LEN], data: &[u8]) -> Vec<u8> {
+    let mut block = Vec::with_capacity(
+        APP_EXT_HDR_LEN as usize + data.len() + data.len() / MAX_SUB_BLOCK_LEN + 2,
+    );
+    block.extend_from_slice(&[
+        EXTENSION_INTRODUCER,
+        APPLICATION_EXT_LABEL,
+        APP_ID_LEN as u8,
+    ]);
+    block.extend_from_slice(app_id);
+
+    for sub_block in data.chunks(MAX_SUB_BLOCK_LEN) {
+        block.push(sub_block.len() as u8);
+        block.extend_from_slice(sub_block);
+    }
+    block.push(0); // block terminator
+
+    block
+}
+
+// create the XMP application extension, the XMP packet is written raw followed by the magic trailer
+fn make_xmp_block(xmp: &str) -> Vec<u8> {
+    let mut block =
+        Vec::with_capacity(APP_EXT_HDR_LEN as usize + xmp.len() + XMP_MAGIC_TRAILER_LEN + 1);
+    block.extend_from_slice(&[
+        EXTENSION_INTRODUCER,
+        APPLICATION_EXT_LABEL,
+        APP_ID_LEN as u8,
+    ]);
+    block.extend_from_slice(&XMP_APP_ID);
+    block.extend_from_slice(xmp.as_bytes());
+
+    // magic trailer: 0x01, 0xff, 0xfe ... 0x01, 0x00
+    block.push(0x01);
+    block.extend((0..=0xffu8).rev());
+    block.push(0); // block terminator
+
+    block
+}
+
+fn range_of(block: &GifBlockPos) -> Result<std::ops::Range<usize>> {
+    let start = usize::value_from(block.start)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    let end = usize::value_from(block.end())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    Ok(start..end)
+}
+
+// remove all application extensions with the given id from the GIF buffer
+fn remove_app_ext_blocks(gif_buf: &mut Vec<u8>, app_id: &[u8; APP_ID_LEN]) -> Result<()> {
+    let mut cursor = Cursor::new(&gif_buf[..]);
+    let bp = get_gif_block_positions(&mut cursor)?;
+
+    // remove from the back so earlier offsets stay valid
+    for block in bp.iter().rev().filter(|b| b.is_app_ext(app_id)) {
+        gif_buf.drain(range_of(block)?);
+    }
+
+    Ok(())
+}
+
+// insert a block directly after the logical screen descriptor and global color table
+fn insert_after_lsd(gif_buf: &mut Vec<u8>, block: &[u8]) -> Result<()> {
+    let mut cursor = Cursor::new(&gif_buf[..]);
+    let bp = get_gif_block_positions(&mut cursor)?;
+
+    let lsd = bp
+        .iter()
+        .find(|b| b.block_type == GifBlockType::LogicalScreenDescriptor)
+        .ok_or(Error::EmbeddingError)?;
+    let insert_pos = range_of(lsd)?.end;
+
+    gif_buf.splice(insert_pos..insert_pos, block.iter().cloned());
+
+    // extensions require GIF89a
+    if gif_buf[..GIF_HDR_LEN as usize] == GIF87A_HDR {
+        gif_buf[..GIF_HDR_LEN as usize].copy_from_slice(&GIF89A_HDR);
+    }
+
+    Ok(())
+}
+
+fn add_required_blocks_to_stream(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let bp = get_gif_block_positions(input_stream)?;
+
+    if bp.iter().any(|b| b.is_app_ext(&C2PA_APP_ID)) {
+        // just clone
+        input_stream.rewind()?;
+        output_stream.rewind()?;
+        std::io::copy(input_stream, output_stream)?;
+    } else {
+        let no_bytes: Vec<u8> = Vec::new();
+        let aio = GifIO {};
+        aio.write_cai(input_stream, output_stream, &no_bytes)?;
+    }
+
+    Ok(())
+}
+
+pub struct GifIO {}
+
+impl CAIReader for GifIO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let cai_data = get_cai_data(asset_reader)?;
+        Ok(cai_data)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, asset_reader: &mut dyn CAIRead) -> Option<String> {
+        let bp = get_gif_block_positions(asset_reader).ok()?;
+        let xmp_block = bp.into_iter().find(|b| b.is_app_ext(&XMP_APP_ID))?;
+
+        // raw data runs from the application id to the block terminator
+        let data_len =
+            usize::value_from(xmp_block.length.checked_sub(APP_EXT_HDR_LEN + 1)?).ok()?;
+        if data_len < XMP_MAGIC_TRAILER_LEN {
+            return None;
+        }
+
+        asset_reader
+            .seek(SeekFrom::Start(xmp_block.start + APP_EXT_HDR_LEN))
+            .ok()?;
+        let mut data = vec![0u8; data_len];
+        asset_reader.read_exact(&mut data).ok()?;
+        data.truncate(data_len - XMP_MAGIC_TRAILER_LEN);
+
+        String::from_utf8(data).ok()
+    }
+}
+
+impl CAIWriter for GifIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let mut gif_buf = Vec::new();
+        input_stream.rewind()?;
+        input_stream
+            .read_to_end(&mut gif_buf)
+            .map_err(Error::IoError)?;
+
+        /*  The manifest store is kept in an application extension:
+                extension introducer (0x21), application extension label (0xff)
+                block size (0x0b), application identifier (8 bytes) and authentication code (3 bytes)
+                data sub-blocks (1 byte length followed by 1 - 255 bytes of data)
+                block terminator (0x00)
+        */
+        let cai_block = make_app_ext_block(&C2PA_APP_ID, store_bytes);
+
+        // erase existing cai data and add the new data after the logical screen descriptor
+        remove_app_ext_blocks(&mut gif_buf, &C2PA_APP_ID)?;
+        insert_after_lsd(&mut gif_buf, &cai_block)?;
+
+        output_stream.rewind()?;
+        output_stream.write_all(&gif_buf)?;
+
+        Ok(())
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        // Ensure the stream has the required blocks so we can generate the required offsets.
+        let output: Vec<u8> = Vec::new();
+        let mut output_stream = Cursor::new(output);
+
+        add_required_blocks_to_stream(input_stream, &mut output_stream)?;
+
+        let bp = get_gif_block_positions(&mut output_stream)?;
+        let file_end = output_stream.get_ref().len();
+
+        let cai_block = bp
+            .into_iter()
+            .find(|b| b.is_app_ext(&C2PA_APP_ID))
+            .ok_or(Error::JumbfNotFound)?;
+        let cai_range = range_of(&cai_block)?;
+
+        positions.push(HashObjectPositions {
+            offset: cai_range.start,
+            length: cai_range.len(),
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of blocks before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: cai_range.start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: cai_range.end,
+            length: file_end - cai_range.end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        let mut gif_buf = Vec::new();
+        input_stream.rewind()?;
+        input_stream
+            .read_to_end(&mut gif_buf)
+            .map_err(Error::IoError)?;
+
+        remove_app_ext_blocks(&mut gif_buf, &C2PA_APP_ID)?;
+
+        output_stream.rewind()?;
+        output_stream.write_all(&gif_buf)?;
+
+        Ok(())
+    }
+}
+
+impl AssetIO for GifIO {
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        self.get_object_locations_from_stream(&mut file)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn new(_asset_type: &str) -> Self
+    where
+        Self: Sized,
+    {
+        GifIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(GifIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(GifIO::new(asset_type)))
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn composed_data_ref(&self) -> Option<&dyn ComposedManifestRef> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl RemoteRefEmbed for GifIO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let output_buf = Vec::new();
+                let mut output_stream = Cursor::new(output_buf);
+
+                // do here so source file is closed after update
+                {
+                    let mut source_stream = std::fs::File::open(asset_path)?;
+                    self.embed_reference_to_stream(
+                        &mut source_stream,
+                        &mut output_stream,
+                        RemoteRefEmbedType::Xmp(manifest_uri),
+                    )?;
+                }
+
+                std::fs::write(asset_path, output_stream.into_inner())?;
+
+                Ok(())
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        source_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                source_stream.rewind()?;
+
+                let xmp = match self.read_xmp(source_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+
+                // update XMP
+                let updated_xmp = add_provenance(&xmp, &manifest_uri)?;
+                let xmp_block = make_xmp_block(&updated_xmp);
+
+                let mut gif_buf = Vec::new();
+                source_stream.rewind()?;
+                source_stream
+                    .read_to_end(&mut gif_buf)
+                    .map_err(Error::IoError)?;
+
+                // replace existing XMP
+                remove_app_ext_blocks(&mut gif_buf, &XMP_APP_ID)?;
+                insert_after_lsd(&mut gif_buf, &xmp_block)?;
+
+                output_stream.rewind()?;
+                output_stream.write_all(&gif_buf)?;
+
+                Ok(())
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+impl AssetBoxHash for GifIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        input_stream.rewind()?;
+
+        let bp = get_gif_block_positions(input_stream)?;
+
+        let mut gif_hdr = [0u8; 6];
+        input_stream.rewind()?;
+        input_stream.read_exact(&mut gif_hdr)?;
+
+        let box_maps = bp
+            .iter()
+            .map(|b| {
+                Ok(BoxMap {
+                    names: vec![b.box_name(&gif_hdr)],
+                    alg: None,
+                    hash: ByteBuf::from(Vec::new()),
+                    pad: ByteBuf::from(Vec::new()),
+                    range_start: usize::value_from(b.start)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                    range_len: usize::value_from(b.length)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                })
+            })
+            .collect::<Result<Vec<BoxMap>>>()?;
+
+        Ok(box_maps)
+    }
+}
+
+impl ComposedManifestRef for GifIO {
+    fn compose_manifest(&self, manifest_data: &[u8], _format: &str) -> Result<Vec<u8>> {
+        Ok(make_app_ext_block(&C2PA_APP_ID, manifest_data))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+#[allow(clippy::unwrap_used)]
+pub mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::utils::{test, xmp_inmemory_utils::extract_provenance};
+
+    #[test]
+    fn test_gif_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let bp = get_gif_block_positions(&mut stream).unwrap();
+
+        // header, lsd, netscape, comment, 2 x (gce, image), trailer
+        assert_eq!(bp.len(), 9);
+        assert_eq!(bp[0].block_type, GifBlockType::Header);
+        assert_eq!(bp[1].block_type, GifBlockType::LogicalScreenDescriptor);
+        assert_eq!(bp[1].end(), 25); // lsd + 4 color global color table
+        assert!(bp[2].is_app_ext(b"NETSCAPE2.0"));
+        assert_eq!(bp[3].block_type, GifBlockType::CommentExtension);
+        assert_eq!(bp[5].block_type, GifBlockType::Image);
+        assert_eq!(bp[8].block_type, GifBlockType::Trailer);
+        assert_eq!(bp[8].end(), source.len() as u64);
+    }
+
+    #[test]
+    fn test_write_cai_using_stream_no_cai_data() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+        let gif_io = GifIO {};
+
+        // no cai data present in stream.
+        assert!(matches!(
+            gif_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        // write new data spanning multiple sub-blocks.
+        let output: Vec<u8> = Vec::new();
+        let mut output_stream = Cursor::new(output);
+
+        let data_to_write: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        gif_io
+            .write_cai(&mut stream, &mut output_stream, &data_to_write)
+            .unwrap();
+
+        // assert new cai data is present.
+        let data_written = gif_io.read_cai(&mut output_stream).unwrap();
+        assert_eq!(data_to_write, data_written);
+
+        // the original blocks are still intact
+        let output = output_stream.into_inner();
+        assert_eq!(output[25..28], [0x21, 0xff, 0x0b]);
+        assert!(output.ends_with(&source[25..]));
+    }
+
+    #[test]
+    fn test_write_cai_using_stream_existing_cai_data() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+        let gif_io = GifIO {};
+
+        let mut first = Cursor::new(Vec::new());
+        gif_io
+            .write_cai(&mut stream, &mut first, &[1, 2, 3])
+            .unwrap();
+
+        // new data replaces the existing cai data
+        let mut second = Cursor::new(Vec::new());
+        let data_to_write: Vec<u8> = vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34];
+        gif_io
+            .write_cai(&mut first, &mut second, &data_to_write)
+            .unwrap();
+
+        let data_written = gif_io.read_cai(&mut second).unwrap();
+        assert_eq!(data_to_write, data_written);
+        assert_eq!(
+            second.get_ref().len(),
+            source.len() + APP_EXT_HDR_LEN as usize + 1 + data_to_write.len() + 1
+        );
+    }
+
+    #[test]
+    fn test_write_cai_upgrades_gif87a() {
+        let mut source = include_bytes!("../../tests/fixtures/sample1.gif").to_vec();
+        source[..6].copy_from_slice(&GIF87A_HDR);
+        let mut stream = Cursor::new(source);
+        let gif_io = GifIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gif_io
+            .write_cai(&mut stream, &mut output_stream, &[1, 2, 3])
+            .unwrap();
+
+        assert_eq!(output_stream.get_ref()[..6], GIF89A_HDR);
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mut stream = Cursor::new(source.to_vec());
+        let gif_io = GifIO {};
+
+        let output: Vec<u8> = Vec::new();
+        let mut output_stream = Cursor::new(output);
+        assert!(matches!(
+            gif_io.write_cai(&mut stream, &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_stream_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+        let gif_io = GifIO {};
+
+        let positions = gif_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_pos = positions
+            .iter()
+            .find(|pos| pos.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        // empty placeholder is inserted after the global color table
+        assert_eq!(cai_pos.offset, 25);
+        assert_eq!(cai_pos.length, APP_EXT_HDR_LEN as usize + 1);
+
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, source.len() + cai_pos.length);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = test::fixture_path("sample1.gif");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output = test::temp_dir_path(&temp_dir, "sample1_tmp.gif");
+        std::fs::copy(source, &output).unwrap();
+
+        let gif_io = GifIO {};
+        gif_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(gif_io.read_cai_store(&output).unwrap(), vec![1, 2, 3, 4]);
+
+        gif_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match gif_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        // the remaining bytes match the original
+        let source_bytes = include_bytes!("../../tests/fixtures/sample1.gif");
+        assert_eq!(std::fs::read(&output).unwrap(), source_bytes.to_vec());
+    }
+
+    #[test]
+    fn test_gif_xmp_write() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+        let gif_io = GifIO {};
+
+        assert!(gif_io.read_xmp(&mut stream).is_none());
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gif_io
+            .embed_reference_to_stream(
+                &mut stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        // the XMP block must still be walkable as sub-blocks
+        let bp = get_gif_block_positions(&mut output_stream).unwrap();
+        assert_eq!(bp.len(), 10);
+
+        let xmp = gif_io.read_xmp(&mut output_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/manifest.c2pa"
+        );
+
+        // updating replaces the existing XMP block
+        let mut updated_stream = Cursor::new(Vec::new());
+        gif_io
+            .embed_reference_to_stream(
+                &mut output_stream,
+                &mut updated_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/updated.c2pa".to_string()),
+            )
+            .unwrap();
+        let bp = get_gif_block_positions(&mut updated_stream).unwrap();
+        assert_eq!(bp.len(), 10);
+
+        let xmp = gif_io.read_xmp(&mut updated_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/updated.c2pa"
+        );
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+        let gif_io = GifIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gif_io
+            .write_cai(&mut stream, &mut output_stream, &[1, 2, 3])
+            .unwrap();
+
+        let box_map = gif_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "GIF89a",
+                "LSD",
+                C2PA_BOXHASH,
+                "APP",
+                "COM",
+                "GCE",
+                "IMG",
+                "GCE",
+                "IMG",
+                "TRL"
+            ]
+        );
+
+        // box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+
+    #[test]
+    fn test_embeddable_manifest() {
+        let gif_io = GifIO {};
+
+        let source = include_bytes!("../../tests/fixtures/sample1.gif");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let ol = gif_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_loc = ol
+            .iter()
+            .find(|o| o.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        // generate new manifest data
+        let manifest = vec![7u8; 600];
+        let em = gif_io
+            .composed_data_ref()
+            .unwrap()
+            .compose_manifest(&manifest, "gif")
+            .unwrap();
+
+        // insert new manifest
+        let mut out_stream = Cursor::new(Vec::new());
+        out_stream.write_all(&source[..cai_loc.offset]).unwrap();
+        out_stream.write_all(&em).unwrap();
+        out_stream.write_all(&source[cai_loc.offset..]).unwrap();
+
+        // read manifest back in from new in-memory GIF
+        let restored_manifest = gif_io.read_cai(&mut out_stream).unwrap();
+        assert_eq!(&manifest, &restored_manifest);
+    }
+}
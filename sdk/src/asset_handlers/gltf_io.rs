@@ -0,0 +1,1041 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! glTF 2.0 support for both binary (GLB) and JSON assets.
+//!
+//! GLB files carry the manifest store in a dedicated `C2PA` chunk placed after the JSON and
+//! binary buffer chunks.  Loaders skip chunk types they do not know, so only the length in
+//! the GLB header changes.  The store is self delimiting, so the zero padding that keeps the
+//! chunk aligned is dropped when it is read back.
+//!
+//! glTF JSON files declare a top level `C2PA_manifest` extension that references an entry in
+//! `buffers`.  Manifests are written as a base64 `data:` URI.  A buffer may also reference an
+//! external `.c2pa` file, which is resolved next to the asset when reading from a path.
+
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use serde_json::{json, Map, Value};
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        HashBlockObjectType, HashObjectPositions,
+    },
+    error::{Error, Result},
+    settings::get_settings_value,
+    utils::base64,
+};
+
+static SUPPORTED_TYPES: [&str; 4] = ["glb", "gltf", "model/gltf-binary", "model/gltf+json"];
+
+const GLB_MAGIC: [u8; 4] = *b"glTF";
+const GLB_VERSION: u32 = 2;
+const GLB_HDR_LEN: u64 = 12;
+const CHUNK_HDR_LEN: u64 = 8;
+
+const JSON_CHUNK: [u8; 4] = *b"JSON";
+const BIN_CHUNK: [u8; 4] = *b"BIN\0";
+const C2PA_CHUNK: [u8; 4] = *b"C2PA";
+
+const C2PA_EXTENSION: &str = "C2PA_manifest";
+const C2PA_DATA_URI_PREFIX: &str = "data:application/c2pa;base64,";
+
+#[derive(Clone, Debug)]
+struct GlbChunkPos {
+    pub start: u64,
+    pub data_len: u64,
+    pub chunk_type: [u8; 4],
+}
+
+impl GlbChunkPos {
+    pub fn end(&self) -> u64 {
+        self.start + CHUNK_HDR_LEN + self.data_len
+    }
+}
+
+fn glb_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("GLB out of range".to_string())
+}
+
+fn gltf_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("glTF invalid".to_string())
+}
+
+fn is_glb<R: Read + Seek + ?Sized>(f: &mut R) -> Result<bool> {
+    let mut magic = [0u8; 4];
+    f.rewind()?;
+    let is_glb = f.read_exact(&mut magic).is_ok() && magic == GLB_MAGIC;
+    f.rewind()?;
+
+    Ok(is_glb)
+}
+
+// get the chunks of a GLB file
+fn get_glb_chunks<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<GlbChunkPos>> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic).map_err(glb_err)?;
+    let version = f.read_u32::<LittleEndian>().map_err(glb_err)?;
+    let length = f.read_u32::<LittleEndian>().map_err(glb_err)? as u64;
+
+    if magic != GLB_MAGIC || version != GLB_VERSION {
+        return Err(Error::InvalidAsset("GLB invalid".to_string()));
+    }
+    if length > stream_len {
+        return Err(Error::InvalidAsset("GLB bad length".to_string()));
+    }
+
+    let mut chunks = Vec::new();
+    let mut current_pos = GLB_HDR_LEN;
+    while current_pos < length {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let data_len = f.read_u32::<LittleEndian>().map_err(glb_err)? as u64;
+        let mut chunk_type = [0u8; 4];
+        f.read_exact(&mut chunk_type).map_err(glb_err)?;
+
+        let chunk = GlbChunkPos {
+            start: current_pos,
+            data_len,
+            chunk_type,
+        };
+        if chunk.end() > length {
+            return Err(Error::InvalidAsset("GLB bad chunk size".to_string()));
+        }
+
+        current_pos = chunk.end();
+        chunks.push(chunk);
+    }
+
+    // the JSON chunk must be first
+    if chunks.first().map(|c| c.chunk_type) != Some(JSON_CHUNK) {
+        return Err(Error::InvalidAsset("GLB missing JSON chunk".to_string()));
+    }
+
+    Ok(chunks)
+}
+
+// create a C2PA chunk, padded to keep the following chunks aligned
+fn make_c2pa_chunk(store_bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut data = store_bytes.to_vec();
+    data.resize(data.len() + (4 - data.len() % 4) % 4, 0);
+
+    let data_len = u32::value_from(data.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    let mut chunk = Vec::with_capacity(data.len() + CHUNK_HDR_LEN as usize);
+    chunk.write_u32::<LittleEndian>(data_len)?;
+    chunk.extend_from_slice(&C2PA_CHUNK);
+    chunk.extend_from_slice(&data);
+
+    Ok(chunk)
+}
+
+// length of the JUMBF superbox at the start of the data, used to drop chunk padding
+fn jumbf_len(data: &[u8]) -> Option<usize> {
+    let lbox = u32::from_be_bytes(data.get(0..4)?.try_into().ok()?);
+    let len = match lbox {
+        1 => usize::value_from(u64::from_be_bytes(data.get(8..16)?.try_into().ok()?)).ok()?,
+        _ => usize::value_from(lbox).ok()?,
+    };
+
+    (len >= 8 && len <= data.len()).then_some(len)
+}
+
+// copy the GLB with the C2PA chunks removed, inserting a new one after the JSON and binary chunks
+fn rewrite_glb(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    new_chunk: Option<&[u8]>,
+) -> Result<()> {
+    let chunks = get_glb_chunks(input_stream)?;
+    let kept: Vec<&GlbChunkPos> = chunks
+        .iter()
+        .filter(|c| c.chunk_type != C2PA_CHUNK)
+        .collect();
+
+    let insert_index = match kept.get(1) {
+        Some(c) if c.chunk_type == BIN_CHUNK => 2,
+        _ => 1,
+    };
+
+    let new_chunk = new_chunk.unwrap_or_default();
+    let length =
+        kept.iter().map(|c| c.end() - c.start).sum::<u64>() + new_chunk.len() as u64 + GLB_HDR_LEN;
+    let length =
+        u32::value_from(length).map_err(|_err| Error::InvalidAsset("GLB too large".to_string()))?;
+
+    output_stream.rewind()?;
+    output_stream.write_all(&GLB_MAGIC)?;
+    output_stream.write_u32::<LittleEndian>(GLB_VERSION)?;
+    output_stream.write_u32::<LittleEndian>(length)?;
+
+    for (index, chunk) in kept.iter().enumerate() {
+        if index == insert_index {
+            output_stream.write_all(new_chunk)?;
+        }
+
+        input_stream.seek(SeekFrom::Start(chunk.start))?;
+        let mut chunk_reader = input_stream.take(chunk.end() - chunk.start);
+        std::io::copy(&mut chunk_reader, output_stream)?;
+    }
+    if insert_index >= kept.len() {
+        output_stream.write_all(new_chunk)?;
+    }
+
+    Ok(())
+}
+
+fn get_glb_cai_data<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<u8>> {
+    let chunks = get_glb_chunks(f)?;
+
+    let mut c2pa_chunks = chunks.iter().filter(|c| c.chunk_type == C2PA_CHUNK);
+    let c2pa_chunk = c2pa_chunks.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_chunks.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    let data_len = usize::value_from(c2pa_chunk.data_len)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(c2pa_chunk.start + CHUNK_HDR_LEN))?;
+    let mut data = vec![0u8; data_len];
+    f.read_exact(&mut data).map_err(glb_err)?;
+
+    if let Some(len) = jumbf_len(&data) {
+        data.truncate(len);
+    }
+
+    Ok(data)
+}
+
+fn read_gltf_json<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Map<String, Value>> {
+    f.rewind()?;
+    let doc: Value = serde_json::from_reader(f).map_err(gltf_err)?;
+
+    match doc {
+        Value::Object(doc) if doc.get("asset").is_some_and(Value::is_object) => Ok(doc),
+        _ => Err(Error::InvalidAsset("glTF invalid".to_string())),
+    }
+}
+
+fn write_gltf_json(doc: &Map<String, Value>, output_stream: &mut dyn CAIReadWrite) -> Result<()> {
+    let json = serde_json::to_vec_pretty(doc).map_err(gltf_err)?;
+
+    output_stream.rewind()?;
+    output_stream.write_all(&json)?;
+
+    Ok(())
+}
+
+// index into buffers of the manifest store buffer
+fn c2pa_buffer_index(doc: &Map<String, Value>) -> Option<usize> {
+    let index = doc
+        .get("extensions")?
+        .get(C2PA_EXTENSION)?
+        .get("buffer")?
+        .as_u64()?;
+
+    usize::value_from(index).ok()
+}
+
+fn c2pa_buffer_uri(doc: &Map<String, Value>) -> Option<&str> {
+    let index = c2pa_buffer_index(doc)?;
+    doc.get("buffers")?.get(index)?.get("uri")?.as_str()
+}
+
+// remove the manifest store buffer, its extension entry and its declaration
+fn remove_c2pa_buffer(doc: &mut Map<String, Value>) {
+    if let Some(index) = c2pa_buffer_index(doc) {
+        if let Some(Value::Array(buffers)) = doc.get_mut("buffers") {
+            if index < buffers.len() {
+                buffers.remove(index);
+
+                // buffer views referencing later buffers move down one
+                if let Some(Value::Array(views)) = doc.get_mut("bufferViews") {
+                    for view in views.iter_mut() {
+                        if let Some(buffer) = view.get_mut("buffer") {
+                            if let Some(b) = buffer.as_u64().filter(|b| *b > index as u64) {
+                                *buffer = json!(b - 1);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut remove_extensions = false;
+    if let Some(Value::Object(extensions)) = doc.get_mut("extensions") {
+        extensions.remove(C2PA_EXTENSION);
+        remove_extensions = extensions.is_empty();
+    }
+    if remove_extensions {
+        doc.remove("extensions");
+    }
+
+    let mut remove_used = false;
+    if let Some(Value::Array(used)) = doc.get_mut("extensionsUsed") {
+        used.retain(|e| e.as_str() != Some(C2PA_EXTENSION));
+        remove_used = used.is_empty();
+    }
+    if remove_used {
+        doc.remove("extensionsUsed");
+    }
+}
+
+// append a buffer holding the manifest store and reference it from the extension
+fn add_c2pa_buffer(doc: &mut Map<String, Value>, store_bytes: &[u8]) -> Result<()> {
+    let buffers = doc
+        .entry("buffers")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or(Error::InvalidAsset("glTF invalid buffers".to_string()))?;
+
+    let index = buffers.len();
+    buffers.push(json!({
+        "byteLength": store_bytes.len(),
+        "uri": format!("{C2PA_DATA_URI_PREFIX}{}", base64::encode(store_bytes)),
+    }));
+
+    doc.entry("extensions")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or(Error::InvalidAsset("glTF invalid extensions".to_string()))?
+        .insert(C2PA_EXTENSION.to_string(), json!({ "buffer": index }));
+
+    let used = doc
+        .entry("extensionsUsed")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or(Error::InvalidAsset(
+            "glTF invalid extensionsUsed".to_string(),
+        ))?;
+    if !used.iter().any(|e| e.as_str() == Some(C2PA_EXTENSION)) {
+        used.push(json!(C2PA_EXTENSION));
+    }
+
+    Ok(())
+}
+
+// manifest stores are embedded as data URIs, other URIs reference an external manifest store
+fn get_gltf_cai_data<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    asset_dir: Option<&Path>,
+) -> Result<Vec<u8>> {
+    let doc = read_gltf_json(f)?;
+    let uri = c2pa_buffer_uri(&doc).ok_or(Error::JumbfNotFound)?;
+
+    if uri.starts_with("data:") {
+        let (_, data) = uri
+            .split_once(";base64,")
+            .ok_or(Error::InvalidAsset("glTF invalid manifest URI".to_string()))?;
+        base64::decode(data).map_err(|_err| Error::InvalidAsset("glTF invalid base64".to_string()))
+    } else if uri.starts_with("http://") || uri.starts_with("https://") {
+        Err(Error::RemoteManifestUrl(uri.to_string()))
+    } else {
+        match asset_dir {
+            Some(dir) => {
+                let path = external_manifest_path(dir, uri)?;
+                let limit = get_settings_value::<u64>("core.max_memory_usage").ok();
+                read_external_manifest(&path, limit)
+            }
+            None => Err(Error::JumbfNotFound),
+        }
+    }
+}
+
+// resolve a relative URI to an external manifest store, which must stay within the asset directory
+fn external_manifest_path(dir: &Path, uri: &str) -> Result<PathBuf> {
+    let invalid_uri = || Error::InvalidAsset("glTF invalid manifest URI".to_string());
+
+    let decoded = percent_decode(uri).ok_or_else(invalid_uri)?;
+    if !decoded.ends_with(".c2pa") {
+        return Err(invalid_uri());
+    }
+
+    let relative = Path::new(&decoded);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(invalid_uri());
+    }
+
+    Ok(dir.join(relative))
+}
+
+// glTF URIs are RFC 3986 references, so reserved characters in file names are percent encoded
+fn percent_decode(uri: &str) -> Option<String> {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn read_external_manifest(path: &Path, limit: Option<u64>) -> Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if let Some(limit) = limit {
+        if len > limit {
+            return Err(Error::InvalidAsset(
+                "glTF external manifest exceeds core.max_memory_usage".to_string(),
+            ));
+        }
+    }
+
+    let mut data = Vec::new();
+    file.take(limit.unwrap_or(len)).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+// byte range of the base64 encoded manifest store within glTF JSON
+fn gltf_manifest_range(gltf_buf: &[u8]) -> Result<Option<std::ops::Range<usize>>> {
+    let doc = read_gltf_json(&mut Cursor::new(gltf_buf))?;
+    let uri = match c2pa_buffer_uri(&doc) {
+        Some(uri) if uri.starts_with(C2PA_DATA_URI_PREFIX) => uri,
+        _ => return Ok(None),
+    };
+
+    let needle = format!("\"{uri}\"");
+    let start = gltf_buf
+        .windows(needle.len())
+        .position(|w| w == needle.as_bytes());
+
+    Ok(start.map(|start| {
+        let data_start = start + 1 + C2PA_DATA_URI_PREFIX.len();
+        data_start..start + needle.len() - 1
+    }))
+}
+
+fn read_all(input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    input_stream.rewind()?;
+    input_stream.read_to_end(&mut buf).map_err(Error::IoError)?;
+    Ok(buf)
+}
+
+pub struct GltfIO {}
+
+impl GltfIO {
+    fn get_cai_data(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        asset_dir: Option<&Path>,
+    ) -> Result<Vec<u8>> {
+        if is_glb(input_stream)? {
+            get_glb_cai_data(input_stream)
+        } else {
+            get_gltf_cai_data(input_stream, asset_dir)
+        }
+    }
+}
+
+impl CAIReader for GltfIO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        self.get_cai_data(asset_reader, None)
+    }
+
+    fn read_xmp(&self, _asset_reader: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for GltfIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        if is_glb(input_stream)? {
+            let chunk = make_c2pa_chunk(store_bytes)?;
+            rewrite_glb(input_stream, output_stream, Some(&chunk))
+        } else {
+            let mut doc = read_gltf_json(input_stream)?;
+            remove_c2pa_buffer(&mut doc);
+            add_c2pa_buffer(&mut doc, store_bytes)?;
+            write_gltf_json(&doc, output_stream)
+        }
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let (manifest_range, file_end) = if is_glb(input_stream)? {
+            // Ensure the stream has a manifest store so we can generate the required offsets.
+            let has_manifest = match get_glb_cai_data(input_stream) {
+                Ok(_) | Err(Error::TooManyManifestStores) => true,
+                Err(Error::JumbfNotFound) => false,
+                Err(e) => return Err(e),
+            };
+
+            let mut glb_stream = Cursor::new(Vec::new());
+            if has_manifest {
+                input_stream.rewind()?;
+                std::io::copy(input_stream, &mut glb_stream)?;
+            } else {
+                self.write_cai(input_stream, &mut glb_stream, &[1, 2, 3, 4])?;
+            }
+
+            let chunks = get_glb_chunks(&mut glb_stream)?;
+            let c2pa_chunk = chunks
+                .iter()
+                .find(|c| c.chunk_type == C2PA_CHUNK)
+                .ok_or(Error::JumbfNotFound)?;
+
+            let start = usize::value_from(c2pa_chunk.start)
+                .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+            let end = usize::value_from(c2pa_chunk.end())
+                .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+            (start..end, glb_stream.get_ref().len())
+        } else {
+            // only the base64 data is excluded, so the manifest must already be embedded
+            let mut gltf_buf = read_all(input_stream)?;
+            let range = match gltf_manifest_range(&gltf_buf)? {
+                Some(range) => range,
+                None => {
+                    let mut gltf_stream = Cursor::new(Vec::new());
+                    self.write_cai(input_stream, &mut gltf_stream, &[1, 2, 3, 4])?;
+                    gltf_buf = gltf_stream.into_inner();
+                    gltf_manifest_range(&gltf_buf)?.ok_or(Error::JumbfNotFound)?
+                }
+            };
+
+            (range, gltf_buf.len())
+        };
+
+        positions.push(HashObjectPositions {
+            offset: manifest_range.start,
+            length: manifest_range.len(),
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of data before the manifest store
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: manifest_range.start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from the manifest store to end
+        positions.push(HashObjectPositions {
+            offset: manifest_range.end,
+            length: file_end - manifest_range.end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        if is_glb(input_stream)? {
+            rewrite_glb(input_stream, output_stream, None)
+        } else {
+            let mut doc = read_gltf_json(input_stream)?;
+            remove_c2pa_buffer(&mut doc);
+            write_gltf_json(&doc, output_stream)
+        }
+    }
+}
+
+impl AssetIO for GltfIO {
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.get_cai_data(&mut f, asset_path.parent())
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        self.get_object_locations_from_stream(&mut file)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn new(_asset_type: &str) -> Self
+    where
+        Self: Sized,
+    {
+        GltfIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(GltfIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(GltfIO::new(asset_type)))
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetBoxHash for GltfIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        // box hashing works on GLB chunks, glTF JSON has no boxes
+        if !is_glb(input_stream)? {
+            return Err(Error::InvalidAsset(
+                "glTF JSON does not support box hashing".to_string(),
+            ));
+        }
+
+        let chunks = get_glb_chunks(input_stream)?;
+        let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+        let make_box_map = |name: String, start: u64, end: u64| -> Result<BoxMap> {
+            Ok(BoxMap {
+                names: vec![name],
+                alg: None,
+                hash: ByteBuf::from(Vec::new()),
+                pad: ByteBuf::from(Vec::new()),
+                range_start: usize::value_from(start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                range_len: usize::value_from(end - start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+            })
+        };
+
+        let mut box_maps = vec![make_box_map("glTF".to_string(), 0, GLB_HDR_LEN)?];
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            let name = if chunk.chunk_type == C2PA_CHUNK {
+                C2PA_BOXHASH.to_string()
+            } else {
+                String::from_utf8_lossy(&chunk.chunk_type)
+                    .trim_end_matches('\0')
+                    .to_string()
+            };
+
+            // the last chunk also covers any data after the GLB length
+            let end = if index + 1 == chunks.len() {
+                stream_len
+            } else {
+                chunk.end()
+            };
+            box_maps.push(make_box_map(name, chunk.start, end)?);
+        }
+
+        Ok(box_maps)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    // jumb superbox with an odd length so the chunk needs padding
+    fn test_store() -> Vec<u8> {
+        let mut store = vec![0, 0, 0, 13];
+        store.extend_from_slice(b"jumbC2PA\0");
+        store
+    }
+
+    #[test]
+    fn test_glb_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.glb");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let chunks = get_glb_chunks(&mut stream).unwrap();
+        let types: Vec<&[u8; 4]> = chunks.iter().map(|c| &c.chunk_type).collect();
+        assert_eq!(types, vec![&JSON_CHUNK, &BIN_CHUNK]);
+        assert_eq!(chunks[1].end(), source.len() as u64);
+    }
+
+    #[test]
+    fn test_glb_write_cai() {
+        let source = include_bytes!("../../tests/fixtures/sample1.glb");
+        let mut stream = Cursor::new(source.to_vec());
+        let gltf_io = GltfIO::new("glb");
+
+        assert!(matches!(
+            gltf_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gltf_io
+            .write_cai(&mut stream, &mut output_stream, &test_store())
+            .unwrap();
+
+        // padding is dropped when reading
+        assert_eq!(gltf_io.read_cai(&mut output_stream).unwrap(), test_store());
+
+        // header length covers the new chunk, which follows the binary chunk
+        let output = output_stream.get_ref();
+        assert_eq!(output.len(), source.len() + 8 + 16);
+        assert_eq!(
+            u32::from_le_bytes(output[8..12].try_into().unwrap()) as usize,
+            output.len()
+        );
+        let chunks = get_glb_chunks(&mut output_stream).unwrap();
+        assert_eq!(chunks[2].chunk_type, C2PA_CHUNK);
+        assert_eq!(chunks[2].start, source.len() as u64);
+
+        // replacing keeps a single store
+        let mut replaced_stream = Cursor::new(Vec::new());
+        gltf_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &[1, 2, 3, 4])
+            .unwrap();
+        assert_eq!(
+            gltf_io.read_cai(&mut replaced_stream).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(replaced_stream.get_ref().len(), source.len() + 8 + 4);
+    }
+
+    #[test]
+    fn test_gltf_write_cai() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gltf");
+        let mut stream = Cursor::new(source.to_vec());
+        let gltf_io = GltfIO::new("gltf");
+
+        assert!(matches!(
+            gltf_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gltf_io
+            .write_cai(&mut stream, &mut output_stream, &test_store())
+            .unwrap();
+        assert_eq!(gltf_io.read_cai(&mut output_stream).unwrap(), test_store());
+
+        let doc = read_gltf_json(&mut output_stream).unwrap();
+        assert_eq!(c2pa_buffer_index(&doc), Some(1));
+        assert_eq!(doc["extensionsUsed"], json!([C2PA_EXTENSION]));
+        assert_eq!(doc["buffers"][1]["byteLength"], json!(test_store().len()));
+
+        // the mesh data is untouched
+        let source_doc = read_gltf_json(&mut stream).unwrap();
+        assert_eq!(doc["buffers"][0], source_doc["buffers"][0]);
+        assert_eq!(doc["accessors"], source_doc["accessors"]);
+    }
+
+    #[test]
+    fn test_gltf_remove_reindexes_buffers() {
+        let source = include_bytes!("../../tests/fixtures/sample1.gltf");
+        let mut doc = read_gltf_json(&mut Cursor::new(source.to_vec())).unwrap();
+
+        // manifest buffer ahead of the mesh data
+        doc["buffers"].as_array_mut().unwrap().insert(
+            0,
+            json!({ "byteLength": 4, "uri": format!("{C2PA_DATA_URI_PREFIX}AQIDBA==") }),
+        );
+        doc["bufferViews"][0]["buffer"] = json!(1);
+        doc.insert(
+            "extensions".to_string(),
+            json!({ C2PA_EXTENSION: { "buffer": 0 } }),
+        );
+        doc.insert("extensionsUsed".to_string(), json!([C2PA_EXTENSION]));
+
+        let mut stream = Cursor::new(serde_json::to_vec(&doc).unwrap());
+        let gltf_io = GltfIO::new("gltf");
+        assert_eq!(gltf_io.read_cai(&mut stream).unwrap(), vec![1, 2, 3, 4]);
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gltf_io
+            .remove_cai_store_from_stream(&mut stream, &mut output_stream)
+            .unwrap();
+
+        let output_doc = read_gltf_json(&mut output_stream).unwrap();
+        let source_doc = read_gltf_json(&mut Cursor::new(source.to_vec())).unwrap();
+        assert_eq!(output_doc, source_doc);
+    }
+
+    #[test]
+    fn test_gltf_external_manifest() {
+        let temp_dir = tempdir().unwrap();
+        let asset_path = temp_dir_path(&temp_dir, "external.gltf");
+        std::fs::write(temp_dir_path(&temp_dir, "external.c2pa"), test_store()).unwrap();
+
+        let source = include_bytes!("../../tests/fixtures/sample1.gltf");
+        let mut doc = read_gltf_json(&mut Cursor::new(source.to_vec())).unwrap();
+        doc["buffers"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "byteLength": test_store().len(), "uri": "external.c2pa" }));
+        doc.insert(
+            "extensions".to_string(),
+            json!({ C2PA_EXTENSION: { "buffer": 1 } }),
+        );
+        std::fs::write(&asset_path, serde_json::to_vec(&doc).unwrap()).unwrap();
+
+        let gltf_io = GltfIO::new("gltf");
+        assert_eq!(gltf_io.read_cai_store(&asset_path).unwrap(), test_store());
+
+        // streams have no location to resolve the reference against
+        let mut stream = File::open(&asset_path).unwrap();
+        assert!(matches!(
+            gltf_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        // writing embeds the manifest store in place of the reference
+        gltf_io.save_cai_store(&asset_path, &[1, 2, 3, 4]).unwrap();
+        let mut stream = File::open(&asset_path).unwrap();
+        assert_eq!(gltf_io.read_cai(&mut stream).unwrap(), vec![1, 2, 3, 4]);
+        let doc = read_gltf_json(&mut stream).unwrap();
+        assert_eq!(doc["buffers"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_gltf_external_manifest_rejected_uris() {
+        let temp_dir = tempdir().unwrap();
+        let asset_path = temp_dir_path(&temp_dir, "external.gltf");
+        std::fs::write(temp_dir_path(&temp_dir, "my manifest.c2pa"), test_store()).unwrap();
+        std::fs::write(temp_dir_path(&temp_dir, "external.json"), test_store()).unwrap();
+
+        let source = include_bytes!("../../tests/fixtures/sample1.gltf");
+        let write_asset = |uri: &str| {
+            let mut doc = read_gltf_json(&mut Cursor::new(source.to_vec())).unwrap();
+            doc["buffers"]
+                .as_array_mut()
+                .unwrap()
+                .push(json!({ "byteLength": test_store().len(), "uri": uri }));
+            doc.insert(
+                "extensions".to_string(),
+                json!({ C2PA_EXTENSION: { "buffer": 1 } }),
+            );
+            std::fs::write(&asset_path, serde_json::to_vec(&doc).unwrap()).unwrap();
+        };
+        let gltf_io = GltfIO::new("gltf");
+
+        // file names are percent decoded
+        write_asset("my%20manifest.c2pa");
+        assert_eq!(gltf_io.read_cai_store(&asset_path).unwrap(), test_store());
+
+        let absolute = temp_dir_path(&temp_dir, "my manifest.c2pa");
+        let parent = format!(
+            "../{}/my%20manifest.c2pa",
+            temp_dir.path().file_name().unwrap().to_str().unwrap()
+        );
+        for uri in [
+            absolute.to_str().unwrap(),
+            "/etc/passwd.c2pa",
+            parent.as_str(),
+            "sub/../my%20manifest.c2pa",
+            "%2E%2E/my%20manifest.c2pa",
+            "external.json",
+            "external%2Ejson",
+            "my%2manifest.c2pa",
+        ] {
+            write_asset(uri);
+            assert!(
+                matches!(
+                    gltf_io.read_cai_store(&asset_path),
+                    Err(Error::InvalidAsset(_))
+                ),
+                "{uri}"
+            );
+        }
+
+        // external manifest stores are subject to the memory limit
+        let manifest_path = temp_dir_path(&temp_dir, "my manifest.c2pa");
+        let limit = test_store().len() as u64;
+        assert_eq!(
+            read_external_manifest(&manifest_path, Some(limit)).unwrap(),
+            test_store()
+        );
+        assert!(matches!(
+            read_external_manifest(&manifest_path, Some(limit - 1)),
+            Err(Error::InvalidAsset(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mut stream = Cursor::new(source.to_vec());
+        let gltf_io = GltfIO::new("glb");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            gltf_io.write_cai(&mut stream, &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_stream_object_locations() {
+        let gltf_io = GltfIO::new("glb");
+
+        for fixture in ["sample1.glb", "sample1.gltf"] {
+            let mut stream = File::open(fixture_path(fixture)).unwrap();
+            let positions = gltf_io
+                .get_object_locations_from_stream(&mut stream)
+                .unwrap();
+
+            // the positions describe the asset with a placeholder manifest store
+            let mut output_stream = Cursor::new(Vec::new());
+            gltf_io
+                .write_cai(&mut stream, &mut output_stream, &[1, 2, 3, 4])
+                .unwrap();
+            let output = output_stream.into_inner();
+
+            let cai_pos = positions
+                .iter()
+                .find(|pos| pos.htype == HashBlockObjectType::Cai)
+                .unwrap();
+            let total: usize = positions.iter().map(|p| p.length).sum();
+            assert_eq!(total, output.len());
+
+            if fixture.ends_with("glb") {
+                assert_eq!(cai_pos.length, 12);
+                assert_eq!(&output[cai_pos.offset + 4..cai_pos.offset + 8], b"C2PA");
+            } else {
+                assert_eq!(
+                    &output[cai_pos.offset..cai_pos.offset + cai_pos.length],
+                    b"AQIDBA=="
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let gltf_io = GltfIO::new("glb");
+        let temp_dir = tempdir().unwrap();
+
+        for fixture in ["sample1.glb", "sample1.gltf"] {
+            let output = temp_dir_path(&temp_dir, fixture);
+            std::fs::copy(fixture_path(fixture), &output).unwrap();
+
+            gltf_io.save_cai_store(&output, &test_store()).unwrap();
+            assert_eq!(gltf_io.read_cai_store(&output).unwrap(), test_store());
+
+            gltf_io.remove_cai_store(&output).unwrap();
+
+            // read back in asset, JumbfNotFound is expected since it was removed
+            match gltf_io.read_cai_store(&output) {
+                Err(Error::JumbfNotFound) => (),
+                _ => unreachable!(),
+            }
+        }
+
+        // GLB chunks are restored exactly
+        let source_bytes = std::fs::read(fixture_path("sample1.glb")).unwrap();
+        let output_bytes = std::fs::read(temp_dir_path(&temp_dir, "sample1.glb")).unwrap();
+        assert_eq!(output_bytes, source_bytes);
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.glb");
+        let mut stream = Cursor::new(source.to_vec());
+        let gltf_io = GltfIO::new("glb");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        gltf_io
+            .write_cai(&mut stream, &mut output_stream, &test_store())
+            .unwrap();
+
+        let box_map = gltf_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(names, vec!["glTF", "JSON", "BIN", C2PA_BOXHASH]);
+
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+
+        // glTF JSON has no boxes to hash
+        let mut gltf_stream = File::open(fixture_path("sample1.gltf")).unwrap();
+        assert!(matches!(
+            gltf_io.get_box_map(&mut gltf_stream),
+            Err(Error::InvalidAsset(_))
+        ));
+    }
+}
@@ -0,0 +1,832 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! JPEG 2000 (JP2 and JPX) support.
+//!
+//! The manifest store is a top level `jumb` superbox placed directly after the JP2 header
+//! box.  XMP is stored in a `uuid` box using the XMP UUID, ahead of the first codestream.
+
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        ComposedManifestRef, HashBlockObjectType, HashObjectPositions, RemoteRefEmbed,
+        RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+// JPEG 2000 signature box
+const JP2_SIG_BOX: [u8; 12] = [
+    0x00, 0x00, 0x00, 0x0c, b'j', b'P', b' ', b' ', 0x0d, 0x0a, 0x87, 0x0a,
+];
+
+const FTYP_BOX: [u8; 4] = *b"ftyp";
+const READER_REQUIREMENTS_BOX: [u8; 4] = *b"rreq";
+const HEADER_BOX: [u8; 4] = *b"jp2h";
+const JUMB_BOX: [u8; 4] = *b"jumb";
+const JUMD_BOX: [u8; 4] = *b"jumd";
+const UUID_BOX: [u8; 4] = *b"uuid";
+const CODESTREAM_BOX: [u8; 4] = *b"jp2c";
+const FRAGMENT_TABLE_BOX: [u8; 4] = *b"ftbl";
+
+// UUID of a uuid box holding XMP
+const XMP_UUID: [u8; 16] = [
+    0xbe, 0x7a, 0xcf, 0xcb, 0x97, 0xa9, 0x42, 0xe8, 0x9c, 0x71, 0x99, 0x94, 0x91, 0xe3, 0xaf, 0xac,
+];
+
+// JUMBF description type of a C2PA manifest store ("c2pa")
+const C2PA_STORE_UUID: [u8; 16] = [
+    0x63, 0x32, 0x70, 0x61, 0x00, 0x11, 0x00, 0x10, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
+];
+
+const BOX_HDR_LEN: u64 = 8;
+const LARGE_BOX_HDR_LEN: u64 = 16;
+
+static SUPPORTED_TYPES: [&str; 5] = ["jp2", "jpx", "jpf", "image/jp2", "image/jpx"];
+
+#[derive(Clone, Debug)]
+struct Jp2BoxPos {
+    pub start: u64,
+    pub length: u64,
+    pub hdr_len: u64,
+    pub box_type: [u8; 4],
+    pub is_c2pa: bool,
+    pub is_xmp: bool,
+}
+
+impl Jp2BoxPos {
+    pub fn end(&self) -> u64 {
+        self.start + self.length
+    }
+
+    fn is_codestream(&self) -> bool {
+        self.box_type == CODESTREAM_BOX || self.box_type == FRAGMENT_TABLE_BOX
+    }
+
+    fn is_header(&self) -> bool {
+        self.box_type == FTYP_BOX
+            || self.box_type == READER_REQUIREMENTS_BOX
+            || self.box_type == HEADER_BOX
+    }
+}
+
+fn jp2_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("JPEG 2000 out of range".to_string())
+}
+
+// check whether a jumb box contains a C2PA manifest store
+fn is_c2pa_jumb<R: Read + Seek + ?Sized>(f: &mut R, data_start: u64) -> Result<bool> {
+    f.seek(SeekFrom::Start(data_start))?;
+
+    let mut jumd_hdr = [0u8; 8];
+    let mut jumd_uuid = [0u8; 16];
+    if f.read_exact(&mut jumd_hdr).is_err() || f.read_exact(&mut jumd_uuid).is_err() {
+        return Ok(false);
+    }
+
+    Ok(jumd_hdr[4..] == JUMD_BOX && jumd_uuid == C2PA_STORE_UUID)
+}
+
+// check whether a uuid box contains XMP
+fn is_xmp_uuid<R: Read + Seek + ?Sized>(f: &mut R, data_start: u64) -> Result<bool> {
+    f.seek(SeekFrom::Start(data_start))?;
+
+    let mut uuid = [0u8; 16];
+    if f.read_exact(&mut uuid).is_err() {
+        return Ok(false);
+    }
+
+    Ok(uuid == XMP_UUID)
+}
+
+// get the top level boxes of a JPEG 2000 file
+fn get_jp2_box_positions<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<Jp2BoxPos>> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    let mut box_positions: Vec<Jp2BoxPos> = Vec::new();
+
+    // check JPEG 2000 signature box
+    let mut sig = [0u8; 12];
+    f.rewind()?;
+    f.read_exact(&mut sig)
+        .map_err(|_err| Error::InvalidAsset("JPEG 2000 invalid".to_string()))?;
+    if sig != JP2_SIG_BOX {
+        return Err(Error::InvalidAsset("JPEG 2000 invalid".to_string()));
+    }
+    f.rewind()?;
+
+    let mut current_pos = 0;
+    while current_pos < stream_len {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let size = f.read_u32::<BigEndian>().map_err(jp2_err)?;
+        let mut box_type = [0u8; 4];
+        f.read_exact(&mut box_type).map_err(jp2_err)?;
+
+        let (length, hdr_len) = match size {
+            0 => (stream_len - current_pos, BOX_HDR_LEN), // box extends to the end of the file
+            1 => (
+                f.read_u64::<BigEndian>().map_err(jp2_err)?,
+                LARGE_BOX_HDR_LEN,
+            ),
+            _ => (size as u64, BOX_HDR_LEN),
+        };
+
+        if length < hdr_len || current_pos + length > stream_len {
+            return Err(Error::InvalidAsset("JPEG 2000 bad box size".to_string()));
+        }
+
+        let is_c2pa = box_type == JUMB_BOX && is_c2pa_jumb(f, current_pos + hdr_len)?;
+        let is_xmp = box_type == UUID_BOX && is_xmp_uuid(f, current_pos + hdr_len)?;
+
+        box_positions.push(Jp2BoxPos {
+            start: current_pos,
+            length,
+            hdr_len,
+            box_type,
+            is_c2pa,
+            is_xmp,
+        });
+
+        current_pos += length;
+    }
+
+    // the file type box must follow the signature
+    if box_positions.len() < 2 || box_positions[1].box_type != FTYP_BOX {
+        return Err(Error::InvalidAsset("JPEG 2000 missing ftyp".to_string()));
+    }
+
+    Ok(box_positions)
+}
+
+// create an ISO BMFF style box
+fn make_box(box_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut jp2_box = Vec::with_capacity(data.len() + LARGE_BOX_HDR_LEN as usize);
+
+    let length = data.len() as u64 + BOX_HDR_LEN;
+    if length <= u32::MAX as u64 {
+        jp2_box.extend_from_slice(&(length as u32).to_be_bytes());
+        jp2_box.extend_from_slice(box_type);
+    } else {
+        jp2_box.extend_from_slice(&1u32.to_be_bytes());
+        jp2_box.extend_from_slice(box_type);
+        jp2_box.extend_from_slice(&(data.len() as u64 + LARGE_BOX_HDR_LEN).to_be_bytes());
+    }
+    jp2_box.extend_from_slice(data);
+
+    jp2_box
+}
+
+// empty C2PA manifest store superbox used to reserve a location for the manifest
+fn make_placeholder_store() -> Vec<u8> {
+    let mut jumd = Vec::new();
+    jumd.extend_from_slice(&C2PA_STORE_UUID);
+    jumd.push(0x03); // requestable + label present
+    jumd.extend_from_slice(b"c2pa\0");
+
+    make_box(&JUMB_BOX, &make_box(&JUMD_BOX, &jumd))
+}
+
+fn read_all(input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    input_stream.rewind()?;
+    input_stream.read_to_end(&mut buf).map_err(Error::IoError)?;
+    Ok(buf)
+}
+
+fn range_of(jp2_box: &Jp2BoxPos) -> Result<std::ops::Range<usize>> {
+    let start = usize::value_from(jp2_box.start)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    let end = usize::value_from(jp2_box.end())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    Ok(start..end)
+}
+
+// remove the top level boxes matching the predicate
+fn remove_boxes<P>(jp2_buf: &mut Vec<u8>, predicate: P) -> Result<()>
+where
+    P: Fn(&Jp2BoxPos) -> bool,
+{
+    let mut cursor = Cursor::new(&jp2_buf[..]);
+    let bp = get_jp2_box_positions(&mut cursor)?;
+
+    // remove from the back so earlier offsets stay valid
+    for jp2_box in bp.iter().rev().filter(|b| predicate(b)) {
+        jp2_buf.drain(range_of(jp2_box)?);
+    }
+
+    Ok(())
+}
+
+// the manifest store is placed directly after the header box, or after the file type and
+// reader requirements boxes when a JPX file has no header box
+fn manifest_insertion_point(jp2_buf: &[u8]) -> Result<usize> {
+    let mut cursor = Cursor::new(jp2_buf);
+    let bp = get_jp2_box_positions(&mut cursor)?;
+
+    let last_hdr_box = bp
+        .iter()
+        .take_while(|b| !b.is_codestream())
+        .filter(|b| b.is_header())
+        .last()
+        .ok_or(Error::EmbeddingError)?;
+
+    Ok(range_of(last_hdr_box)?.end)
+}
+
+// XMP is placed before the first codestream box
+fn xmp_insertion_point(jp2_buf: &[u8]) -> Result<usize> {
+    let mut cursor = Cursor::new(jp2_buf);
+    let bp = get_jp2_box_positions(&mut cursor)?;
+
+    let codestream = bp
+        .iter()
+        .find(|b| b.is_codestream())
+        .ok_or(Error::InvalidAsset(
+            "JPEG 2000 missing codestream".to_string(),
+        ))?;
+
+    Ok(range_of(codestream)?.start)
+}
+
+fn get_cai_data<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<u8>> {
+    let bp = get_jp2_box_positions(f)?;
+
+    let mut c2pa_boxes = bp.iter().filter(|b| b.is_c2pa);
+    let c2pa_box = c2pa_boxes.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_boxes.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    // the manifest store is the complete jumb superbox
+    let length = usize::value_from(c2pa_box.length)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(c2pa_box.start))?;
+    let mut data: Vec<u8> = vec![0; length];
+    f.read_exact(&mut data[..]).map_err(jp2_err)?;
+
+    Ok(data)
+}
+
+pub struct Jpeg2000IO {}
+
+impl CAIReader for Jpeg2000IO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let cai_data = get_cai_data(asset_reader)?;
+        Ok(cai_data)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, asset_reader: &mut dyn CAIRead) -> Option<String> {
+        let bp = get_jp2_box_positions(asset_reader).ok()?;
+        let xmp_box = bp.into_iter().find(|b| b.is_xmp)?;
+
+        // skip the UUID that starts the box contents
+        let xmp_start = xmp_box.start + xmp_box.hdr_len + XMP_UUID.len() as u64;
+        let length = usize::value_from(xmp_box.end() - xmp_start).ok()?;
+        asset_reader.seek(SeekFrom::Start(xmp_start)).ok()?;
+        let mut data = vec![0u8; length];
+        asset_reader.read_exact(&mut data).ok()?;
+
+        String::from_utf8(data).ok()
+    }
+}
+
+impl CAIWriter for Jpeg2000IO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let mut jp2_buf = read_all(input_stream)?;
+
+        // erase existing manifest stores
+        remove_boxes(&mut jp2_buf, |b| b.is_c2pa)?;
+
+        // the store bytes are a complete jumb superbox
+        let insert_pos = manifest_insertion_point(&jp2_buf)?;
+        jp2_buf.splice(insert_pos..insert_pos, store_bytes.iter().cloned());
+
+        output_stream.rewind()?;
+        output_stream.write_all(&jp2_buf)?;
+
+        Ok(())
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        // Ensure the stream has a manifest store so we can generate the required offsets.
+        let has_manifest = match get_cai_data(input_stream) {
+            Ok(_) | Err(Error::TooManyManifestStores) => true,
+            Err(Error::JumbfNotFound) => false,
+            Err(e) => return Err(e),
+        };
+
+        let mut jp2_stream = Cursor::new(Vec::new());
+        if has_manifest {
+            input_stream.rewind()?;
+            std::io::copy(input_stream, &mut jp2_stream)?;
+        } else {
+            self.write_cai(input_stream, &mut jp2_stream, &make_placeholder_store())?;
+        }
+
+        let bp = get_jp2_box_positions(&mut jp2_stream)?;
+        let file_end = jp2_stream.get_ref().len();
+
+        let c2pa_box = bp
+            .into_iter()
+            .find(|b| b.is_c2pa)
+            .ok_or(Error::JumbfNotFound)?;
+        let c2pa_range = range_of(&c2pa_box)?;
+
+        positions.push(HashObjectPositions {
+            offset: c2pa_range.start,
+            length: c2pa_range.len(),
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of boxes before the manifest store
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: c2pa_range.start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from the manifest store to end
+        positions.push(HashObjectPositions {
+            offset: c2pa_range.end,
+            length: file_end - c2pa_range.end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        let mut jp2_buf = read_all(input_stream)?;
+
+        remove_boxes(&mut jp2_buf, |b| b.is_c2pa)?;
+
+        output_stream.rewind()?;
+        output_stream.write_all(&jp2_buf)?;
+
+        Ok(())
+    }
+}
+
+impl AssetIO for Jpeg2000IO {
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        self.get_object_locations_from_stream(&mut file)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn new(_asset_type: &str) -> Self
+    where
+        Self: Sized,
+    {
+        Jpeg2000IO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(Jpeg2000IO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(Jpeg2000IO::new(asset_type)))
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn composed_data_ref(&self) -> Option<&dyn ComposedManifestRef> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl RemoteRefEmbed for Jpeg2000IO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let output_buf = Vec::new();
+                let mut output_stream = Cursor::new(output_buf);
+
+                // do here so source file is closed after update
+                {
+                    let mut source_stream = std::fs::File::open(asset_path)?;
+                    self.embed_reference_to_stream(
+                        &mut source_stream,
+                        &mut output_stream,
+                        RemoteRefEmbedType::Xmp(manifest_uri),
+                    )?;
+                }
+
+                std::fs::write(asset_path, output_stream.into_inner())?;
+
+                Ok(())
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        source_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                source_stream.rewind()?;
+
+                let xmp = match self.read_xmp(source_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+
+                // update XMP
+                let updated_xmp = add_provenance(&xmp, &manifest_uri)?;
+                let mut xmp_data = XMP_UUID.to_vec();
+                xmp_data.extend_from_slice(updated_xmp.as_bytes());
+                let xmp_box = make_box(&UUID_BOX, &xmp_data);
+
+                let mut jp2_buf = read_all(source_stream)?;
+
+                // replace existing XMP
+                remove_boxes(&mut jp2_buf, |b| b.is_xmp)?;
+                let insert_pos = xmp_insertion_point(&jp2_buf)?;
+                jp2_buf.splice(insert_pos..insert_pos, xmp_box);
+
+                output_stream.rewind()?;
+                output_stream.write_all(&jp2_buf)?;
+
+                Ok(())
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+impl AssetBoxHash for Jpeg2000IO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        let bp = get_jp2_box_positions(input_stream)?;
+
+        let box_maps = bp
+            .iter()
+            .map(|b| {
+                let name = if b.is_c2pa {
+                    C2PA_BOXHASH.to_string()
+                } else {
+                    String::from_utf8_lossy(&b.box_type).to_string()
+                };
+
+                Ok(BoxMap {
+                    names: vec![name],
+                    alg: None,
+                    hash: ByteBuf::from(Vec::new()),
+                    pad: ByteBuf::from(Vec::new()),
+                    range_start: usize::value_from(b.start)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                    range_len: usize::value_from(b.length)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                })
+            })
+            .collect::<Result<Vec<BoxMap>>>()?;
+
+        Ok(box_maps)
+    }
+}
+
+impl ComposedManifestRef for Jpeg2000IO {
+    // the manifest store is already a jumb superbox so it is embedded as is
+    fn compose_manifest(&self, manifest_data: &[u8], _format: &str) -> Result<Vec<u8>> {
+        Ok(manifest_data.to_vec())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+#[allow(clippy::unwrap_used)]
+pub mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::utils::{test, xmp_inmemory_utils::extract_provenance};
+
+    fn test_store() -> Vec<u8> {
+        // jumb superbox with a c2pa description box and some content
+        let mut store = make_placeholder_store();
+        let content = make_box(b"json", b"{}");
+        store.extend_from_slice(&content);
+        let len = store.len() as u32;
+        store[..4].copy_from_slice(&len.to_be_bytes());
+        store
+    }
+
+    #[test]
+    fn test_jp2_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jp2");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let bp = get_jp2_box_positions(&mut stream).unwrap();
+        let types: Vec<&[u8; 4]> = bp.iter().map(|b| &b.box_type).collect();
+        assert_eq!(types, vec![b"jP  ", b"ftyp", b"jp2h", b"jp2c"]);
+        assert_eq!(bp[3].end(), source.len() as u64);
+    }
+
+    #[test]
+    fn test_write_cai() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jp2");
+        let mut stream = Cursor::new(source.to_vec());
+        let jp2_io = Jpeg2000IO {};
+
+        // no cai data present in stream.
+        assert!(matches!(
+            jp2_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        let store = test_store();
+        let mut output_stream = Cursor::new(Vec::new());
+        jp2_io
+            .write_cai(&mut stream, &mut output_stream, &store)
+            .unwrap();
+
+        assert_eq!(jp2_io.read_cai(&mut output_stream).unwrap(), store);
+
+        // manifest store goes directly after the header box
+        let bp = get_jp2_box_positions(&mut output_stream).unwrap();
+        assert_eq!(&bp[2].box_type, b"jp2h");
+        assert!(bp[3].is_c2pa);
+        assert_eq!(bp[3].start, bp[2].end());
+
+        // replacing keeps a single store
+        let new_store = make_placeholder_store();
+        let mut replaced_stream = Cursor::new(Vec::new());
+        jp2_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &new_store)
+            .unwrap();
+        assert_eq!(jp2_io.read_cai(&mut replaced_stream).unwrap(), new_store);
+        assert_eq!(
+            replaced_stream.get_ref().len(),
+            source.len() + new_store.len()
+        );
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mut stream = Cursor::new(source.to_vec());
+        let jp2_io = Jpeg2000IO {};
+
+        let output: Vec<u8> = Vec::new();
+        let mut output_stream = Cursor::new(output);
+        assert!(matches!(
+            jp2_io.write_cai(&mut stream, &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_stream_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jp2");
+        let mut stream = Cursor::new(source.to_vec());
+        let jp2_io = Jpeg2000IO {};
+
+        let positions = jp2_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_pos = positions
+            .iter()
+            .find(|pos| pos.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        assert_eq!(cai_pos.offset, 77);
+        assert_eq!(cai_pos.length, make_placeholder_store().len());
+
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, source.len() + cai_pos.length);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = test::fixture_path("sample1.jp2");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output = test::temp_dir_path(&temp_dir, "sample1_tmp.jp2");
+        std::fs::copy(source, &output).unwrap();
+
+        let jp2_io = Jpeg2000IO {};
+        jp2_io.save_cai_store(&output, &test_store()).unwrap();
+        assert_eq!(jp2_io.read_cai_store(&output).unwrap(), test_store());
+
+        jp2_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match jp2_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        let source_bytes = include_bytes!("../../tests/fixtures/sample1.jp2");
+        assert_eq!(std::fs::read(&output).unwrap(), source_bytes.to_vec());
+    }
+
+    #[test]
+    fn test_jp2_xmp_write() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jp2");
+        let mut stream = Cursor::new(source.to_vec());
+        let jp2_io = Jpeg2000IO {};
+
+        assert!(jp2_io.read_xmp(&mut stream).is_none());
+
+        let mut output_stream = Cursor::new(Vec::new());
+        jp2_io
+            .embed_reference_to_stream(
+                &mut stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let xmp = jp2_io.read_xmp(&mut output_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/manifest.c2pa"
+        );
+
+        // updating replaces the existing XMP box, which stays before the codestream
+        let mut updated_stream = Cursor::new(Vec::new());
+        jp2_io
+            .embed_reference_to_stream(
+                &mut output_stream,
+                &mut updated_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/updated.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let bp = get_jp2_box_positions(&mut updated_stream).unwrap();
+        let types: Vec<&[u8; 4]> = bp.iter().map(|b| &b.box_type).collect();
+        assert_eq!(types, vec![b"jP  ", b"ftyp", b"jp2h", b"uuid", b"jp2c"]);
+        assert!(bp[3].is_xmp);
+
+        let xmp = jp2_io.read_xmp(&mut updated_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/updated.c2pa"
+        );
+
+        // the manifest store still goes ahead of the XMP
+        let mut signed_stream = Cursor::new(Vec::new());
+        jp2_io
+            .write_cai(&mut updated_stream, &mut signed_stream, &test_store())
+            .unwrap();
+        let bp = get_jp2_box_positions(&mut signed_stream).unwrap();
+        assert!(bp[3].is_c2pa);
+        assert!(bp[4].is_xmp);
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jp2");
+        let mut stream = Cursor::new(source.to_vec());
+        let jp2_io = Jpeg2000IO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        jp2_io
+            .write_cai(&mut stream, &mut output_stream, &test_store())
+            .unwrap();
+
+        let box_map = jp2_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(names, vec!["jP  ", "ftyp", "jp2h", C2PA_BOXHASH, "jp2c"]);
+
+        // the box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+
+    #[test]
+    fn test_embeddable_manifest() {
+        let jp2_io = Jpeg2000IO {};
+
+        let source = include_bytes!("../../tests/fixtures/sample1.jp2");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let ol = jp2_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_loc = ol
+            .iter()
+            .find(|o| o.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        let manifest = test_store();
+        let em = jp2_io
+            .composed_data_ref()
+            .unwrap()
+            .compose_manifest(&manifest, "jp2")
+            .unwrap();
+
+        // insert new manifest
+        let mut out_stream = Cursor::new(Vec::new());
+        out_stream.write_all(&source[..cai_loc.offset]).unwrap();
+        out_stream.write_all(&em).unwrap();
+        out_stream.write_all(&source[cai_loc.offset..]).unwrap();
+
+        // read manifest back in from new in-memory JPEG 2000
+        let restored_manifest = jp2_io.read_cai(&mut out_stream).unwrap();
+        assert_eq!(&manifest, &restored_manifest);
+    }
+}
@@ -14,17 +14,18 @@
 use std::{
     collections::HashMap,
     fs::File,
-    io::{BufReader, Cursor, Write},
+    io::{BufReader, Cursor, Read, SeekFrom, Write},
     path::*,
 };
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
 use img_parts::{
     jpeg::{
         markers::{self, APP0, APP15, COM, DQT, DRI, P, RST0, RST7, SOF0, SOF15, SOS, Z},
-        Jpeg, JpegSegment,
+        JpegSegment,
     },
-    Bytes, DynImage,
+    Bytes,
 };
 use serde_bytes::ByteBuf;
 use tempfile::Builder;
@@ -49,6 +50,12 @@ const MAX_JPEG_MARKER_SIZE: usize = 64000; // technically it's 64K but a bit sma
 
 const C2PA_MARKER: [u8; 4] = [0x63, 0x32, 0x70, 0x61];
 
+const TEM: u8 = 0x01; // temporary private use marker
+
+fn jpeg_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("JPEG out of range".to_string())
+}
+
 fn vec_compare(va: &[u8], vb: &[u8]) -> bool {
     (va.len() == vb.len()) &&  // zip stops at the shortest
      va.iter()
@@ -67,103 +74,244 @@ fn extract_xmp(seg: &JpegSegment) -> Option<String> {
     }
 }
 
-// Extract XMP from bytes.
-fn xmp_from_bytes(asset_bytes: &[u8]) -> Option<String> {
-    if let Ok(jpeg) = Jpeg::from_bytes(Bytes::copy_from_slice(asset_bytes)) {
-        let segs = jpeg.segments_by_marker(markers::APP1);
-        let xmp: String = segs.filter_map(extract_xmp).collect();
-        Some(xmp)
-    } else {
-        None
-    }
+// markers that stand alone without a length field
+fn is_standalone(marker: u8) -> bool {
+    matches!(marker, markers::SOI | markers::EOI | RST0..=RST7 | TEM)
 }
 
-fn add_required_segs_to_stream(
-    input_stream: &mut dyn CAIRead,
-    output_stream: &mut dyn CAIReadWrite,
-) -> Result<()> {
-    let mut buf: Vec<u8> = Vec::new();
-    input_stream.rewind()?;
-    input_stream.read_to_end(&mut buf).map_err(Error::IoError)?;
-    input_stream.rewind()?;
+// build a marker segment from its contents
+fn make_segment(marker: u8, contents: &[u8]) -> Result<Vec<u8>> {
+    let len = u16::try_from(contents.len() + 2)
+        .map_err(|_err| Error::InvalidAsset("JPEG segment too large".to_string()))?;
 
-    let dimg_opt = DynImage::from_bytes(buf.into())
-        .map_err(|_err| Error::InvalidAsset("Could not parse input JPEG".to_owned()))?;
+    let mut segment = Vec::with_capacity(contents.len() + 4);
+    segment.push(P);
+    segment.push(marker);
+    segment.write_u16::<BigEndian>(len)?;
+    segment.extend_from_slice(contents);
 
-    if let Some(DynImage::Jpeg(jpeg)) = dimg_opt {
-        // check for JUMBF Seg
-        let cai_app11 = get_cai_segments(&jpeg)?; // make sure we only check for C2PA segments
+    Ok(segment)
+}
 
-        if cai_app11.is_empty() {
-            // create dummy JUMBF seg
-            let mut no_bytes: Vec<u8> = vec![0; 50]; // enough bytes to be valid
-            no_bytes.splice(16..20, C2PA_MARKER); // cai UUID signature
-            let aio = JpegIO {};
-            aio.write_cai(input_stream, output_stream, &no_bytes)?;
-        } else {
-            // just clone
-            input_stream.rewind()?;
-            output_stream.rewind()?;
-            std::io::copy(input_stream, output_stream)?;
+// A marker segment from the JPEG header.  The offset and length include the marker.
+struct HeaderSegment {
+    offset: u64,
+    len: u64,
+    marker: u8,
+    contents: Vec<u8>,
+}
+
+// Reads the JPEG header one marker segment at a time.  Reading stops after the first
+// SOS segment so the entropy coded scan data is never read, and only the current segment
+// is held in memory.
+struct HeaderReader<'a> {
+    reader: BufReader<&'a mut dyn CAIRead>,
+    pos: u64,
+    done: bool,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn new(input_stream: &'a mut dyn CAIRead) -> Result<Self> {
+        input_stream.rewind()?;
+        let mut reader = BufReader::new(input_stream);
+
+        let mut soi = [0u8; 2];
+        reader
+            .read_exact(&mut soi)
+            .map_err(|_err| Error::InvalidAsset("Could not parse input JPEG".to_owned()))?;
+        if soi != [P, markers::SOI] {
+            return Err(Error::InvalidAsset("Could not parse input JPEG".to_owned()));
         }
-    } else {
-        return Err(Error::UnsupportedType);
+
+        Ok(HeaderReader {
+            reader,
+            pos: 2,
+            done: false,
+        })
     }
 
-    Ok(())
+    fn next_segment(&mut self) -> Result<Option<HeaderSegment>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let offset = self.pos;
+        let mut len = 2;
+
+        if self.reader.read_u8().map_err(jpeg_err)? != P {
+            return Err(Error::InvalidAsset(
+                "Cannot read segment marker".to_string(),
+            ));
+        }
+
+        // a marker may be preceded by any number of fill bytes
+        let mut marker = self.reader.read_u8().map_err(jpeg_err)?;
+        while marker == P {
+            marker = self.reader.read_u8().map_err(jpeg_err)?;
+            len += 1;
+        }
+
+        let mut contents = Vec::new();
+        if !is_standalone(marker) {
+            let seg_len = self.reader.read_u16::<BigEndian>().map_err(jpeg_err)?;
+            if seg_len < 2 {
+                return Err(Error::InvalidAsset("Bad JPEG segment length".to_string()));
+            }
+            contents.resize(seg_len as usize - 2, 0);
+            self.reader.read_exact(&mut contents).map_err(jpeg_err)?;
+            len += u64::from(seg_len);
+        }
+
+        self.pos += len;
+        self.done = marker == SOS || marker == markers::EOI;
+
+        Ok(Some(HeaderSegment {
+            offset,
+            len,
+            marker,
+            contents,
+        }))
+    }
+
+    // offset of the first byte after the segments read so far
+    fn position(&self) -> u64 {
+        self.pos
+    }
 }
 
-// all cai specific segments
-fn get_cai_segments(jpeg: &img_parts::jpeg::Jpeg) -> Result<Vec<usize>> {
-    let mut cai_segs: Vec<usize> = Vec::new();
+// Identifies the APP11 segments holding the manifest store.  The first segment starts a
+// c2pa JUMBF box and the following segments continue it with the same box instance number.
+#[derive(Default)]
+struct CaiSegmentTracker {
+    cai_en: Vec<u8>,
+    cai_seg_cnt: u32,
+}
 
-    let segments = jpeg.segments();
+impl CaiSegmentTracker {
+    fn is_cai(&mut self, marker: u8, contents: &[u8]) -> bool {
+        // we need at least 16 bytes in each segment for CAI
+        if marker != markers::APP11 || contents.len() <= 16 {
+            return false;
+        }
 
-    let mut cai_en: Vec<u8> = Vec::new();
-    let mut cai_seg_cnt: u32 = 0;
+        let en = &contents[2..4];
+        if self.cai_seg_cnt > 0 && vec_compare(&self.cai_en, en) {
+            self.cai_seg_cnt += 1;
+            true
+        } else if contents.len() > 28 && vec_compare(&C2PA_MARKER, &contents[24..28]) {
+            self.cai_seg_cnt = 1;
+            self.cai_en = en.to_vec(); // store the identifier
+            true
+        } else {
+            false
+        }
+    }
+}
 
-    for (i, segment) in segments.iter().enumerate() {
-        let raw_bytes = segment.contents();
-        let seg_type = segment.marker();
+#[derive(Clone, Debug)]
+struct SegmentPos {
+    offset: u64,
+    len: u64,
+    marker: u8,
+    htype: HashBlockObjectType,
+    is_xmp: bool,
+}
 
-        if raw_bytes.len() > 16 && seg_type == markers::APP11 {
-            // we need at least 16 bytes in each segment for CAI
-            let mut raw_vec = raw_bytes.to_vec();
-            let _ci = raw_vec.as_mut_slice()[0..2].to_vec();
-            let en = raw_vec.as_mut_slice()[2..4].to_vec();
-            let mut z_vec = Cursor::new(raw_vec.as_mut_slice()[4..8].to_vec());
-            let _z = z_vec.read_u32::<BigEndian>()?;
+// The header segments up to and including SOS, and where the scan data starts.
+struct JpegHeader {
+    segments: Vec<SegmentPos>,
+    scan_start: u64,
+    xmp: Option<String>, // contents of the first XMP segment
+}
 
-            let is_cai_continuation = vec_compare(&cai_en, &en);
+fn read_header(input_stream: &mut dyn CAIRead) -> Result<JpegHeader> {
+    let mut reader = HeaderReader::new(input_stream)?;
+    let mut tracker = CaiSegmentTracker::default();
+    let mut segments = Vec::new();
+    let mut xmp = None;
+
+    while let Some(segment) = reader.next_segment()? {
+        let is_cai = tracker.is_cai(segment.marker, &segment.contents);
+        let is_xmp = segment.marker == markers::APP1 && segment.contents.starts_with(XMP_SIGNATURE);
+
+        let htype = if is_cai {
+            HashBlockObjectType::Cai
+        } else if segment.marker == markers::APP1 {
+            // XMP marker or EXIF or Extra XMP
+            HashBlockObjectType::Xmp
+        } else {
+            HashBlockObjectType::Other
+        };
 
-            if cai_seg_cnt > 0 && is_cai_continuation {
-                cai_seg_cnt += 1;
-                cai_segs.push(i);
-            } else {
-                // check if this is a CAI JUMBF block
-                let jumb_type = &raw_vec.as_mut_slice()[24..28];
-                let is_cai = vec_compare(&C2PA_MARKER, jumb_type);
-                if is_cai {
-                    cai_segs.push(i);
-                    cai_seg_cnt = 1;
-                    cai_en.clone_from(&en); // store the identifier
-                }
-            }
+        if is_xmp && !segments.iter().any(|s: &SegmentPos| s.is_xmp) {
+            let seg = JpegSegment::new_with_contents(markers::APP1, Bytes::from(segment.contents));
+            xmp = extract_xmp(&seg);
         }
+
+        segments.push(SegmentPos {
+            offset: segment.offset,
+            len: segment.len,
+            marker: segment.marker,
+            htype,
+            is_xmp,
+        });
     }
 
-    Ok(cai_segs)
+    Ok(JpegHeader {
+        segments,
+        scan_start: reader.position(),
+        xmp,
+    })
+}
+
+// A piece of the rewritten header, either copied from the source or new segment data.
+enum HeaderPart<'a> {
+    Source(&'a SegmentPos),
+    New(&'a [u8]),
 }
 
-// delete cai segments
-fn delete_cai_segments(jpeg: &mut img_parts::jpeg::Jpeg) -> Result<()> {
-    let cai_segs = get_cai_segments(jpeg)?;
-    let jpeg_segs = jpeg.segments_mut();
+// Places new segments after the first segment, unless the scan starts right away.
+fn insert_after_first<'a>(
+    segments: impl Iterator<Item = &'a SegmentPos>,
+    new_segments: &'a [u8],
+) -> Vec<HeaderPart<'a>> {
+    let mut parts: Vec<HeaderPart> = segments.map(HeaderPart::Source).collect();
+
+    let index = match parts.first() {
+        Some(HeaderPart::Source(seg)) if seg.marker != SOS && seg.marker != markers::EOI => 1,
+        _ => 0,
+    };
+    parts.insert(index, HeaderPart::New(new_segments));
+
+    parts
+}
 
-    // remove cai segments
-    for seg in cai_segs.iter().rev() {
-        jpeg_segs.remove(*seg);
+// Writes SOI and the header parts, then copies the scan data through unchanged.
+fn write_jpeg(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    parts: &[HeaderPart],
+    scan_start: u64,
+) -> Result<()> {
+    output_stream.rewind()?;
+    output_stream.write_all(&[P, markers::SOI])?;
+
+    for part in parts {
+        match part {
+            HeaderPart::Source(seg) => {
+                input_stream.seek(SeekFrom::Start(seg.offset))?;
+                let copied = std::io::copy(&mut input_stream.take(seg.len), output_stream)?;
+                if copied != seg.len {
+                    return Err(Error::InvalidAsset("JPEG segment truncated".to_string()));
+                }
+            }
+            HeaderPart::New(data) => output_stream.write_all(data)?,
+        }
     }
+
+    input_stream.seek(SeekFrom::Start(scan_start))?;
+    std::io::copy(input_stream, output_stream)?;
+
     Ok(())
 }
 
@@ -175,70 +323,57 @@ impl CAIReader for JpegIO {
 
         let mut manifest_store_cnt = 0;
 
-        // load the bytes
-        let mut buf: Vec<u8> = Vec::new();
-
-        asset_reader.rewind()?;
-        asset_reader.read_to_end(&mut buf).map_err(Error::IoError)?;
+        let mut cai_en: Vec<u8> = Vec::new();
+        let mut cai_seg_cnt: u32 = 0;
 
-        let dimg_opt = DynImage::from_bytes(buf.into())
-            .map_err(|_err| Error::InvalidAsset("Could not parse input JPEG".to_owned()))?;
+        // only the header segments are read, the scan data is never loaded
+        let mut reader = HeaderReader::new(asset_reader)?;
+        while let Some(segment) = reader.next_segment()? {
+            if segment.marker != markers::APP11 {
+                continue;
+            }
 
-        if let Some(dimg) = dimg_opt {
-            match dimg {
-                DynImage::Jpeg(jpeg) => {
-                    let app11 = jpeg.segments_by_marker(markers::APP11);
-                    let mut cai_en: Vec<u8> = Vec::new();
-                    let mut cai_seg_cnt: u32 = 0;
-                    for segment in app11 {
-                        let raw_bytes = segment.contents();
-                        if raw_bytes.len() > 16 {
-                            // we need at least 16 bytes in each segment for CAI
-                            let mut raw_vec = raw_bytes.to_vec();
-                            let _ci = raw_vec.as_mut_slice()[0..2].to_vec();
-                            let en = raw_vec.as_mut_slice()[2..4].to_vec();
-                            let mut z_vec = Cursor::new(raw_vec.as_mut_slice()[4..8].to_vec());
-                            let z = z_vec.read_u32::<BigEndian>()?;
-
-                            let is_cai_continuation = vec_compare(&cai_en, &en);
-
-                            if cai_seg_cnt > 0 && is_cai_continuation {
-                                // make sure this is a cai segment for additional segments,
-                                if z <= cai_seg_cnt {
-                                    // this a non contiguous segment with same "en" so a bad set of data
-                                    // reset and continue to search
-                                    cai_en = Vec::new();
-                                    continue;
-                                }
-                                // take out LBox & TBox
-                                buffer.append(&mut raw_vec.as_mut_slice()[16..].to_vec());
-
-                                cai_seg_cnt += 1;
-                            } else if raw_vec.len() > 28 {
-                                // must be at least 28 bytes for this to be a valid JUMBF box
-                                // check if this is a CAI JUMBF block
-                                let jumb_type = &raw_vec.as_mut_slice()[24..28];
-                                let is_cai = vec_compare(&C2PA_MARKER, jumb_type);
-
-                                if is_cai {
-                                    if manifest_store_cnt == 1 {
-                                        return Err(Error::TooManyManifestStores);
-                                    }
-
-                                    buffer.append(&mut raw_vec.as_mut_slice()[8..].to_vec());
-                                    cai_seg_cnt = 1;
-                                    cai_en.clone_from(&en); // store the identifier
-
-                                    manifest_store_cnt += 1;
-                                }
-                            }
+            let raw_vec = segment.contents;
+            if raw_vec.len() > 16 {
+                // we need at least 16 bytes in each segment for CAI
+                let _ci = &raw_vec[0..2];
+                let en = raw_vec[2..4].to_vec();
+                let mut z_vec = Cursor::new(&raw_vec[4..8]);
+                let z = z_vec.read_u32::<BigEndian>()?;
+
+                let is_cai_continuation = vec_compare(&cai_en, &en);
+
+                if cai_seg_cnt > 0 && is_cai_continuation {
+                    // make sure this is a cai segment for additional segments,
+                    if z <= cai_seg_cnt {
+                        // this a non contiguous segment with same "en" so a bad set of data
+                        // reset and continue to search
+                        cai_en = Vec::new();
+                        continue;
+                    }
+                    // take out LBox & TBox
+                    buffer.extend_from_slice(&raw_vec[16..]);
+
+                    cai_seg_cnt += 1;
+                } else if raw_vec.len() > 28 {
+                    // must be at least 28 bytes for this to be a valid JUMBF box
+                    // check if this is a CAI JUMBF block
+                    let jumb_type = &raw_vec[24..28];
+                    let is_cai = vec_compare(&C2PA_MARKER, jumb_type);
+
+                    if is_cai {
+                        if manifest_store_cnt == 1 {
+                            return Err(Error::TooManyManifestStores);
                         }
+
+                        buffer.extend_from_slice(&raw_vec[8..]);
+                        cai_seg_cnt = 1;
+                        cai_en.clone_from(&en); // store the identifier
+
+                        manifest_store_cnt += 1;
                     }
                 }
-                _ => return Err(Error::InvalidAsset("Unknown image format".to_owned())),
-            };
-        } else {
-            return Err(Error::UnsupportedType);
+            }
         }
 
         if buffer.is_empty() {
@@ -250,12 +385,20 @@ impl CAIReader for JpegIO {
 
     // Get XMP block
     fn read_xmp(&self, asset_reader: &mut dyn CAIRead) -> Option<String> {
-        // load the bytes
-        let mut buf: Vec<u8> = Vec::new();
-        match asset_reader.read_to_end(&mut buf) {
-            Ok(_) => xmp_from_bytes(&buf),
-            Err(_) => None,
+        let mut reader = HeaderReader::new(asset_reader).ok()?;
+
+        let mut xmp = String::new();
+        while let Some(segment) = reader.next_segment().ok()? {
+            if segment.marker == markers::APP1 {
+                let seg =
+                    JpegSegment::new_with_contents(markers::APP1, Bytes::from(segment.contents));
+                if let Some(s) = extract_xmp(&seg) {
+                    xmp.push_str(&s);
+                }
+            }
         }
+
+        Some(xmp)
     }
 }
 
@@ -266,169 +409,69 @@ impl CAIWriter for JpegIO {
         output_stream: &mut dyn CAIReadWrite,
         store_bytes: &[u8],
     ) -> Result<()> {
-        let mut buf = Vec::new();
-        // read the whole asset
-        input_stream.rewind()?;
-        input_stream.read_to_end(&mut buf).map_err(Error::IoError)?;
-        let mut jpeg = Jpeg::from_bytes(buf.into()).map_err(|_err| Error::EmbeddingError)?;
-
-        // remove existing CAI segments
-        delete_cai_segments(&mut jpeg)?;
-
-        let jumbf_len = store_bytes.len();
-        let num_segments = (jumbf_len / MAX_JPEG_MARKER_SIZE) + 1;
-        let mut seg_chucks = store_bytes.chunks(MAX_JPEG_MARKER_SIZE);
-
-        for seg in 1..num_segments + 1 {
-            /*
-                If the size of the box payload is less than 2^32-8 bytes,
-                then all fields except the XLBox field, that is: Le, CI, En, Z, LBox and TBox,
-                shall be present in all JPEG XT marker segment representing this box,
-                regardless of whether the marker segments starts this box,
-                or continues a box started by a former JPEG XT Marker segment.
-            */
-            // we need to prefix the JUMBF with the JPEG XT markers (ISO 19566-5)
-            // CI: JPEG extensions marker - JP
-            // En: Box Instance Number  - 0x0001
-            //          (NOTE: can be any unique ID, so we pick one that shouldn't conflict)
-            // Z: Packet sequence number - 0x00000001...
-            let ci = vec![0x4a, 0x50];
-            let en = vec![0x02, 0x11];
-            let z: u32 = u32::try_from(seg)
-                .map_err(|_| Error::InvalidAsset("Too many JUMBF segments".to_string()))?; //seg.to_be_bytes();
-
-            let mut seg_data = Vec::new();
-            seg_data.extend(ci);
-            seg_data.extend(en);
-            seg_data.extend(z.to_be_bytes());
-            if seg > 1 {
-                // the LBox and TBox are already in the JUMBF
-                // but we need to duplicate them in all other segments
-                let lbox_tbox = &store_bytes[..8];
-                seg_data.extend(lbox_tbox);
-            }
-            if seg_chucks.len() > 0 {
-                // make sure we have some...
-                if let Some(next_seg) = seg_chucks.next() {
-                    seg_data.extend(next_seg);
-                }
-            } else {
-                seg_data.extend(store_bytes);
-            }
-
-            let seg_bytes = Bytes::from(seg_data);
-            let app11_segment = JpegSegment::new_with_contents(markers::APP11, seg_bytes);
-            jpeg.segments_mut().insert(seg, app11_segment); // we put this in the beginning...
-        }
-
-        output_stream.rewind()?;
-        jpeg.encoder()
-            .write_to(output_stream)
-            .map_err(|_err| Error::InvalidAsset("JPEG write error".to_owned()))?;
-
-        Ok(())
+        let header = read_header(input_stream)?;
+
+        // replace existing CAI segments with the new ones, we put them at the beginning
+        let cai_segments = self.compose_manifest(store_bytes, "jpeg")?;
+        let parts = insert_after_first(
+            header
+                .segments
+                .iter()
+                .filter(|seg| seg.htype != HashBlockObjectType::Cai),
+            &cai_segments,
+        );
+
+        write_jpeg(input_stream, output_stream, &parts, header.scan_start)
     }
 
     fn get_object_locations_from_stream(
         &self,
         input_stream: &mut dyn CAIRead,
     ) -> Result<Vec<HashObjectPositions>> {
-        let mut cai_en: Vec<u8> = Vec::new();
-        let mut cai_seg_cnt: u32 = 0;
+        let header = read_header(input_stream)?;
+        let file_len = input_stream.seek(SeekFrom::End(0))?;
+
+        // make sure there are CAI segments so we can generate all the required offsets
+        let placeholder: Vec<u8>;
+        let parts = if header
+            .segments
+            .iter()
+            .any(|seg| seg.htype == HashBlockObjectType::Cai)
+        {
+            header.segments.iter().map(HeaderPart::Source).collect()
+        } else {
+            // create dummy JUMBF seg
+            let mut no_bytes: Vec<u8> = vec![0; 50]; // enough bytes to be valid
+            no_bytes.splice(16..20, C2PA_MARKER); // cai UUID signature
+            placeholder = self.compose_manifest(&no_bytes, "jpeg")?;
+            insert_after_first(header.segments.iter(), &placeholder)
+        };
 
         let mut positions: Vec<HashObjectPositions> = Vec::new();
-        let mut curr_offset = 2; // start after JPEG marker
-
-        let output_vec: Vec<u8> = Vec::new();
-        let mut output_stream = Cursor::new(output_vec);
-        // make sure the file has the required segments so we can generate all the required offsets
-        add_required_segs_to_stream(input_stream, &mut output_stream)?;
-
-        let buf: Vec<u8> = output_stream.into_inner();
-
-        let dimg = DynImage::from_bytes(buf.into())
-            .map_err(|e| Error::OtherError(Box::new(e)))?
-            .ok_or(Error::UnsupportedType)?;
-
-        match dimg {
-            DynImage::Jpeg(jpeg) => {
-                for seg in jpeg.segments() {
-                    match seg.marker() {
-                        markers::APP11 => {
-                            // JUMBF marker
-                            let raw_bytes = seg.contents();
-
-                            if raw_bytes.len() > 16 {
-                                // we need at least 16 bytes in each segment for CAI
-                                let mut raw_vec = raw_bytes.to_vec();
-                                let _ci = raw_vec.as_mut_slice()[0..2].to_vec();
-                                let en = raw_vec.as_mut_slice()[2..4].to_vec();
-
-                                let is_cai_continuation = vec_compare(&cai_en, &en);
-
-                                if cai_seg_cnt > 0 && is_cai_continuation {
-                                    cai_seg_cnt += 1;
-
-                                    let v = HashObjectPositions {
-                                        offset: curr_offset,
-                                        length: seg.len_with_entropy(),
-                                        htype: HashBlockObjectType::Cai,
-                                    };
-                                    positions.push(v);
-                                } else {
-                                    // check if this is a CAI JUMBF block
-                                    let jumb_type = raw_vec.as_mut_slice()[24..28].to_vec();
-                                    let is_cai = vec_compare(&C2PA_MARKER, &jumb_type);
-                                    if is_cai {
-                                        cai_seg_cnt = 1;
-                                        cai_en.clone_from(&en); // store the identifier
-
-                                        let v = HashObjectPositions {
-                                            offset: curr_offset,
-                                            length: seg.len_with_entropy(),
-                                            htype: HashBlockObjectType::Cai,
-                                        };
-
-                                        positions.push(v);
-                                    } else {
-                                        // save other for completeness sake
-                                        let v = HashObjectPositions {
-                                            offset: curr_offset,
-                                            length: seg.len_with_entropy(),
-                                            htype: HashBlockObjectType::Other,
-                                        };
-                                        positions.push(v);
-                                    }
-                                }
-                            }
-                        }
-                        markers::APP1 => {
-                            // XMP marker or EXIF or Extra XMP
-                            let v = HashObjectPositions {
-                                offset: curr_offset,
-                                length: seg.len_with_entropy(),
-                                htype: HashBlockObjectType::Xmp,
-                            };
-                            // todo: pick the app1 that is the xmp (not crucial as it gets hashed either way)
-                            positions.push(v);
-                        }
-                        _ => {
-                            // save other for completeness sake
-                            let v = HashObjectPositions {
-                                offset: curr_offset,
-                                length: seg.len_with_entropy(),
-                                htype: HashBlockObjectType::Other,
-                            };
+        let mut curr_offset: u64 = 2; // start after JPEG marker
 
-                            positions.push(v);
-                        }
-                    }
-                    curr_offset += seg.len_with_entropy();
-                }
-            }
-            _ => return Err(Error::InvalidAsset("Unknown image format".to_owned())),
+        for part in &parts {
+            let (length, htype) = match part {
+                HeaderPart::Source(seg) => (seg.len, seg.htype),
+                HeaderPart::New(data) => (data.len() as u64, HashBlockObjectType::Cai),
+            };
+
+            positions.push(HashObjectPositions {
+                offset: usize::value_from(curr_offset).map_err(jpeg_err)?,
+                length: usize::value_from(length).map_err(jpeg_err)?,
+                htype,
+            });
+            curr_offset += length;
         }
 
+        // the scan data through to the end of the file
+        positions.push(HashObjectPositions {
+            offset: usize::value_from(curr_offset).map_err(jpeg_err)?,
+            length: usize::value_from(file_len.saturating_sub(header.scan_start))
+                .map_err(jpeg_err)?,
+            htype: HashBlockObjectType::Other,
+        });
+
         Ok(positions)
     }
 
@@ -437,21 +480,17 @@ impl CAIWriter for JpegIO {
         input_stream: &mut dyn CAIRead,
         output_stream: &mut dyn CAIReadWrite,
     ) -> Result<()> {
-        let mut buf = Vec::new();
-        // read the whole asset
-        input_stream.rewind()?;
-        input_stream.read_to_end(&mut buf).map_err(Error::IoError)?;
-        let mut jpeg = Jpeg::from_bytes(buf.into()).map_err(|_err| Error::EmbeddingError)?;
+        let header = read_header(input_stream)?;
 
         // remove existing CAI segments
-        delete_cai_segments(&mut jpeg)?;
-
-        output_stream.rewind()?;
-        jpeg.encoder()
-            .write_to(output_stream)
-            .map_err(|_err| Error::InvalidAsset("JPEG write error".to_owned()))?;
+        let parts: Vec<HeaderPart> = header
+            .segments
+            .iter()
+            .filter(|seg| seg.htype != HashBlockObjectType::Cai)
+            .map(HeaderPart::Source)
+            .collect();
 
-        Ok(())
+        write_jpeg(input_stream, output_stream, &parts, header.scan_start)
     }
 }
 
@@ -491,26 +530,17 @@ impl AssetIO for JpegIO {
     }
 
     fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
-        let input = std::fs::read(asset_path).map_err(Error::IoError)?;
-
-        let mut jpeg = Jpeg::from_bytes(input.into()).map_err(|_err| Error::EmbeddingError)?;
+        let mut input_stream = File::open(asset_path).map_err(Error::IoError)?;
 
-        // remove existing CAI segments
-        delete_cai_segments(&mut jpeg)?;
-
-        // save updated file
-        let output = std::fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .truncate(true)
-            .open(asset_path)
-            .map_err(Error::IoError)?;
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
 
-        jpeg.encoder()
-            .write_to(output)
-            .map_err(|_err| Error::InvalidAsset("JPEG write error".to_owned()))?;
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
 
-        Ok(())
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
     }
 
     fn new(_asset_type: &str) -> Self
@@ -585,42 +615,34 @@ impl RemoteRefEmbed for JpegIO {
     ) -> Result<()> {
         match embed_ref {
             crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
-                let mut buf = Vec::new();
-                // read the whole asset
-                source_stream.rewind()?;
-                source_stream
-                    .read_to_end(&mut buf)
-                    .map_err(Error::IoError)?;
-                let mut jpeg =
-                    Jpeg::from_bytes(buf.into()).map_err(|_err| Error::EmbeddingError)?;
-
-                // find any existing XMP segment and remember where it was
-                let mut xmp = MIN_XMP.to_string(); // default minimal XMP
-                let mut xmp_index = None;
-                let segments = jpeg.segments_mut();
-                for (i, seg) in segments.iter().enumerate() {
-                    if seg.marker() == markers::APP1 && seg.contents().starts_with(XMP_SIGNATURE) {
-                        xmp = extract_xmp(seg).unwrap_or_else(|| xmp.clone());
-                        xmp_index = Some(i);
-                        break;
-                    }
-                }
-                // add provenance and JPEG XMP prefix
+                let header = read_header(source_stream)?;
+
+                // add provenance to any existing XMP and the JPEG XMP prefix
+                let xmp = header.xmp.as_deref().unwrap_or(MIN_XMP);
                 let xmp = format!(
                     "http://ns.adobe.com/xap/1.0/\0 {}",
-                    add_provenance(&xmp, &manifest_uri)?
+                    add_provenance(xmp, &manifest_uri)?
                 );
-                let segment = JpegSegment::new_with_contents(markers::APP1, Bytes::from(xmp));
-                // insert or add the segment
-                match xmp_index {
-                    Some(i) => segments[i] = segment,
-                    None => segments.insert(1, segment),
-                }
+                let xmp_segment = make_segment(markers::APP1, xmp.as_bytes())?;
+
+                // replace the existing XMP segment or add a new one
+                let parts = match header.segments.iter().position(|seg| seg.is_xmp) {
+                    Some(i) => header
+                        .segments
+                        .iter()
+                        .enumerate()
+                        .map(|(j, seg)| {
+                            if i == j {
+                                HeaderPart::New(&xmp_segment)
+                            } else {
+                                HeaderPart::Source(seg)
+                            }
+                        })
+                        .collect(),
+                    None => insert_after_first(header.segments.iter(), &xmp_segment),
+                };
 
-                jpeg.encoder()
-                    .write_to(output_stream)
-                    .map_err(|_err| Error::InvalidAsset("JPEG write error".to_owned()))?;
-                Ok(())
+                write_jpeg(source_stream, output_stream, &parts, header.scan_start)
             }
             crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
             crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
@@ -1270,4 +1292,103 @@ pub mod tests {
 
         assert_eq!(&curr_manifest, &restored_manifest);
     }
+
+    // a fake JUMBF store large enough to span several APP11 segments
+    fn large_store() -> Vec<u8> {
+        let mut store = vec![0x55u8; 150000];
+        store[0..4].copy_from_slice(&150000u32.to_be_bytes());
+        store[4..8].copy_from_slice(b"jumb");
+        store[16..20].copy_from_slice(&C2PA_MARKER);
+        store
+    }
+
+    #[test]
+    fn test_write_cai_preserves_scan_data() {
+        let source_bytes = include_bytes!("../../tests/fixtures/no_manifest.jpg");
+        let jpeg_io = JpegIO {};
+
+        let mut source_stream = Cursor::new(source_bytes.to_vec());
+        let source_header = read_header(&mut source_stream).unwrap();
+
+        let store = large_store();
+        let mut output_stream = Cursor::new(Vec::new());
+        jpeg_io
+            .write_cai(&mut source_stream, &mut output_stream, &store)
+            .unwrap();
+
+        assert_eq!(jpeg_io.read_cai(&mut output_stream).unwrap(), store);
+
+        // the store is split across segments placed after the first segment
+        let output_header = read_header(&mut output_stream).unwrap();
+        let cai_segs: Vec<&SegmentPos> = output_header
+            .segments
+            .iter()
+            .filter(|seg| seg.htype == HashBlockObjectType::Cai)
+            .collect();
+        assert_eq!(cai_segs.len(), 3);
+        assert_eq!(cai_segs[0].offset, 2 + source_header.segments[0].len);
+
+        // the scan data is copied through unchanged
+        let output_bytes = output_stream.into_inner();
+        assert_eq!(
+            &source_bytes[source_header.scan_start as usize..],
+            &output_bytes[output_header.scan_start as usize..]
+        );
+
+        // removing the store restores the original file
+        let mut restored = Cursor::new(Vec::new());
+        jpeg_io
+            .remove_cai_store_from_stream(&mut Cursor::new(output_bytes), &mut restored)
+            .unwrap();
+        assert_eq!(restored.into_inner(), source_bytes.to_vec());
+    }
+
+    #[test]
+    fn test_read_stops_at_scan_data() {
+        let source_bytes = include_bytes!("../../tests/fixtures/CA.jpg");
+        let jpeg_io = JpegIO {};
+
+        let mut source_stream = Cursor::new(source_bytes.to_vec());
+        let header = read_header(&mut source_stream).unwrap();
+        assert_eq!(header.segments.last().unwrap().marker, SOS);
+        let manifest = jpeg_io.read_cai(&mut source_stream).unwrap();
+
+        // nothing past the start of the scan data is needed for reading
+        let mut header_only = Cursor::new(source_bytes[..header.scan_start as usize].to_vec());
+        assert_eq!(jpeg_io.read_cai(&mut header_only).unwrap(), manifest);
+        assert!(jpeg_io.read_xmp(&mut header_only).is_some());
+    }
+
+    #[test]
+    fn test_object_locations_cover_file() {
+        let jpeg_io = JpegIO {};
+
+        for (fixture, has_manifest) in [("CA.jpg", true), ("no_manifest.jpg", false)] {
+            let source = crate::utils::test::fixture_path(fixture);
+            let file_len = std::fs::metadata(&source).unwrap().len() as usize;
+
+            let ol = jpeg_io.get_object_locations(&source).unwrap();
+
+            // positions are contiguous from just after SOI
+            let mut offset = 2;
+            for pos in &ol {
+                assert_eq!(pos.offset, offset);
+                offset += pos.length;
+            }
+
+            let cai_len: usize = ol
+                .iter()
+                .filter(|o| o.htype == HashBlockObjectType::Cai)
+                .map(|o| o.length)
+                .sum();
+            assert!(cai_len > 0);
+
+            if has_manifest {
+                assert_eq!(offset, file_len);
+            } else {
+                // a placeholder store is accounted for
+                assert_eq!(offset, file_len + cai_len);
+            }
+        }
+    }
 }
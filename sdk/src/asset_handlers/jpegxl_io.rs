@@ -0,0 +1,882 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        ComposedManifestRef, HashBlockObjectType, HashObjectPositions, RemoteRefEmbed,
+        RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+// signature of a bare JPEG XL codestream
+const JXL_CODESTREAM_SIG: [u8; 2] = [0xff, 0x0a];
+
+// JPEG XL signature box followed by the file type box
+const JXL_SIG_BOX: [u8; 12] = [
+    0x00, 0x00, 0x00, 0x0c, b'J', b'X', b'L', b' ', 0x0d, 0x0a, 0x87, 0x0a,
+];
+const JXL_FTYP_BOX: [u8; 20] = [
+    0x00, 0x00, 0x00, 0x14, b'f', b't', b'y', b'p', b'j', b'x', b'l', b' ', 0x00, 0x00, 0x00, 0x00,
+    b'j', b'x', b'l', b' ',
+];
+
+const FTYP_BOX: [u8; 4] = *b"ftyp";
+const LEVEL_BOX: [u8; 4] = *b"jxll";
+const JUMB_BOX: [u8; 4] = *b"jumb";
+const JUMD_BOX: [u8; 4] = *b"jumd";
+const XMP_BOX: [u8; 4] = *b"xml ";
+const CODESTREAM_BOX: [u8; 4] = *b"jxlc";
+const PARTIAL_CODESTREAM_BOX: [u8; 4] = *b"jxlp";
+
+// JUMBF description type of a C2PA manifest store ("c2pa")
+const C2PA_STORE_UUID: [u8; 16] = [
+    0x63, 0x32, 0x70, 0x61, 0x00, 0x11, 0x00, 0x10, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
+];
+
+const BOX_HDR_LEN: u64 = 8;
+const LARGE_BOX_HDR_LEN: u64 = 16;
+
+static SUPPORTED_TYPES: [&str; 2] = ["jxl", "image/jxl"];
+
+#[derive(Clone, Debug)]
+struct JxlBoxPos {
+    pub start: u64,
+    pub length: u64,
+    pub hdr_len: u64,
+    pub box_type: [u8; 4],
+    pub is_c2pa: bool,
+}
+
+impl JxlBoxPos {
+    pub fn end(&self) -> u64 {
+        self.start + self.length
+    }
+
+    fn is_codestream(&self) -> bool {
+        self.box_type == CODESTREAM_BOX || self.box_type == PARTIAL_CODESTREAM_BOX
+    }
+}
+
+fn jxl_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("JPEG XL out of range".to_string())
+}
+
+fn is_naked_codestream<R: Read + Seek + ?Sized>(f: &mut R) -> Result<bool> {
+    let mut sig = [0u8; 2];
+    f.rewind()?;
+    f.read_exact(&mut sig)
+        .map_err(|_err| Error::InvalidAsset("JPEG XL invalid".to_string()))?;
+    f.rewind()?;
+
+    Ok(sig == JXL_CODESTREAM_SIG)
+}
+
+// check whether a jumb box contains a C2PA manifest store
+fn is_c2pa_jumb<R: Read + Seek + ?Sized>(f: &mut R, data_start: u64) -> Result<bool> {
+    f.seek(SeekFrom::Start(data_start))?;
+
+    let mut jumd_hdr = [0u8; 8];
+    let mut jumd_uuid = [0u8; 16];
+    if f.read_exact(&mut jumd_hdr).is_err() || f.read_exact(&mut jumd_uuid).is_err() {
+        return Ok(false);
+    }
+
+    Ok(jumd_hdr[4..] == JUMD_BOX && jumd_uuid == C2PA_STORE_UUID)
+}
+
+// get the top level boxes of a JPEG XL container
+fn get_jxl_box_positions<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<JxlBoxPos>> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    let mut box_positions: Vec<JxlBoxPos> = Vec::new();
+
+    // check JPEG XL signature box
+    let mut sig = [0u8; 12];
+    f.rewind()?;
+    f.read_exact(&mut sig)
+        .map_err(|_err| Error::InvalidAsset("JPEG XL invalid".to_string()))?;
+    if sig != JXL_SIG_BOX {
+        return Err(Error::InvalidAsset("JPEG XL invalid".to_string()));
+    }
+    f.rewind()?;
+
+    let mut current_pos = 0;
+    while current_pos < stream_len {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let size = f.read_u32::<BigEndian>().map_err(jxl_err)?;
+        let mut box_type = [0u8; 4];
+        f.read_exact(&mut box_type).map_err(jxl_err)?;
+
+        let (length, hdr_len) = match size {
+            0 => (stream_len - current_pos, BOX_HDR_LEN), // box extends to the end of the file
+            1 => (
+                f.read_u64::<BigEndian>().map_err(jxl_err)?,
+                LARGE_BOX_HDR_LEN,
+            ),
+            _ => (size as u64, BOX_HDR_LEN),
+        };
+
+        if length < hdr_len || current_pos + length > stream_len {
+            return Err(Error::InvalidAsset("JPEG XL bad box size".to_string()));
+        }
+
+        let is_c2pa = box_type == JUMB_BOX && is_c2pa_jumb(f, current_pos + hdr_len)?;
+
+        box_positions.push(JxlBoxPos {
+            start: current_pos,
+            length,
+            hdr_len,
+            box_type,
+            is_c2pa,
+        });
+
+        current_pos += length;
+    }
+
+    // the file type box must follow the signature
+    if box_positions.len() < 2 || box_positions[1].box_type != FTYP_BOX {
+        return Err(Error::InvalidAsset("JPEG XL missing ftyp".to_string()));
+    }
+
+    Ok(box_positions)
+}
+
+// create an ISO BMFF style box
+fn make_box(box_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut jxl_box = Vec::with_capacity(data.len() + LARGE_BOX_HDR_LEN as usize);
+
+    let length = data.len() as u64 + BOX_HDR_LEN;
+    if length <= u32::MAX as u64 {
+        jxl_box.extend_from_slice(&(length as u32).to_be_bytes());
+        jxl_box.extend_from_slice(box_type);
+    } else {
+        jxl_box.extend_from_slice(&1u32.to_be_bytes());
+        jxl_box.extend_from_slice(box_type);
+        jxl_box.extend_from_slice(&(data.len() as u64 + LARGE_BOX_HDR_LEN).to_be_bytes());
+    }
+    jxl_box.extend_from_slice(data);
+
+    jxl_box
+}
+
+// empty C2PA manifest store superbox used to reserve a location for the manifest
+fn make_placeholder_store() -> Vec<u8> {
+    let mut jumd = Vec::new();
+    jumd.extend_from_slice(&C2PA_STORE_UUID);
+    jumd.push(0x03); // requestable + label present
+    jumd.extend_from_slice(b"c2pa\0");
+
+    make_box(&JUMB_BOX, &make_box(&JUMD_BOX, &jumd))
+}
+
+// read the asset, wrapping a bare codestream in a JPEG XL container
+fn read_as_container(input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+    let naked = is_naked_codestream(input_stream)?;
+
+    let mut buf = Vec::new();
+    input_stream.rewind()?;
+    input_stream.read_to_end(&mut buf).map_err(Error::IoError)?;
+
+    if naked {
+        let mut container = Vec::with_capacity(buf.len() + 64);
+        container.extend_from_slice(&JXL_SIG_BOX);
+        container.extend_from_slice(&JXL_FTYP_BOX);
+        container.extend_from_slice(&make_box(&CODESTREAM_BOX, &buf));
+        Ok(container)
+    } else {
+        Ok(buf)
+    }
+}
+
+fn range_of(jxl_box: &JxlBoxPos) -> Result<std::ops::Range<usize>> {
+    let start = usize::value_from(jxl_box.start)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    let end = usize::value_from(jxl_box.end())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    Ok(start..end)
+}
+
+// remove the top level boxes matching the predicate
+fn remove_boxes<P>(jxl_buf: &mut Vec<u8>, predicate: P) -> Result<()>
+where
+    P: Fn(&JxlBoxPos) -> bool,
+{
+    let mut cursor = Cursor::new(&jxl_buf[..]);
+    let bp = get_jxl_box_positions(&mut cursor)?;
+
+    // remove from the back so earlier offsets stay valid
+    for jxl_box in bp.iter().rev().filter(|b| predicate(b)) {
+        jxl_buf.drain(range_of(jxl_box)?);
+    }
+
+    Ok(())
+}
+
+// the manifest store is placed directly after the file type and level boxes
+fn manifest_insertion_point(jxl_buf: &[u8]) -> Result<usize> {
+    let mut cursor = Cursor::new(jxl_buf);
+    let bp = get_jxl_box_positions(&mut cursor)?;
+
+    let last_hdr_box = bp
+        .iter()
+        .take_while(|b| b.box_type != JUMB_BOX && !b.is_codestream())
+        .filter(|b| b.box_type == FTYP_BOX || b.box_type == LEVEL_BOX)
+        .last()
+        .ok_or(Error::EmbeddingError)?;
+
+    Ok(range_of(last_hdr_box)?.end)
+}
+
+// XMP is placed before the first codestream box
+fn xmp_insertion_point(jxl_buf: &[u8]) -> Result<usize> {
+    let mut cursor = Cursor::new(jxl_buf);
+    let bp = get_jxl_box_positions(&mut cursor)?;
+
+    let codestream = bp
+        .iter()
+        .find(|b| b.is_codestream())
+        .ok_or(Error::InvalidAsset(
+            "JPEG XL missing codestream".to_string(),
+        ))?;
+
+    Ok(range_of(codestream)?.start)
+}
+
+fn get_cai_data<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<u8>> {
+    if is_naked_codestream(f)? {
+        return Err(Error::JumbfNotFound);
+    }
+
+    let bp = get_jxl_box_positions(f)?;
+
+    let mut c2pa_boxes = bp.iter().filter(|b| b.is_c2pa);
+    let c2pa_box = c2pa_boxes.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_boxes.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    // the manifest store is the complete jumb superbox
+    let length = usize::value_from(c2pa_box.length)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(c2pa_box.start))?;
+    let mut data: Vec<u8> = vec![0; length];
+    f.read_exact(&mut data[..]).map_err(jxl_err)?;
+
+    Ok(data)
+}
+
+pub struct JpegXlIO {}
+
+impl CAIReader for JpegXlIO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let cai_data = get_cai_data(asset_reader)?;
+        Ok(cai_data)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, asset_reader: &mut dyn CAIRead) -> Option<String> {
+        if is_naked_codestream(asset_reader).ok()? {
+            return None;
+        }
+
+        let bp = get_jxl_box_positions(asset_reader).ok()?;
+        let xmp_box = bp.into_iter().find(|b| b.box_type == XMP_BOX)?;
+
+        let length = usize::value_from(xmp_box.length - xmp_box.hdr_len).ok()?;
+        asset_reader
+            .seek(SeekFrom::Start(xmp_box.start + xmp_box.hdr_len))
+            .ok()?;
+        let mut data = vec![0u8; length];
+        asset_reader.read_exact(&mut data).ok()?;
+
+        String::from_utf8(data).ok()
+    }
+}
+
+impl CAIWriter for JpegXlIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        // bare codestreams are converted to the container format
+        let mut jxl_buf = read_as_container(input_stream)?;
+
+        // erase existing manifest stores
+        remove_boxes(&mut jxl_buf, |b| b.is_c2pa)?;
+
+        // the store bytes are a complete jumb superbox
+        let insert_pos = manifest_insertion_point(&jxl_buf)?;
+        jxl_buf.splice(insert_pos..insert_pos, store_bytes.iter().cloned());
+
+        output_stream.rewind()?;
+        output_stream.write_all(&jxl_buf)?;
+
+        Ok(())
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        // Ensure the stream has a manifest store so we can generate the required offsets.
+        let has_manifest = match get_cai_data(input_stream) {
+            Ok(_) | Err(Error::TooManyManifestStores) => true,
+            Err(Error::JumbfNotFound) => false,
+            Err(e) => return Err(e),
+        };
+
+        let mut jxl_stream = Cursor::new(Vec::new());
+        if has_manifest {
+            input_stream.rewind()?;
+            std::io::copy(input_stream, &mut jxl_stream)?;
+        } else {
+            self.write_cai(input_stream, &mut jxl_stream, &make_placeholder_store())?;
+        }
+
+        let bp = get_jxl_box_positions(&mut jxl_stream)?;
+        let file_end = jxl_stream.get_ref().len();
+
+        let c2pa_box = bp
+            .into_iter()
+            .find(|b| b.is_c2pa)
+            .ok_or(Error::JumbfNotFound)?;
+        let c2pa_range = range_of(&c2pa_box)?;
+
+        positions.push(HashObjectPositions {
+            offset: c2pa_range.start,
+            length: c2pa_range.len(),
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of boxes before the manifest store
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: c2pa_range.start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from the manifest store to end
+        positions.push(HashObjectPositions {
+            offset: c2pa_range.end,
+            length: file_end - c2pa_range.end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        // nothing to remove from a bare codestream
+        if is_naked_codestream(input_stream)? {
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            return Ok(());
+        }
+
+        let mut jxl_buf = Vec::new();
+        input_stream.rewind()?;
+        input_stream
+            .read_to_end(&mut jxl_buf)
+            .map_err(Error::IoError)?;
+
+        remove_boxes(&mut jxl_buf, |b| b.is_c2pa)?;
+
+        output_stream.rewind()?;
+        output_stream.write_all(&jxl_buf)?;
+
+        Ok(())
+    }
+}
+
+impl AssetIO for JpegXlIO {
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        self.get_object_locations_from_stream(&mut file)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut stream = std::fs::OpenOptions::new()
+            .read(true)
+            .open(asset_path)
+            .map_err(Error::IoError)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn new(_asset_type: &str) -> Self
+    where
+        Self: Sized,
+    {
+        JpegXlIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(JpegXlIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(JpegXlIO::new(asset_type)))
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn composed_data_ref(&self) -> Option<&dyn ComposedManifestRef> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl RemoteRefEmbed for JpegXlIO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let output_buf = Vec::new();
+                let mut output_stream = Cursor::new(output_buf);
+
+                // do here so source file is closed after update
+                {
+                    let mut source_stream = std::fs::File::open(asset_path)?;
+                    self.embed_reference_to_stream(
+                        &mut source_stream,
+                        &mut output_stream,
+                        RemoteRefEmbedType::Xmp(manifest_uri),
+                    )?;
+                }
+
+                std::fs::write(asset_path, output_stream.into_inner())?;
+
+                Ok(())
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        source_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                source_stream.rewind()?;
+
+                let xmp = match self.read_xmp(source_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+
+                // update XMP
+                let updated_xmp = add_provenance(&xmp, &manifest_uri)?;
+                let xmp_box = make_box(&XMP_BOX, updated_xmp.as_bytes());
+
+                // bare codestreams are converted to the container format
+                let mut jxl_buf = read_as_container(source_stream)?;
+
+                // replace existing XMP
+                remove_boxes(&mut jxl_buf, |b| b.box_type == XMP_BOX)?;
+                let insert_pos = xmp_insertion_point(&jxl_buf)?;
+                jxl_buf.splice(insert_pos..insert_pos, xmp_box);
+
+                output_stream.rewind()?;
+                output_stream.write_all(&jxl_buf)?;
+
+                Ok(())
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+impl AssetBoxHash for JpegXlIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        // box hashing requires the container format so the manifest store has a place to go
+        if is_naked_codestream(input_stream)? {
+            return Err(Error::InvalidAsset(
+                "JPEG XL codestream must be in a container for box hashing".to_string(),
+            ));
+        }
+
+        let bp = get_jxl_box_positions(input_stream)?;
+
+        let box_maps = bp
+            .iter()
+            .map(|b| {
+                let name = if b.is_c2pa {
+                    C2PA_BOXHASH.to_string()
+                } else {
+                    String::from_utf8_lossy(&b.box_type).to_string()
+                };
+
+                Ok(BoxMap {
+                    names: vec![name],
+                    alg: None,
+                    hash: ByteBuf::from(Vec::new()),
+                    pad: ByteBuf::from(Vec::new()),
+                    range_start: usize::value_from(b.start)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                    range_len: usize::value_from(b.length)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                })
+            })
+            .collect::<Result<Vec<BoxMap>>>()?;
+
+        Ok(box_maps)
+    }
+}
+
+impl ComposedManifestRef for JpegXlIO {
+    // the manifest store is already a jumb superbox so it is embedded as is
+    fn compose_manifest(&self, manifest_data: &[u8], _format: &str) -> Result<Vec<u8>> {
+        Ok(manifest_data.to_vec())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+#[allow(clippy::unwrap_used)]
+pub mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::utils::{test, xmp_inmemory_utils::extract_provenance};
+
+    fn test_store() -> Vec<u8> {
+        // jumb superbox with a c2pa description box and some content
+        let mut store = make_placeholder_store();
+        let content = make_box(b"json", b"{}");
+        store.extend_from_slice(&content);
+        let len = store.len() as u32;
+        store[..4].copy_from_slice(&len.to_be_bytes());
+        store
+    }
+
+    #[test]
+    fn test_jxl_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let bp = get_jxl_box_positions(&mut stream).unwrap();
+        let types: Vec<&[u8; 4]> = bp.iter().map(|b| &b.box_type).collect();
+        assert_eq!(types, vec![b"JXL ", b"ftyp", b"jxlc"]);
+        assert_eq!(bp[2].end(), source.len() as u64);
+    }
+
+    #[test]
+    fn test_write_cai_container() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+        let jxl_io = JpegXlIO {};
+
+        // no cai data present in stream.
+        assert!(matches!(
+            jxl_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        let store = test_store();
+        let mut output_stream = Cursor::new(Vec::new());
+        jxl_io
+            .write_cai(&mut stream, &mut output_stream, &store)
+            .unwrap();
+
+        assert_eq!(jxl_io.read_cai(&mut output_stream).unwrap(), store);
+
+        // manifest store goes directly after the file type box
+        let bp = get_jxl_box_positions(&mut output_stream).unwrap();
+        assert!(bp[2].is_c2pa);
+        assert_eq!(bp[2].start, 32);
+
+        // replacing keeps a single store
+        let new_store = make_placeholder_store();
+        let mut replaced_stream = Cursor::new(Vec::new());
+        jxl_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &new_store)
+            .unwrap();
+        assert_eq!(jxl_io.read_cai(&mut replaced_stream).unwrap(), new_store);
+        assert_eq!(
+            replaced_stream.get_ref().len(),
+            source.len() + new_store.len()
+        );
+    }
+
+    #[test]
+    fn test_write_cai_naked_codestream() {
+        let source = include_bytes!("../../tests/fixtures/sample1_codestream.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+        let jxl_io = JpegXlIO {};
+
+        assert!(matches!(
+            jxl_io.read_cai(&mut stream),
+            Err(Error::JumbfNotFound)
+        ));
+
+        let store = test_store();
+        let mut output_stream = Cursor::new(Vec::new());
+        jxl_io
+            .write_cai(&mut stream, &mut output_stream, &store)
+            .unwrap();
+
+        // the codestream is wrapped in a container
+        let output = output_stream.get_ref();
+        assert!(output.starts_with(&JXL_SIG_BOX));
+        assert!(output.ends_with(source));
+        assert_eq!(jxl_io.read_cai(&mut output_stream).unwrap(), store);
+
+        // matches converting the codestream to a container first
+        let container = include_bytes!("../../tests/fixtures/sample1.jxl");
+        let mut container_output = Cursor::new(Vec::new());
+        jxl_io
+            .write_cai(
+                &mut Cursor::new(container.to_vec()),
+                &mut container_output,
+                &store,
+            )
+            .unwrap();
+        assert_eq!(output_stream.into_inner(), container_output.into_inner());
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mut stream = Cursor::new(source.to_vec());
+        let jxl_io = JpegXlIO {};
+
+        let output: Vec<u8> = Vec::new();
+        let mut output_stream = Cursor::new(output);
+        assert!(matches!(
+            jxl_io.write_cai(&mut stream, &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_stream_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+        let jxl_io = JpegXlIO {};
+
+        let positions = jxl_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_pos = positions
+            .iter()
+            .find(|pos| pos.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        assert_eq!(cai_pos.offset, 32);
+        assert_eq!(cai_pos.length, make_placeholder_store().len());
+
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, source.len() + cai_pos.length);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = test::fixture_path("sample1.jxl");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output = test::temp_dir_path(&temp_dir, "sample1_tmp.jxl");
+        std::fs::copy(source, &output).unwrap();
+
+        let jxl_io = JpegXlIO {};
+        jxl_io.save_cai_store(&output, &test_store()).unwrap();
+        assert_eq!(jxl_io.read_cai_store(&output).unwrap(), test_store());
+
+        jxl_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match jxl_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        let source_bytes = include_bytes!("../../tests/fixtures/sample1.jxl");
+        assert_eq!(std::fs::read(&output).unwrap(), source_bytes.to_vec());
+    }
+
+    #[test]
+    fn test_jxl_xmp_write() {
+        let source = include_bytes!("../../tests/fixtures/sample1_codestream.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+        let jxl_io = JpegXlIO {};
+
+        assert!(jxl_io.read_xmp(&mut stream).is_none());
+
+        let mut output_stream = Cursor::new(Vec::new());
+        jxl_io
+            .embed_reference_to_stream(
+                &mut stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let xmp = jxl_io.read_xmp(&mut output_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/manifest.c2pa"
+        );
+
+        // updating replaces the existing XMP box, which stays before the codestream
+        let mut updated_stream = Cursor::new(Vec::new());
+        jxl_io
+            .embed_reference_to_stream(
+                &mut output_stream,
+                &mut updated_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/updated.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let bp = get_jxl_box_positions(&mut updated_stream).unwrap();
+        let types: Vec<&[u8; 4]> = bp.iter().map(|b| &b.box_type).collect();
+        assert_eq!(types, vec![b"JXL ", b"ftyp", b"xml ", b"jxlc"]);
+
+        let xmp = jxl_io.read_xmp(&mut updated_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/updated.c2pa"
+        );
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+        let jxl_io = JpegXlIO {};
+
+        let mut output_stream = Cursor::new(Vec::new());
+        jxl_io
+            .write_cai(&mut stream, &mut output_stream, &test_store())
+            .unwrap();
+
+        let box_map = jxl_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(names, vec!["JXL ", "ftyp", C2PA_BOXHASH, "jxlc"]);
+
+        // box hashing is not possible for a bare codestream
+        let codestream = include_bytes!("../../tests/fixtures/sample1_codestream.jxl");
+        assert!(matches!(
+            jxl_io.get_box_map(&mut Cursor::new(codestream.to_vec())),
+            Err(Error::InvalidAsset(_))
+        ));
+    }
+
+    #[test]
+    fn test_embeddable_manifest() {
+        let jxl_io = JpegXlIO {};
+
+        let source = include_bytes!("../../tests/fixtures/sample1.jxl");
+        let mut stream = Cursor::new(source.to_vec());
+
+        let ol = jxl_io
+            .get_object_locations_from_stream(&mut stream)
+            .unwrap();
+        let cai_loc = ol
+            .iter()
+            .find(|o| o.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        let manifest = test_store();
+        let em = jxl_io
+            .composed_data_ref()
+            .unwrap()
+            .compose_manifest(&manifest, "jxl")
+            .unwrap();
+
+        // insert new manifest
+        let mut out_stream = Cursor::new(Vec::new());
+        out_stream.write_all(&source[..cai_loc.offset]).unwrap();
+        out_stream.write_all(&em).unwrap();
+        out_stream.write_all(&source[cai_loc.offset..]).unwrap();
+
+        // read manifest back in from new in-memory JPEG XL
+        let restored_manifest = jxl_io.read_cai(&mut out_stream).unwrap();
+        assert_eq!(&manifest, &restored_manifest);
+    }
+}
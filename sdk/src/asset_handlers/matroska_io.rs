@@ -0,0 +1,1087 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Matroska/WebM support.
+//!
+//! The manifest store is written as a private top-level element at the end of the
+//! first Segment, so adding a manifest never moves the clusters.  XMP is written
+//! the same way in its own element.  When an element is removed from the middle of
+//! the Segment the SeekHead and Cues positions that follow it are updated.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::ReadBytesExt;
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader,
+        CAIWriter, HashBlockObjectType, HashObjectPositions, RemoteRefEmbed, RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+static SUPPORTED_TYPES: [&str; 7] = [
+    "mkv",
+    "mka",
+    "webm",
+    "video/x-matroska",
+    "audio/x-matroska",
+    "video/webm",
+    "audio/webm",
+];
+
+// EBML element ids, including the length marker bits
+const EBML_ID: u32 = 0x1a45_dfa3;
+const SEGMENT_ID: u32 = 0x1853_8067;
+const SEEK_HEAD_ID: u32 = 0x114d_9b74;
+const SEEK_ID: u32 = 0x4dbb;
+const SEEK_POSITION_ID: u32 = 0x53ac;
+const INFO_ID: u32 = 0x1549_a966;
+const TRACKS_ID: u32 = 0x1654_ae6b;
+const CLUSTER_ID: u32 = 0x1f43_b675;
+const CUES_ID: u32 = 0x1c53_bb6b;
+const CUE_POINT_ID: u32 = 0xbb;
+const CUE_TRACK_POSITIONS_ID: u32 = 0xb7;
+const CUE_CLUSTER_POSITION_ID: u32 = 0xf1;
+const ATTACHMENTS_ID: u32 = 0x1941_a469;
+const CHAPTERS_ID: u32 = 0x1043_a770;
+const TAGS_ID: u32 = 0x1254_c367;
+const VOID_ID: u32 = 0xec;
+
+// private top-level elements holding the manifest store and XMP
+const C2PA_ID: u32 = 0x1c32_5041;
+const XMP_ID: u32 = 0x1c58_4d50;
+
+// elements that may follow a Cluster of unknown size at the Segment level
+const SEGMENT_CHILD_IDS: [u32; 10] = [
+    SEEK_HEAD_ID,
+    INFO_ID,
+    TRACKS_ID,
+    CLUSTER_ID,
+    CUES_ID,
+    ATTACHMENTS_ID,
+    CHAPTERS_ID,
+    TAGS_ID,
+    C2PA_ID,
+    XMP_ID,
+];
+
+// sizes we write are always 8 bytes so a store can be replaced in place
+const SIZE_LEN: usize = 8;
+
+// reserved size value for elements whose size is not known
+const UNKNOWN_SIZE: [u8; SIZE_LEN] = [0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+
+#[derive(Clone, Debug)]
+struct EbmlElement {
+    pub id: u32,
+    pub start: u64,
+    pub data_start: u64,
+    pub end: u64,
+}
+
+impl EbmlElement {
+    pub fn data_len(&self) -> u64 {
+        self.end - self.data_start
+    }
+}
+
+struct MatroskaLayout {
+    // top-level elements before the Segment, starting with the EBML header
+    header_elements: Vec<EbmlElement>,
+    segment: EbmlElement,
+    segment_size_known: bool,
+    segment_size_len: usize,
+    children: Vec<EbmlElement>,
+}
+
+fn mkv_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("Matroska out of range".to_string())
+}
+
+// read an element id, the length marker bits are kept as part of the id
+fn read_element_id<R: Read + ?Sized>(f: &mut R) -> Result<(u32, u64)> {
+    let first = f.read_u8().map_err(mkv_err)?;
+    let len = first.leading_zeros() + 1;
+    if len > 4 {
+        return Err(Error::InvalidAsset(
+            "Matroska invalid element id".to_string(),
+        ));
+    }
+
+    let mut id = first as u32;
+    for _ in 1..len {
+        id = (id << 8) | f.read_u8().map_err(mkv_err)? as u32;
+    }
+
+    Ok((id, len as u64))
+}
+
+// read an element data size, None is returned for the reserved unknown size
+fn read_element_size<R: Read + ?Sized>(f: &mut R) -> Result<(Option<u64>, usize)> {
+    let first = f.read_u8().map_err(mkv_err)?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 {
+        return Err(Error::InvalidAsset(
+            "Matroska invalid element size".to_string(),
+        ));
+    }
+
+    let mut size = (first as u64) & (0xff >> len);
+    for _ in 1..len {
+        size = (size << 8) | f.read_u8().map_err(mkv_err)? as u64;
+    }
+
+    // all value bits set means the size is unknown
+    let unknown = (1u64 << (7 * len)) - 1;
+    Ok(((size != unknown).then_some(size), len))
+}
+
+fn encode_element_id(id: u32) -> Vec<u8> {
+    let bytes = id.to_be_bytes();
+    let skip = (id.leading_zeros() / 8) as usize;
+    bytes[skip..].to_vec()
+}
+
+fn encode_element_size(size: u64, len: usize) -> Result<Vec<u8>> {
+    if len == 0 || len > 8 || size >= (1u64 << (7 * len)) - 1 {
+        return Err(Error::InvalidAsset(
+            "Matroska element too large".to_string(),
+        ));
+    }
+
+    let marked = size | (1u64 << (7 * len));
+    Ok(marked.to_be_bytes()[8 - len..].to_vec())
+}
+
+fn make_element(id: u32, data: &[u8]) -> Result<Vec<u8>> {
+    let mut element = encode_element_id(id);
+    element.extend(encode_element_size(data.len() as u64, SIZE_LEN)?);
+    element.extend_from_slice(data);
+    Ok(element)
+}
+
+fn read_element_header<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    start: u64,
+) -> Result<(u32, u64, Option<u64>, usize)> {
+    f.seek(SeekFrom::Start(start))?;
+    let (id, id_len) = read_element_id(f)?;
+    let (size, size_len) = read_element_size(f)?;
+    Ok((id, start + id_len + size_len as u64, size, size_len))
+}
+
+// Find the end of a Cluster with unknown size, which is where the next Segment
+// level element starts.
+fn find_unknown_size_end<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    data_start: u64,
+    limit: u64,
+) -> Result<u64> {
+    let mut pos = data_start;
+    while pos < limit {
+        let (id, child_data_start, size, _) = read_element_header(f, pos)?;
+        if SEGMENT_CHILD_IDS.contains(&id) {
+            break;
+        }
+
+        let size = size.ok_or_else(|| {
+            Error::InvalidAsset("Matroska nested unknown sized element".to_string())
+        })?;
+        pos = child_data_start + size;
+    }
+
+    Ok(std::cmp::min(pos, limit))
+}
+
+fn get_matroska_layout<R: Read + Seek + ?Sized>(f: &mut R) -> Result<MatroskaLayout> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut header_elements = Vec::new();
+    let mut pos = 0;
+    let segment = loop {
+        if pos >= stream_len {
+            return Err(Error::InvalidAsset("Matroska missing Segment".to_string()));
+        }
+
+        let (id, data_start, size, size_len) = read_element_header(f, pos)
+            .map_err(|_err| Error::InvalidAsset("Matroska invalid".to_string()))?;
+        if pos == 0 && id != EBML_ID {
+            return Err(Error::InvalidAsset("Matroska invalid".to_string()));
+        }
+
+        if id == SEGMENT_ID {
+            break (id, pos, data_start, size, size_len);
+        }
+
+        let size = size.ok_or_else(|| Error::InvalidAsset("Matroska invalid".to_string()))?;
+        let element = EbmlElement {
+            id,
+            start: pos,
+            data_start,
+            end: data_start + size,
+        };
+        if element.end > stream_len {
+            return Err(Error::InvalidAsset("Matroska bad element size".to_string()));
+        }
+        pos = element.end;
+        header_elements.push(element);
+    };
+
+    let (_, segment_start, segment_data_start, segment_size, segment_size_len) = segment;
+    let segment_end = match segment_size {
+        Some(size) => segment_data_start + size,
+        None => stream_len, // live streams may not know the final size
+    };
+    if segment_end > stream_len {
+        return Err(Error::InvalidAsset("Matroska bad Segment size".to_string()));
+    }
+
+    let mut children = Vec::new();
+    let mut pos = segment_data_start;
+    while pos < segment_end {
+        let (id, data_start, size, _) = read_element_header(f, pos)?;
+
+        let end = match size {
+            Some(size) => data_start + size,
+            None if id == CLUSTER_ID => find_unknown_size_end(f, data_start, segment_end)?,
+            None => {
+                return Err(Error::InvalidAsset(
+                    "Matroska unknown sized element".to_string(),
+                ))
+            }
+        };
+        if end > segment_end {
+            return Err(Error::InvalidAsset("Matroska bad element size".to_string()));
+        }
+
+        children.push(EbmlElement {
+            id,
+            start: pos,
+            data_start,
+            end,
+        });
+        pos = end;
+    }
+
+    Ok(MatroskaLayout {
+        header_elements,
+        segment: EbmlElement {
+            id: SEGMENT_ID,
+            start: segment_start,
+            data_start: segment_data_start,
+            end: segment_end,
+        },
+        segment_size_known: segment_size.is_some(),
+        segment_size_len,
+        children,
+    })
+}
+
+fn read_element_data<R: Read + Seek + ?Sized>(f: &mut R, element: &EbmlElement) -> Result<Vec<u8>> {
+    let len = usize::value_from(element.data_len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(element.data_start))?;
+    let mut data = vec![0u8; len];
+    f.read_exact(&mut data).map_err(mkv_err)?;
+
+    Ok(data)
+}
+
+fn find_single_child(layout: &MatroskaLayout, id: u32) -> Result<Option<&EbmlElement>> {
+    let mut matches = layout.children.iter().filter(|c| c.id == id);
+    let element = matches.next();
+    if element.is_some() && matches.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    Ok(element)
+}
+
+// Rewrite the Segment relative positions stored in a SeekHead or Cues element.
+fn patch_positions(data: &mut [u8], map_position: &dyn Fn(u64) -> u64) -> Result<()> {
+    let mut reader = Cursor::new(&*data);
+    let mut patches = Vec::new();
+
+    let data_len = data.len() as u64;
+    let mut pos = 0;
+    while pos < data_len {
+        reader.set_position(pos);
+        let (id, id_len) = read_element_id(&mut reader)?;
+        let (size, size_len) = read_element_size(&mut reader)?;
+        let data_start = pos + id_len + size_len as u64;
+        let size =
+            size.ok_or_else(|| Error::InvalidAsset("Matroska unknown sized element".to_string()))?;
+        if data_start + size > data_len {
+            return Err(Error::InvalidAsset("Matroska bad element size".to_string()));
+        }
+
+        match id {
+            // descend into the master elements that lead to positions
+            SEEK_HEAD_ID | SEEK_ID | CUES_ID | CUE_POINT_ID | CUE_TRACK_POSITIONS_ID => {
+                pos = data_start;
+                continue;
+            }
+            SEEK_POSITION_ID | CUE_CLUSTER_POSITION_ID if (1..=8).contains(&size) => {
+                patches.push((data_start as usize, size as usize));
+            }
+            _ => (),
+        }
+        pos = data_start + size;
+    }
+
+    for (offset, len) in patches {
+        let value = data[offset..offset + len]
+            .iter()
+            .fold(0u64, |v, b| (v << 8) | *b as u64);
+        let new_value = map_position(value);
+
+        if len < 8 && new_value >= 1u64 << (8 * len) {
+            return Err(Error::InvalidAsset(
+                "Matroska position does not fit".to_string(),
+            ));
+        }
+        data[offset..offset + len].copy_from_slice(&new_value.to_be_bytes()[8 - len..]);
+    }
+
+    Ok(())
+}
+
+// Rewrite the file without the Segment children whose ids are in `remove` and with
+// `new_elements` appended at the end of the Segment.  Everything else is copied.
+fn rewrite_matroska(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    remove: &[u32],
+    new_elements: &[Vec<u8>],
+) -> Result<()> {
+    let layout = get_matroska_layout(input_stream)?;
+    let stream_len = input_stream.seek(SeekFrom::End(0))?;
+    let segment_data_start = layout.segment.data_start;
+
+    // Segment relative ranges of the removed elements
+    let removed: Vec<(u64, u64)> = layout
+        .children
+        .iter()
+        .filter(|c| remove.contains(&c.id))
+        .map(|c| (c.start - segment_data_start, c.end - c.start))
+        .collect();
+    let map_position = |position: u64| -> u64 {
+        position
+            - removed
+                .iter()
+                .filter(|(start, _)| *start < position)
+                .map(|(_, len)| *len)
+                .sum::<u64>()
+    };
+
+    let kept: Vec<&EbmlElement> = layout
+        .children
+        .iter()
+        .filter(|c| !remove.contains(&c.id))
+        .collect();
+
+    output_stream.rewind()?;
+
+    // everything before the Segment is unchanged
+    input_stream.rewind()?;
+    std::io::copy(&mut input_stream.take(layout.segment.start), output_stream)?;
+
+    // Segment header
+    output_stream.write_all(&encode_element_id(SEGMENT_ID))?;
+    if layout.segment_size_known {
+        let segment_size = kept.iter().map(|c| c.end - c.start).sum::<u64>()
+            + new_elements.iter().map(|e| e.len() as u64).sum::<u64>();
+
+        let size_bytes = encode_element_size(segment_size, layout.segment_size_len)
+            .or_else(|_| encode_element_size(segment_size, SIZE_LEN))?;
+        output_stream.write_all(&size_bytes)?;
+    } else {
+        output_stream.write_all(&UNKNOWN_SIZE)?;
+    }
+
+    for child in kept {
+        let needs_patch = (child.id == SEEK_HEAD_ID || child.id == CUES_ID) && !removed.is_empty();
+
+        input_stream.seek(SeekFrom::Start(child.start))?;
+        if needs_patch {
+            let len = usize::value_from(child.end - child.start)
+                .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+            let mut data = vec![0u8; len];
+            input_stream.read_exact(&mut data).map_err(mkv_err)?;
+
+            patch_positions(&mut data, &map_position)?;
+            output_stream.write_all(&data)?;
+        } else {
+            std::io::copy(
+                &mut input_stream.take(child.end - child.start),
+                output_stream,
+            )?;
+        }
+    }
+
+    for element in new_elements {
+        output_stream.write_all(element)?;
+    }
+
+    // anything following the Segment is unchanged
+    input_stream.seek(SeekFrom::Start(layout.segment.end))?;
+    std::io::copy(
+        &mut input_stream.take(stream_len - layout.segment.end),
+        output_stream,
+    )?;
+
+    Ok(())
+}
+
+fn element_name(id: u32) -> String {
+    match id {
+        EBML_ID => "EBML".to_string(),
+        SEGMENT_ID => "Segment".to_string(),
+        SEEK_HEAD_ID => "SeekHead".to_string(),
+        INFO_ID => "Info".to_string(),
+        TRACKS_ID => "Tracks".to_string(),
+        CLUSTER_ID => "Cluster".to_string(),
+        CUES_ID => "Cues".to_string(),
+        ATTACHMENTS_ID => "Attachments".to_string(),
+        CHAPTERS_ID => "Chapters".to_string(),
+        TAGS_ID => "Tags".to_string(),
+        VOID_ID => "Void".to_string(),
+        C2PA_ID => C2PA_BOXHASH.to_string(),
+        XMP_ID => "XMP".to_string(),
+        _ => format!("{id:X}"),
+    }
+}
+
+fn add_required_elements(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let aio = MatroskaIO {};
+
+    match aio.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            aio.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct MatroskaIO {}
+
+impl CAIReader for MatroskaIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let layout = get_matroska_layout(input_stream)?;
+        let c2pa_element = find_single_child(&layout, C2PA_ID)?.ok_or(Error::JumbfNotFound)?;
+
+        read_element_data(input_stream, c2pa_element)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, input_stream: &mut dyn CAIRead) -> Option<String> {
+        let layout = get_matroska_layout(input_stream).ok()?;
+        let xmp_element = layout.children.iter().find(|c| c.id == XMP_ID)?;
+
+        let data = read_element_data(input_stream, xmp_element).ok()?;
+        String::from_utf8(data).ok()
+    }
+}
+
+impl CAIWriter for MatroskaIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        rewrite_matroska(
+            input_stream,
+            output_stream,
+            &[C2PA_ID],
+            &[make_element(C2PA_ID, store_bytes)?],
+        )
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_elements(input_stream, &mut output_stream)?;
+
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let layout = get_matroska_layout(&mut output_stream)?;
+        let c2pa_element = find_single_child(&layout, C2PA_ID)?.ok_or(Error::JumbfNotFound)?;
+
+        let start = usize::value_from(c2pa_element.start)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let end = usize::value_from(c2pa_element.end)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let file_end = output_stream.get_ref().len();
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of elements before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_matroska(input_stream, output_stream, &[C2PA_ID], &[])
+    }
+}
+
+impl AssetIO for MatroskaIO {
+    fn new(_asset_type: &str) -> Self {
+        MatroskaIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(MatroskaIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(MatroskaIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for MatroskaIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let layout = get_matroska_layout(&mut asset)?;
+        let c2pa_element = find_single_child(&layout, C2PA_ID)?.ok_or(Error::JumbfNotFound)?;
+
+        if store_bytes.len() as u64 == c2pa_element.data_len() {
+            asset.seek(SeekFrom::Start(c2pa_element.data_start))?;
+            asset.write_all(store_bytes)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+impl RemoteRefEmbed for MatroskaIO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.embed_reference_to_stream(&mut input_stream, &mut temp_file, embed_ref)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let xmp = match self.read_xmp(input_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+                let new_xmp = add_provenance(&xmp, &manifest_uri)?;
+
+                let mut new_elements = vec![make_element(XMP_ID, new_xmp.as_bytes())?];
+
+                // keep the manifest store as the last element
+                match self.read_cai(input_stream) {
+                    Ok(manifest) => new_elements.push(make_element(C2PA_ID, &manifest)?),
+                    Err(Error::JumbfNotFound) => (),
+                    Err(e) => return Err(e),
+                }
+
+                rewrite_matroska(
+                    input_stream,
+                    output_stream,
+                    &[XMP_ID, C2PA_ID],
+                    &new_elements,
+                )
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+impl AssetBoxHash for MatroskaIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        let layout = get_matroska_layout(input_stream)?;
+        let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+        let make_box_map = |name: String, start: u64, end: u64| -> Result<BoxMap> {
+            Ok(BoxMap {
+                names: vec![name],
+                alg: None,
+                hash: ByteBuf::from(Vec::new()),
+                pad: ByteBuf::from(Vec::new()),
+                range_start: usize::value_from(start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                range_len: usize::value_from(end - start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+            })
+        };
+
+        let mut box_maps = Vec::new();
+
+        for element in &layout.header_elements {
+            box_maps.push(make_box_map(
+                element_name(element.id),
+                element.start,
+                element.end,
+            )?);
+        }
+
+        // the Segment header, each top-level element is then hashed on its own
+        box_maps.push(make_box_map(
+            element_name(SEGMENT_ID),
+            layout.segment.start,
+            layout.segment.data_start,
+        )?);
+
+        for element in &layout.children {
+            box_maps.push(make_box_map(
+                element_name(element.id),
+                element.start,
+                element.end,
+            )?);
+        }
+
+        if layout.segment.end < stream_len {
+            box_maps.push(make_box_map(
+                "Trailer".to_string(),
+                layout.segment.end,
+                stream_len,
+            )?);
+        }
+
+        Ok(box_maps)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::{
+        test::{fixture_path, temp_dir_path},
+        xmp_inmemory_utils::extract_provenance,
+    };
+
+    fn child_ids(data: &[u8]) -> Vec<u32> {
+        let layout = get_matroska_layout(&mut Cursor::new(data)).unwrap();
+        layout.children.iter().map(|c| c.id).collect()
+    }
+
+    // Segment relative positions stored in the SeekHead and Cues
+    fn stored_positions(data: &[u8]) -> Vec<u64> {
+        let layout = get_matroska_layout(&mut Cursor::new(data)).unwrap();
+
+        let positions = std::cell::RefCell::new(Vec::new());
+        for child in layout
+            .children
+            .iter()
+            .filter(|c| c.id == SEEK_HEAD_ID || c.id == CUES_ID)
+        {
+            let mut element = data[child.start as usize..child.end as usize].to_vec();
+            patch_positions(&mut element, &|p| {
+                positions.borrow_mut().push(p);
+                p
+            })
+            .unwrap();
+        }
+        positions.into_inner()
+    }
+
+    // insert an element in front of the first Cluster, as another tool might
+    fn insert_before_clusters(data: &[u8], element: &[u8]) -> Vec<u8> {
+        let layout = get_matroska_layout(&mut Cursor::new(data)).unwrap();
+        let cluster = layout.children.iter().find(|c| c.id == CLUSTER_ID).unwrap();
+        let insert_at = cluster.start;
+        let insert_rel = insert_at - layout.segment.data_start;
+
+        let mut output = data[..layout.segment.data_start as usize].to_vec();
+        for child in &layout.children {
+            if child.start == insert_at {
+                output.extend_from_slice(element);
+            }
+            let mut bytes = data[child.start as usize..child.end as usize].to_vec();
+            if child.id == SEEK_HEAD_ID || child.id == CUES_ID {
+                patch_positions(&mut bytes, &|p| {
+                    if p >= insert_rel {
+                        p + element.len() as u64
+                    } else {
+                        p
+                    }
+                })
+                .unwrap();
+            }
+            output.extend_from_slice(&bytes);
+        }
+
+        // fix the Segment size
+        let size_start = layout.segment.start as usize + 4;
+        let new_size = (output.len() - layout.segment.data_start as usize) as u64;
+        output[size_start..size_start + layout.segment_size_len]
+            .copy_from_slice(&encode_element_size(new_size, layout.segment_size_len).unwrap());
+        output
+    }
+
+    #[test]
+    fn test_matroska_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.webm");
+        assert_eq!(
+            child_ids(source),
+            vec![
+                SEEK_HEAD_ID,
+                VOID_ID,
+                INFO_ID,
+                TRACKS_ID,
+                CLUSTER_ID,
+                CLUSTER_ID,
+                CUES_ID
+            ]
+        );
+
+        // positions point at the elements they reference
+        let layout = get_matroska_layout(&mut Cursor::new(source)).unwrap();
+        for position in stored_positions(source) {
+            assert!(layout
+                .children
+                .iter()
+                .any(|c| c.start - layout.segment.data_start == position));
+        }
+    }
+
+    #[test]
+    fn test_write_matroska() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("sample1.webm");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-webm.webm");
+        std::fs::copy(&source, &output).unwrap();
+
+        let matroska_io = MatroskaIO::new("webm");
+        matroska_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(matroska_io.read_cai_store(&output).unwrap(), more_data);
+
+        // nothing moved so the stored positions are unchanged
+        let source_bytes = std::fs::read(&source).unwrap();
+        let output_bytes = std::fs::read(&output).unwrap();
+        assert_eq!(
+            stored_positions(&output_bytes),
+            stored_positions(&source_bytes)
+        );
+        assert_eq!(child_ids(&output_bytes).last().unwrap(), &C2PA_ID);
+    }
+
+    #[test]
+    fn test_positions_updated() {
+        let source = include_bytes!("../../tests/fixtures/sample1.webm");
+        let matroska_io = MatroskaIO::new("webm");
+
+        // a manifest in front of the clusters moves them
+        let c2pa_element = make_element(C2PA_ID, &[9; 100]).unwrap();
+        let moved = insert_before_clusters(source, &c2pa_element);
+        assert_ne!(stored_positions(&moved), stored_positions(source));
+        assert_eq!(
+            matroska_io.read_cai(&mut Cursor::new(&moved)).unwrap(),
+            vec![9; 100]
+        );
+
+        // replacing it moves the manifest to the end and restores the positions
+        let mut output_stream = Cursor::new(Vec::new());
+        matroska_io
+            .write_cai(&mut Cursor::new(&moved), &mut output_stream, &[1, 2, 3])
+            .unwrap();
+        let output = output_stream.into_inner();
+        assert_eq!(stored_positions(&output), stored_positions(source));
+
+        // removing it gives back the original file
+        let mut removed_stream = Cursor::new(Vec::new());
+        matroska_io
+            .remove_cai_store_from_stream(&mut Cursor::new(&moved), &mut removed_stream)
+            .unwrap();
+        assert_eq!(removed_stream.into_inner(), source.to_vec());
+    }
+
+    #[test]
+    fn test_unknown_segment_size() {
+        let mut source = include_bytes!("../../tests/fixtures/sample1.webm").to_vec();
+        let layout = get_matroska_layout(&mut Cursor::new(&source)).unwrap();
+        let size_start = layout.segment.start as usize + 4;
+        source[size_start..size_start + SIZE_LEN].copy_from_slice(&UNKNOWN_SIZE);
+
+        let matroska_io = MatroskaIO::new("webm");
+        let mut output_stream = Cursor::new(Vec::new());
+        matroska_io
+            .write_cai(&mut Cursor::new(&source), &mut output_stream, &[1, 2, 3])
+            .unwrap();
+        assert_eq!(
+            matroska_io.read_cai(&mut output_stream).unwrap(),
+            vec![1, 2, 3]
+        );
+
+        let layout = get_matroska_layout(&mut output_stream).unwrap();
+        assert!(!layout.segment_size_known);
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let matroska_io = MatroskaIO::new("webm");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            matroska_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_patch_write_matroska() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("sample1.webm");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-webm.webm");
+        std::fs::copy(source, &output).unwrap();
+
+        let matroska_io = MatroskaIO::new("webm");
+        matroska_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        matroska_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(matroska_io.read_cai_store(&output).unwrap(), new_data);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.webm");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-webm.webm");
+        std::fs::copy(&source, &output).unwrap();
+
+        let matroska_io = MatroskaIO::new("webm");
+        matroska_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        matroska_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match matroska_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let source = include_bytes!("../../tests/fixtures/sample1.webm");
+        let matroska_io = MatroskaIO::new("webm");
+
+        let mut signed_stream = Cursor::new(Vec::new());
+        matroska_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut signed_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert!(matroska_io.read_xmp(&mut signed_stream).is_none());
+
+        let mut output_stream = Cursor::new(Vec::new());
+        matroska_io
+            .embed_reference_to_stream(
+                &mut signed_stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let xmp = matroska_io.read_xmp(&mut output_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/manifest.c2pa"
+        );
+
+        let ids = child_ids(output_stream.get_ref());
+        assert_eq!(ids[ids.len() - 2..], [XMP_ID, C2PA_ID]);
+        assert_eq!(
+            matroska_io.read_cai(&mut output_stream).unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.webm");
+        let matroska_io = MatroskaIO::new("webm");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        matroska_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let box_map = matroska_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "EBML",
+                "Segment",
+                "SeekHead",
+                "Void",
+                "Info",
+                "Tracks",
+                "Cluster",
+                "Cluster",
+                "Cues",
+                C2PA_BOXHASH
+            ]
+        );
+
+        // box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+}
@@ -11,14 +11,28 @@
 // specific language governing permissions and limitations under
 // each license.
 
+pub mod aiff_io;
 pub mod bmff_io;
 pub mod c2pa_io;
+pub mod exr_io;
+pub mod flac_io;
+pub mod gif_io;
+pub mod gltf_io;
+pub mod jpeg2000_io;
 pub mod jpeg_io;
+pub mod jpegxl_io;
+pub mod matroska_io;
 pub mod mp3_io;
+pub mod mpegts_io;
+pub mod ogg_io;
 pub mod png_io;
+pub mod psd_io;
 pub mod riff_io;
+pub mod sfnt_io;
 pub mod svg_io;
+pub mod text_io;
 pub mod tiff_io;
+pub mod zip_io;
 
 #[cfg(feature = "pdf")]
 pub(crate) mod pdf;
@@ -0,0 +1,895 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! MPEG-2 Transport Stream support.
+//!
+//! The manifest store is carried in `private_stream_2` PES packets on a private data PID.
+//! The PID is registered in the PMT of the first program with a registration descriptor
+//! using the `C2PA` format identifier, and its packets follow the first PMT packet.
+//! Data hash exclusions always cover whole transport packets.
+//!
+//! Each HLS segment starts with its own PAT and PMT, so a segment is signed and
+//! validated on its own with a manifest store carried in that segment.
+
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use conv::ValueFrom;
+use tempfile::Builder;
+
+use crate::{
+    asset_io::{
+        rename_or_copy, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        HashBlockObjectType, HashObjectPositions,
+    },
+    error::{Error, Result},
+};
+
+static SUPPORTED_TYPES: [&str; 4] = ["ts", "m2ts", "mts", "video/mp2t"];
+
+const SYNC_BYTE: u8 = 0x47;
+
+// transport packets, BDAV (m2ts) packets have an extra 4 byte timestamp prefix
+const TS_PACKET_LEN: usize = 188;
+const M2TS_PREFIX_LEN: usize = 4;
+const TS_HDR_LEN: usize = 4;
+const TS_PAYLOAD_LEN: usize = TS_PACKET_LEN - TS_HDR_LEN;
+
+const PAT_PID: u16 = 0x0000;
+const NULL_PID: u16 = 0x1fff;
+const PAT_TABLE_ID: u8 = 0x00;
+const PMT_TABLE_ID: u8 = 0x02;
+
+// preferred PID for the manifest store, the next free PID below is used if taken
+const C2PA_PID: u16 = 0x1c2a;
+const MIN_PID: u16 = 0x0010;
+
+const PRIVATE_DATA_STREAM_TYPE: u8 = 0x06;
+const REGISTRATION_DESCRIPTOR_TAG: u8 = 0x05;
+const C2PA_FORMAT_IDENTIFIER: [u8; 4] = *b"C2PA";
+
+// private_stream_2 PES packets have no optional header
+const PRIVATE_STREAM_2: [u8; 4] = [0x00, 0x00, 0x01, 0xbf];
+const PES_HDR_LEN: usize = 6;
+const MAX_PES_PAYLOAD: usize = 0xffff;
+
+// section CRC, polynomial 0x04c11db7 with no reflection
+fn crc32_mpeg2(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for b in data {
+        crc ^= (*b as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x80000000 != 0 {
+                (crc << 1) ^ 0x04c11db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PacketFormat {
+    pub packet_len: usize,
+    pub prefix_len: usize,
+}
+
+// information collected from a pass over the packets
+#[derive(Default)]
+struct TsInfo {
+    pub pmt_pid: Option<u16>,
+    pub c2pa_pids: Vec<u16>,
+    pub used_pids: HashSet<u16>,
+    pub c2pa_runs: Vec<(u64, u64)>, // first packet and packet count
+    pub c2pa_pes: Vec<Vec<u8>>,
+    pub packet_count: u64,
+}
+
+fn ts_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("MPEG-TS out of range".to_string())
+}
+
+fn detect_packet_format<R: Read + Seek + ?Sized>(f: &mut R) -> Result<PacketFormat> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut header = [0u8; M2TS_PREFIX_LEN + TS_PACKET_LEN + 1];
+    let header_len = std::cmp::min(header.len() as u64, stream_len) as usize;
+    f.read_exact(&mut header[..header_len])?;
+    f.rewind()?;
+
+    let is_synced = |first: usize, packet_len: usize| {
+        header_len > first
+            && header[first] == SYNC_BYTE
+            && (header_len <= first + packet_len || header[first + packet_len] == SYNC_BYTE)
+    };
+
+    let format = if is_synced(0, TS_PACKET_LEN) {
+        PacketFormat {
+            packet_len: TS_PACKET_LEN,
+            prefix_len: 0,
+        }
+    } else if is_synced(M2TS_PREFIX_LEN, M2TS_PREFIX_LEN + TS_PACKET_LEN) {
+        PacketFormat {
+            packet_len: M2TS_PREFIX_LEN + TS_PACKET_LEN,
+            prefix_len: M2TS_PREFIX_LEN,
+        }
+    } else {
+        return Err(Error::InvalidAsset("MPEG-TS invalid".to_string()));
+    };
+
+    if stream_len % format.packet_len as u64 != 0 {
+        return Err(Error::InvalidAsset("MPEG-TS truncated packet".to_string()));
+    }
+
+    Ok(format)
+}
+
+// read packets sequentially, returning the prefix and transport packet
+struct PacketReader<'a> {
+    reader: BufReader<&'a mut dyn CAIRead>,
+    format: PacketFormat,
+    buf: [u8; M2TS_PREFIX_LEN + TS_PACKET_LEN],
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(f: &'a mut dyn CAIRead) -> Result<Self> {
+        let format = detect_packet_format(f)?;
+        Ok(PacketReader {
+            reader: BufReader::new(f),
+            format,
+            buf: [0u8; M2TS_PREFIX_LEN + TS_PACKET_LEN],
+        })
+    }
+
+    fn next_packet(&mut self) -> Result<Option<(&[u8], &[u8])>> {
+        let packet = &mut self.buf[..self.format.packet_len];
+        match self.reader.read_exact(packet) {
+            Ok(_) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let (prefix, ts_packet) = packet.split_at(self.format.prefix_len);
+        if ts_packet[0] != SYNC_BYTE {
+            return Err(Error::InvalidAsset("MPEG-TS lost sync".to_string()));
+        }
+
+        Ok(Some((prefix, ts_packet)))
+    }
+}
+
+fn packet_pid(packet: &[u8]) -> u16 {
+    (((packet[1] & 0x1f) as u16) << 8) | packet[2] as u16
+}
+
+fn packet_pusi(packet: &[u8]) -> bool {
+    packet[1] & 0x40 != 0
+}
+
+// offset of the payload in the packet, None if there is no payload
+fn payload_offset(packet: &[u8]) -> Result<Option<usize>> {
+    let adaptation_field_control = (packet[3] >> 4) & 0x03;
+
+    let offset = match adaptation_field_control {
+        0x01 => TS_HDR_LEN,
+        0x03 => TS_HDR_LEN + 1 + packet[TS_HDR_LEN] as usize,
+        _ => return Ok(None),
+    };
+
+    if offset > TS_PACKET_LEN {
+        return Err(Error::InvalidAsset(
+            "MPEG-TS bad adaptation field".to_string(),
+        ));
+    }
+
+    Ok(Some(offset))
+}
+
+// the PSI section starting in this packet, sections must fit in one packet
+fn packet_section(packet: &[u8]) -> Result<Option<(usize, &[u8])>> {
+    let Some(offset) = payload_offset(packet)? else {
+        return Ok(None);
+    };
+
+    let payload = &packet[offset..];
+    let pointer = *payload.first().ok_or_else(|| ts_err(()))? as usize;
+    let section = payload.get(1 + pointer..).ok_or_else(|| ts_err(()))?;
+    if section.len() < 3 {
+        return Err(ts_err(()));
+    }
+
+    let section_len = 3 + ((((section[1] & 0x0f) as usize) << 8) | section[2] as usize);
+    match section.get(..section_len) {
+        Some(section) if section_len >= 12 => Ok(Some((offset + 1 + pointer, section))),
+        _ => Err(Error::InvalidAsset(
+            "MPEG-TS multi-packet sections are not supported".to_string(),
+        )),
+    }
+}
+
+// returns the PMT PID of the first program
+fn parse_pat(section: &[u8]) -> Option<u16> {
+    section[8..section.len() - 4]
+        .chunks_exact(4)
+        .filter(|p| p[0] != 0 || p[1] != 0) // skip the network PID
+        .map(|p| (((p[2] & 0x1f) as u16) << 8) | p[3] as u16)
+        .next()
+}
+
+struct EsInfo<'a> {
+    pub pid: u16,
+    pub is_c2pa: bool,
+    pub data: &'a [u8],
+}
+
+fn is_c2pa_registration(descriptors: &[u8]) -> bool {
+    let mut pos = 0;
+    while pos + 2 <= descriptors.len() {
+        let tag = descriptors[pos];
+        let len = descriptors[pos + 1] as usize;
+        if tag == REGISTRATION_DESCRIPTOR_TAG
+            && descriptors.get(pos + 2..pos + 6) == Some(&C2PA_FORMAT_IDENTIFIER[..])
+        {
+            return true;
+        }
+        pos += 2 + len;
+    }
+    false
+}
+
+// returns the length of the PMT header up to the ES loop and the ES entries
+fn parse_pmt(section: &[u8]) -> Result<(usize, Vec<EsInfo<'_>>)> {
+    let program_info_len = ((((section[10] & 0x0f) as usize) << 8) | section[11] as usize) + 12;
+    let es_end = section.len() - 4;
+    if program_info_len > es_end {
+        return Err(Error::InvalidAsset("MPEG-TS bad PMT".to_string()));
+    }
+
+    let mut entries = Vec::new();
+    let mut pos = program_info_len;
+    while pos + 5 <= es_end {
+        let pid = (((section[pos + 1] & 0x1f) as u16) << 8) | section[pos + 2] as u16;
+        let es_info_len = (((section[pos + 3] & 0x0f) as usize) << 8) | section[pos + 4] as usize;
+        let data = section
+            .get(pos..pos + 5 + es_info_len)
+            .ok_or_else(|| Error::InvalidAsset("MPEG-TS bad PMT".to_string()))?;
+
+        entries.push(EsInfo {
+            pid,
+            is_c2pa: is_c2pa_registration(&data[5..]),
+            data,
+        });
+        pos += data.len();
+    }
+
+    Ok((program_info_len, entries))
+}
+
+// Rebuild the PMT without any C2PA streams, adding one on `c2pa_pid` when present.
+fn make_pmt_section(section: &[u8], c2pa_pid: Option<u16>) -> Result<Vec<u8>> {
+    let (header_len, entries) = parse_pmt(section)?;
+
+    let mut new_section = section[..header_len].to_vec();
+    for entry in entries.iter().filter(|e| !e.is_c2pa) {
+        new_section.extend_from_slice(entry.data);
+    }
+
+    if let Some(pid) = c2pa_pid {
+        new_section.push(PRIVATE_DATA_STREAM_TYPE);
+        new_section.extend_from_slice(&(0xe000 | pid).to_be_bytes());
+        new_section.extend_from_slice(&(0xf000u16 | 6).to_be_bytes());
+        new_section.push(REGISTRATION_DESCRIPTOR_TAG);
+        new_section.push(C2PA_FORMAT_IDENTIFIER.len() as u8);
+        new_section.extend_from_slice(&C2PA_FORMAT_IDENTIFIER);
+    }
+
+    // section length counts the bytes after the length field including the CRC
+    let section_len = new_section.len() + 4 - 3;
+    if section_len > 0x3fd {
+        return Err(Error::InvalidAsset("MPEG-TS PMT too large".to_string()));
+    }
+    new_section[1] = (section[1] & 0xf0) | (section_len >> 8) as u8;
+    new_section[2] = section_len as u8;
+
+    let crc = crc32_mpeg2(&new_section);
+    new_section.extend_from_slice(&crc.to_be_bytes());
+
+    Ok(new_section)
+}
+
+// replace the section in a PMT packet, keeping the packet header and adaptation field
+fn make_pmt_packet(packet: &[u8], c2pa_pid: Option<u16>) -> Result<Vec<u8>> {
+    let (section_offset, section) = packet_section(packet)?.ok_or_else(|| ts_err(()))?;
+    let new_section = make_pmt_section(section, c2pa_pid)?;
+
+    let mut new_packet = packet[..section_offset].to_vec();
+    new_packet.extend_from_slice(&new_section);
+    if new_packet.len() > TS_PACKET_LEN {
+        return Err(Error::InvalidAsset("MPEG-TS PMT too large".to_string()));
+    }
+    new_packet.resize(TS_PACKET_LEN, 0xff);
+
+    Ok(new_packet)
+}
+
+// split the manifest store into PES packets carried in transport packets
+fn make_c2pa_packets(store_bytes: &[u8], pid: u16) -> Vec<Vec<u8>> {
+    let mut packets = Vec::new();
+    let mut continuity_counter = 0u8;
+
+    for chunk in store_bytes.chunks(MAX_PES_PAYLOAD) {
+        let mut pes = Vec::with_capacity(PES_HDR_LEN + chunk.len());
+        pes.extend_from_slice(&PRIVATE_STREAM_2);
+        pes.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+        pes.extend_from_slice(chunk);
+
+        for (index, payload) in pes.chunks(TS_PAYLOAD_LEN).enumerate() {
+            let pusi = if index == 0 { 0x40 } else { 0x00 };
+
+            let mut packet = Vec::with_capacity(TS_PACKET_LEN);
+            packet.push(SYNC_BYTE);
+            packet.push(pusi | (pid >> 8) as u8);
+            packet.push(pid as u8);
+
+            if payload.len() < TS_PAYLOAD_LEN {
+                // fill the rest of the packet with adaptation field stuffing
+                packet.push(0x30 | continuity_counter);
+                let adaptation_field_len = TS_PAYLOAD_LEN - payload.len() - 1;
+                packet.push(adaptation_field_len as u8);
+                if adaptation_field_len > 0 {
+                    packet.push(0x00); // no adaptation field flags
+                    packet.resize(TS_HDR_LEN + 1 + adaptation_field_len, 0xff);
+                }
+            } else {
+                packet.push(0x10 | continuity_counter);
+            }
+            packet.extend_from_slice(payload);
+
+            continuity_counter = (continuity_counter + 1) & 0x0f;
+            packets.push(packet);
+        }
+    }
+
+    packets
+}
+
+// Scan the packets for the PMT of the first program and the C2PA PIDs it registers.
+// The C2PA PES packets are collected when `collect_pes` is set.
+fn scan_ts(input_stream: &mut dyn CAIRead, collect_pes: bool) -> Result<TsInfo> {
+    let mut info = TsInfo::default();
+    let mut reader = PacketReader::new(input_stream)?;
+
+    let mut pat_found = false;
+    let mut pmt_found = false;
+    let mut index = 0u64;
+    while let Some((_prefix, packet)) = reader.next_packet()? {
+        let pid = packet_pid(packet);
+        info.used_pids.insert(pid);
+
+        if pid == PAT_PID && packet_pusi(packet) && !pat_found {
+            if let Some((_, section)) = packet_section(packet)? {
+                if section[0] == PAT_TABLE_ID {
+                    info.pmt_pid = parse_pat(section);
+                    pat_found = true;
+                }
+            }
+        } else if Some(pid) == info.pmt_pid && packet_pusi(packet) && !pmt_found {
+            if let Some((_, section)) = packet_section(packet)? {
+                if section[0] == PMT_TABLE_ID {
+                    let (_, entries) = parse_pmt(section)?;
+                    for entry in &entries {
+                        info.used_pids.insert(entry.pid);
+                        if entry.is_c2pa {
+                            info.c2pa_pids.push(entry.pid);
+                        }
+                    }
+                    pmt_found = true;
+                }
+            }
+        } else if info.c2pa_pids.contains(&pid) {
+            // group adjacent packets into runs
+            match info.c2pa_runs.last_mut() {
+                Some((start, count)) if *start + *count == index => *count += 1,
+                _ => info.c2pa_runs.push((index, 1)),
+            }
+
+            if collect_pes && Some(&pid) == info.c2pa_pids.first() {
+                if let Some(offset) = payload_offset(packet)? {
+                    if packet_pusi(packet) {
+                        info.c2pa_pes.push(Vec::new());
+                    }
+                    if let Some(pes) = info.c2pa_pes.last_mut() {
+                        pes.extend_from_slice(&packet[offset..]);
+                    }
+                }
+            }
+        }
+
+        index += 1;
+    }
+    info.packet_count = index;
+
+    if !pmt_found {
+        return Err(Error::InvalidAsset("MPEG-TS PMT not found".to_string()));
+    }
+
+    Ok(info)
+}
+
+// Rewrite the stream without the C2PA PID, adding the manifest store after the first
+// PMT packet when `store_bytes` is present.  All PMT packets are updated.
+fn rewrite_ts(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    store_bytes: Option<&[u8]>,
+) -> Result<()> {
+    let info = scan_ts(input_stream, false)?;
+    let pmt_pid = info.pmt_pid.ok_or_else(|| ts_err(()))?;
+
+    // pick a PID that is not in use
+    let c2pa_pid = match store_bytes {
+        Some(_) => Some(
+            (MIN_PID..=C2PA_PID)
+                .rev()
+                .find(|pid| {
+                    info.c2pa_pids.contains(pid)
+                        || !(info.used_pids.contains(pid) || *pid == NULL_PID)
+                })
+                .ok_or_else(|| Error::InvalidAsset("MPEG-TS no free PID".to_string()))?,
+        ),
+        None => None,
+    };
+    let mut c2pa_packets = store_bytes.map(|s| make_c2pa_packets(s, c2pa_pid.unwrap_or(C2PA_PID)));
+
+    let mut reader = PacketReader::new(input_stream)?;
+
+    output_stream.rewind()?;
+    let mut writer = BufWriter::new(&mut *output_stream);
+
+    while let Some((prefix, packet)) = reader.next_packet()? {
+        let pid = packet_pid(packet);
+
+        if info.c2pa_pids.contains(&pid) {
+            continue;
+        }
+
+        writer.write_all(prefix)?;
+
+        let is_pmt = pid == pmt_pid
+            && packet_pusi(packet)
+            && packet_section(packet)?.map(|(_, s)| s[0]) == Some(PMT_TABLE_ID);
+        if !is_pmt {
+            writer.write_all(packet)?;
+            continue;
+        }
+
+        writer.write_all(&make_pmt_packet(packet, c2pa_pid)?)?;
+
+        // the manifest store follows the first PMT, the timestamp prefix is repeated
+        if let Some(packets) = c2pa_packets.take() {
+            for c2pa_packet in packets {
+                writer.write_all(prefix)?;
+                writer.write_all(&c2pa_packet)?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn add_required_packets(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let mpegts_io = MpegTsIO {};
+
+    match mpegts_io.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            mpegts_io.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct MpegTsIO {}
+
+impl CAIReader for MpegTsIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let info = scan_ts(input_stream, true)?;
+
+        match info.c2pa_pids.len() {
+            0 => return Err(Error::JumbfNotFound),
+            1 => (),
+            _ => return Err(Error::TooManyManifestStores),
+        }
+
+        let mut manifest = Vec::new();
+        for pes in &info.c2pa_pes {
+            let payload_len = pes
+                .get(4..PES_HDR_LEN)
+                .map(|len| u16::from_be_bytes([len[0], len[1]]) as usize)
+                .ok_or_else(|| Error::InvalidAsset("MPEG-TS bad C2PA PES".to_string()))?;
+
+            if pes[..4] != PRIVATE_STREAM_2 || pes.len() != PES_HDR_LEN + payload_len {
+                return Err(Error::InvalidAsset("MPEG-TS bad C2PA PES".to_string()));
+            }
+            manifest.extend_from_slice(&pes[PES_HDR_LEN..]);
+        }
+
+        if manifest.is_empty() {
+            return Err(Error::JumbfNotFound);
+        }
+
+        Ok(manifest)
+    }
+
+    // XMP is not supported in transport streams
+    fn read_xmp(&self, _input_stream: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for MpegTsIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        rewrite_ts(input_stream, output_stream, Some(store_bytes))
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_packets(input_stream, &mut output_stream)?;
+
+        let format = detect_packet_format(&mut output_stream)?;
+        let info = scan_ts(&mut output_stream, false)?;
+
+        let to_offset = |packet_index: u64| -> Result<usize> {
+            usize::value_from(packet_index * format.packet_len as u64)
+                .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))
+        };
+
+        // exclusions are whole packets
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+        let mut current_packet = 0u64;
+        for (start, count) in &info.c2pa_runs {
+            if *start > current_packet {
+                positions.push(HashObjectPositions {
+                    offset: to_offset(current_packet)?,
+                    length: to_offset(*start - current_packet)?,
+                    htype: HashBlockObjectType::Other,
+                });
+            }
+
+            positions.push(HashObjectPositions {
+                offset: to_offset(*start)?,
+                length: to_offset(*count)?,
+                htype: HashBlockObjectType::Cai,
+            });
+            current_packet = start + count;
+        }
+
+        if info.packet_count > current_packet {
+            positions.push(HashObjectPositions {
+                offset: to_offset(current_packet)?,
+                length: to_offset(info.packet_count - current_packet)?,
+                htype: HashBlockObjectType::Other,
+            });
+        }
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_ts(input_stream, output_stream, None)
+    }
+}
+
+impl AssetIO for MpegTsIO {
+    fn new(_asset_type: &str) -> Self {
+        MpegTsIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(MpegTsIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(MpegTsIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for MpegTsIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let format = detect_packet_format(&mut asset)?;
+        let info = scan_ts(&mut asset, false)?;
+
+        let (pid, (start, count)) = match (info.c2pa_pids.as_slice(), info.c2pa_runs.as_slice()) {
+            ([pid], [run]) => (*pid, *run),
+            ([], _) => return Err(Error::JumbfNotFound),
+            _ => return Err(Error::TooManyManifestStores),
+        };
+
+        let packets = make_c2pa_packets(store_bytes, pid);
+        if packets.len() as u64 != count {
+            return Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ));
+        }
+
+        // packets of the same store size only differ in the payload
+        for (index, packet) in packets.iter().enumerate() {
+            let offset = (start + index as u64) * format.packet_len as u64;
+            asset.seek(SeekFrom::Start(offset + format.prefix_len as u64))?;
+            asset.write_all(packet)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    // PIDs of the packets and streams in the PMT
+    fn used_pids(data: &[u8]) -> Vec<u16> {
+        let info = scan_ts(&mut Cursor::new(data), false).unwrap();
+        let mut pids: Vec<u16> = info.used_pids.into_iter().collect();
+        pids.sort();
+        pids
+    }
+
+    #[test]
+    fn test_mpegts_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.ts");
+
+        let info = scan_ts(&mut Cursor::new(source.to_vec()), true).unwrap();
+        assert_eq!(info.pmt_pid, Some(0x1000));
+        assert!(info.c2pa_pids.is_empty());
+        assert_eq!(info.packet_count, 12);
+        assert_eq!(
+            used_pids(source),
+            vec![0x0000, 0x0100, 0x0101, 0x1000, 0x1fff]
+        );
+    }
+
+    #[test]
+    fn test_crc() {
+        // a section including its CRC has a remainder of zero
+        let source = include_bytes!("../../tests/fixtures/sample1.ts");
+        let (_, section) = packet_section(&source[..TS_PACKET_LEN]).unwrap().unwrap();
+        assert_eq!(crc32_mpeg2(section), 0);
+    }
+
+    #[test]
+    fn test_write_ts() {
+        let more_data = vec![0xa5u8; 70000];
+        let source = fixture_path("sample1.ts");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-ts.ts");
+        std::fs::copy(source, &output).unwrap();
+
+        let mpegts_io = MpegTsIO::new("ts");
+        mpegts_io.save_cai_store(&output, &more_data).unwrap();
+        assert_eq!(mpegts_io.read_cai_store(&output).unwrap(), more_data);
+
+        let data = std::fs::read(&output).unwrap();
+        let info = scan_ts(&mut Cursor::new(&data), false).unwrap();
+        assert_eq!(info.c2pa_pids, vec![C2PA_PID]);
+
+        // the manifest store follows the first PMT
+        assert_eq!(info.c2pa_runs.len(), 1);
+        assert_eq!(info.c2pa_runs[0].0, 2);
+
+        // every PMT registers the C2PA PID
+        for packet in data.chunks(TS_PACKET_LEN) {
+            if packet_pid(packet) == 0x1000 {
+                let (_, section) = packet_section(packet).unwrap().unwrap();
+                assert_eq!(crc32_mpeg2(section), 0);
+                let (_, entries) = parse_pmt(section).unwrap();
+                assert!(entries.iter().any(|e| e.is_c2pa && e.pid == C2PA_PID));
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_m2ts_stream() {
+        let source = include_bytes!("../../tests/fixtures/sample1.m2ts");
+        let mpegts_io = MpegTsIO::new("m2ts");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        mpegts_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert_eq!(
+            mpegts_io.read_cai(&mut output_stream).unwrap(),
+            vec![1, 2, 3]
+        );
+
+        // the C2PA packet repeats the timestamp of the PMT packet
+        let data = output_stream.into_inner();
+        assert_eq!(data.len(), source.len() + 192);
+        assert_eq!(data[2 * 192..2 * 192 + 4], source[192..192 + 4]);
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let mpegts_io = MpegTsIO::new("ts");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            mpegts_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_get_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.ts");
+        let mpegts_io = MpegTsIO::new("ts");
+
+        let positions = mpegts_io
+            .get_object_locations_from_stream(&mut Cursor::new(source.to_vec()))
+            .unwrap();
+
+        // all positions are whole packets and cover the stream
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, source.len() + TS_PACKET_LEN);
+        assert!(positions
+            .iter()
+            .all(|p| p.offset % TS_PACKET_LEN == 0 && p.length % TS_PACKET_LEN == 0));
+
+        let cai: Vec<&HashObjectPositions> = positions
+            .iter()
+            .filter(|p| p.htype == HashBlockObjectType::Cai)
+            .collect();
+        assert_eq!(cai.len(), 1);
+        assert_eq!(cai[0].offset, 2 * TS_PACKET_LEN);
+        assert_eq!(cai[0].length, TS_PACKET_LEN);
+    }
+
+    #[test]
+    fn test_patch_write_ts() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("sample1.ts");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-ts.ts");
+        std::fs::copy(source, &output).unwrap();
+
+        let mpegts_io = MpegTsIO::new("ts");
+        mpegts_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        mpegts_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(mpegts_io.read_cai_store(&output).unwrap(), new_data);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.m2ts");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-m2ts.m2ts");
+        std::fs::copy(&source, &output).unwrap();
+
+        let mpegts_io = MpegTsIO::new("m2ts");
+        mpegts_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        mpegts_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match mpegts_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+}
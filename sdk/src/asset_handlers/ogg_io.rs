@@ -0,0 +1,784 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Ogg (Vorbis/Opus) support.
+//!
+//! The manifest store is carried in its own logical bitstream that is multiplexed
+//! with the media streams.  Its BOS page is placed directly after the BOS pages of
+//! the first link and the remaining pages follow immediately, so the manifest
+//! occupies a single contiguous byte range and the pages of the media streams are
+//! never modified.  Decoders ignore logical streams they do not recognize.
+
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use conv::ValueFrom;
+use tempfile::Builder;
+
+use crate::{
+    asset_io::{
+        rename_or_copy, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        HashBlockObjectType, HashObjectPositions,
+    },
+    error::{Error, Result},
+};
+
+static SUPPORTED_TYPES: [&str; 5] = ["ogg", "oga", "opus", "audio/ogg", "audio/opus"];
+
+const CAPTURE_PATTERN: [u8; 4] = *b"OggS";
+
+// identification packet found in the BOS page of the C2PA logical stream
+const C2PA_STREAM_ID: [u8; 8] = *b"\x00c2pa\x00\x00\x00";
+
+// preferred serial number of the C2PA logical stream ("c2pa")
+const C2PA_SERIAL: u32 = 0x6332_7061;
+
+// capture pattern(4) + version(1) + header type(1) + granule(8) + serial(4) +
+// sequence(4) + crc(4) + segment count(1)
+const PAGE_HDR_LEN: u64 = 27;
+const CRC_OFFSET: usize = 22;
+
+const MAX_SEGMENTS: usize = 255;
+const MAX_SEGMENT_LEN: usize = 255;
+
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_BOS: u8 = 0x02;
+const FLAG_EOS: u8 = 0x04;
+
+// granule position of a page on which no packet completes
+const NO_GRANULE: u64 = u64::MAX;
+
+static CRC_TABLE: [u32; 256] = make_crc_table();
+
+// Ogg uses the unreflected CRC-32 with polynomial 0x04c11db7, no initial or final xor
+const fn make_crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut r = (i as u32) << 24;
+        let mut j = 0;
+        while j < 8 {
+            r = if r & 0x8000_0000 != 0 {
+                (r << 1) ^ 0x04c1_1db7
+            } else {
+                r << 1
+            };
+            j += 1;
+        }
+        table[i] = r;
+        i += 1;
+    }
+    table
+}
+
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |crc, b| {
+        (crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ b) as usize]
+    })
+}
+
+#[derive(Clone, Debug)]
+struct OggPage {
+    pub start: u64,
+    pub header_type: u8,
+    pub serial: u32,
+    pub segments: Vec<u8>,
+}
+
+impl OggPage {
+    pub fn header_len(&self) -> u64 {
+        PAGE_HDR_LEN + self.segments.len() as u64
+    }
+
+    pub fn body_len(&self) -> u64 {
+        self.segments.iter().map(|s| *s as u64).sum()
+    }
+
+    pub fn end(&self) -> u64 {
+        self.start + self.header_len() + self.body_len()
+    }
+
+    pub fn is_bos(&self) -> bool {
+        self.header_type & FLAG_BOS != 0
+    }
+}
+
+fn ogg_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("OGG out of range".to_string())
+}
+
+// Read the page headers, skipping over the page bodies.
+fn get_ogg_pages<R: Read + Seek + ?Sized>(f: &mut R) -> Result<Vec<OggPage>> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut pages = Vec::new();
+    let mut current_pos = 0;
+    while current_pos < stream_len {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let mut capture = [0u8; 4];
+        f.read_exact(&mut capture).map_err(ogg_err)?;
+        let version = f.read_u8().map_err(ogg_err)?;
+        if capture != CAPTURE_PATTERN || version != 0 {
+            return Err(Error::InvalidAsset("OGG invalid".to_string()));
+        }
+
+        let header_type = f.read_u8().map_err(ogg_err)?;
+        let _granule = f.read_u64::<LittleEndian>().map_err(ogg_err)?;
+        let serial = f.read_u32::<LittleEndian>().map_err(ogg_err)?;
+        let _sequence = f.read_u32::<LittleEndian>().map_err(ogg_err)?;
+        let _crc = f.read_u32::<LittleEndian>().map_err(ogg_err)?;
+        let num_segments = f.read_u8().map_err(ogg_err)?;
+        let mut segments = vec![0u8; num_segments as usize];
+        f.read_exact(&mut segments).map_err(ogg_err)?;
+
+        let page = OggPage {
+            start: current_pos,
+            header_type,
+            serial,
+            segments,
+        };
+
+        if page.end() > stream_len {
+            return Err(Error::InvalidAsset("OGG bad page size".to_string()));
+        }
+
+        current_pos = page.end();
+        pages.push(page);
+    }
+
+    if pages.is_empty() || !pages[0].is_bos() {
+        return Err(Error::InvalidAsset("OGG invalid".to_string()));
+    }
+
+    Ok(pages)
+}
+
+fn read_page_body<R: Read + Seek + ?Sized>(f: &mut R, page: &OggPage) -> Result<Vec<u8>> {
+    let body_len = usize::value_from(page.body_len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(page.start + page.header_len()))?;
+    let mut body = vec![0u8; body_len];
+    f.read_exact(&mut body).map_err(ogg_err)?;
+
+    Ok(body)
+}
+
+// Returns the serial number of the C2PA logical stream if there is one.
+fn find_c2pa_serial<R: Read + Seek + ?Sized>(f: &mut R, pages: &[OggPage]) -> Result<Option<u32>> {
+    let mut c2pa_serial = None;
+
+    for page in pages.iter().filter(|p| p.is_bos()) {
+        // the identification packet is always the only packet on the BOS page
+        if page.segments.len() != 1 || page.body_len() != C2PA_STREAM_ID.len() as u64 {
+            continue;
+        }
+
+        if read_page_body(f, page)? == C2PA_STREAM_ID {
+            if c2pa_serial.is_some() {
+                return Err(Error::TooManyManifestStores);
+            }
+            c2pa_serial = Some(page.serial);
+        }
+    }
+
+    Ok(c2pa_serial)
+}
+
+// Reassemble the packets of a logical stream.
+fn read_packets<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    pages: &[OggPage],
+    serial: u32,
+) -> Result<Vec<Vec<u8>>> {
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+
+    for page in pages.iter().filter(|p| p.serial == serial) {
+        let body = read_page_body(f, page)?;
+
+        let mut pos = 0;
+        for segment in &page.segments {
+            let seg_len = *segment as usize;
+            packet.extend_from_slice(&body[pos..pos + seg_len]);
+            pos += seg_len;
+
+            // a segment shorter than the maximum ends the packet
+            if seg_len < MAX_SEGMENT_LEN {
+                packets.push(std::mem::take(&mut packet));
+            }
+        }
+    }
+
+    Ok(packets)
+}
+
+fn write_page(
+    output: &mut Vec<u8>,
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    sequence: u32,
+    segments: &[u8],
+    body: &[u8],
+) {
+    let page_start = output.len();
+
+    output.extend_from_slice(&CAPTURE_PATTERN);
+    output.push(0); // version
+    output.push(header_type);
+    output.extend_from_slice(&granule.to_le_bytes());
+    output.extend_from_slice(&serial.to_le_bytes());
+    output.extend_from_slice(&sequence.to_le_bytes());
+    output.extend_from_slice(&[0u8; 4]); // crc is computed with this field zeroed
+    output.push(segments.len() as u8);
+    output.extend_from_slice(segments);
+    output.extend_from_slice(body);
+
+    let crc = ogg_crc(&output[page_start..]);
+    output[page_start + CRC_OFFSET..page_start + CRC_OFFSET + 4]
+        .copy_from_slice(&crc.to_le_bytes());
+}
+
+// Build the pages of the C2PA logical stream: a BOS page with the identification
+// packet followed by the manifest store packet, split across as many pages as needed.
+fn make_c2pa_pages(serial: u32, store_bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    write_page(
+        &mut output,
+        FLAG_BOS,
+        0,
+        serial,
+        0,
+        &[C2PA_STREAM_ID.len() as u8],
+        &C2PA_STREAM_ID,
+    );
+
+    // lace the store, a packet that is a multiple of 255 ends with a zero length segment
+    let mut lacing = vec![MAX_SEGMENT_LEN as u8; store_bytes.len() / MAX_SEGMENT_LEN];
+    lacing.push((store_bytes.len() % MAX_SEGMENT_LEN) as u8);
+
+    let page_count = lacing.len().div_ceil(MAX_SEGMENTS);
+    let mut body_pos = 0;
+    for (i, segments) in lacing.chunks(MAX_SEGMENTS).enumerate() {
+        let last_page = i + 1 == page_count;
+
+        let mut header_type = 0;
+        if i > 0 {
+            header_type |= FLAG_CONTINUED;
+        }
+        if last_page {
+            header_type |= FLAG_EOS;
+        }
+        let granule = if last_page { 0 } else { NO_GRANULE };
+
+        let sequence = u32::value_from(i + 1)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+        let body_len: usize = segments.iter().map(|s| *s as usize).sum();
+        write_page(
+            &mut output,
+            header_type,
+            granule,
+            serial,
+            sequence,
+            segments,
+            &store_bytes[body_pos..body_pos + body_len],
+        );
+        body_pos += body_len;
+    }
+
+    Ok(output)
+}
+
+// Byte range of the C2PA logical stream, which must be contiguous.
+fn get_c2pa_range(pages: &[OggPage], serial: u32) -> Result<(u64, u64)> {
+    let c2pa_pages: Vec<(usize, &OggPage)> = pages
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.serial == serial)
+        .collect();
+
+    let (first_index, first_page) = c2pa_pages.first().ok_or(Error::JumbfNotFound)?;
+    let (last_index, last_page) = c2pa_pages.last().ok_or(Error::JumbfNotFound)?;
+
+    if last_index - first_index + 1 != c2pa_pages.len() {
+        return Err(Error::InvalidAsset(
+            "OGG manifest pages are not contiguous".to_string(),
+        ));
+    }
+
+    Ok((first_page.start, last_page.end()))
+}
+
+// Rewrite the stream without the C2PA logical stream, inserting `store_bytes` as
+// a new C2PA logical stream after the BOS pages when present.  The media pages are
+// copied as is.
+fn rewrite_ogg(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    store_bytes: Option<&[u8]>,
+) -> Result<()> {
+    let pages = get_ogg_pages(input_stream)?;
+    let old_serial = find_c2pa_serial(input_stream, &pages)?;
+
+    let kept: Vec<&OggPage> = pages
+        .iter()
+        .filter(|p| Some(p.serial) != old_serial)
+        .collect();
+
+    let c2pa_pages = match store_bytes {
+        Some(store_bytes) => {
+            // pick a serial number that does not collide with the media streams
+            let serials: HashSet<u32> = kept.iter().map(|p| p.serial).collect();
+            let mut serial = old_serial.unwrap_or(C2PA_SERIAL);
+            while serials.contains(&serial) {
+                serial = serial.wrapping_add(1);
+            }
+
+            make_c2pa_pages(serial, store_bytes)?
+        }
+        None => Vec::new(),
+    };
+
+    // all BOS pages of a link must come before any other page
+    let insert_at = kept.iter().position(|p| !p.is_bos()).unwrap_or(kept.len());
+
+    output_stream.rewind()?;
+    for (i, page) in kept.iter().enumerate() {
+        if i == insert_at {
+            output_stream.write_all(&c2pa_pages)?;
+        }
+
+        input_stream.seek(SeekFrom::Start(page.start))?;
+        std::io::copy(
+            &mut input_stream.take(page.end() - page.start),
+            output_stream,
+        )?;
+    }
+    if insert_at == kept.len() {
+        output_stream.write_all(&c2pa_pages)?;
+    }
+
+    Ok(())
+}
+
+fn add_required_pages(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let aio = OggIO {};
+
+    match aio.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            aio.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct OggIO {}
+
+impl CAIReader for OggIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let pages = get_ogg_pages(input_stream)?;
+        let serial = find_c2pa_serial(input_stream, &pages)?.ok_or(Error::JumbfNotFound)?;
+
+        // the manifest store is the packet following the identification packet
+        let mut packets = read_packets(input_stream, &pages, serial)?;
+        if packets.len() != 2 {
+            return Err(Error::InvalidAsset(
+                "OGG invalid manifest stream".to_string(),
+            ));
+        }
+
+        packets.pop().ok_or(Error::JumbfNotFound)
+    }
+
+    // XMP is not supported in Ogg
+    fn read_xmp(&self, _input_stream: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for OggIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        rewrite_ogg(input_stream, output_stream, Some(store_bytes))
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_pages(input_stream, &mut output_stream)?;
+
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let pages = get_ogg_pages(&mut output_stream)?;
+        let serial = find_c2pa_serial(&mut output_stream, &pages)?.ok_or(Error::JumbfNotFound)?;
+        let (start, end) = get_c2pa_range(&pages, serial)?;
+
+        let start = usize::value_from(start)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let end = usize::value_from(end)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let file_end = output_stream.get_ref().len();
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of pages before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_ogg(input_stream, output_stream, None)
+    }
+}
+
+impl AssetIO for OggIO {
+    fn new(_asset_type: &str) -> Self {
+        OggIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(OggIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(OggIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for OggIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let pages = get_ogg_pages(&mut asset)?;
+        let serial = find_c2pa_serial(&mut asset, &pages)?.ok_or(Error::JumbfNotFound)?;
+        let (start, end) = get_c2pa_range(&pages, serial)?;
+        let existing = self.read_cai(&mut asset)?;
+
+        // the pages are regenerated so the checksums match the new store
+        let c2pa_pages = make_c2pa_pages(serial, store_bytes)?;
+
+        if store_bytes.len() == existing.len() && c2pa_pages.len() as u64 == end - start {
+            asset.seek(SeekFrom::Start(start))?;
+            asset.write_all(&c2pa_pages)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    // check the capture pattern, checksum and sequence number of every page
+    fn verify_pages(data: &[u8]) {
+        let mut stream = Cursor::new(data);
+        let pages = get_ogg_pages(&mut stream).unwrap();
+
+        let mut sequences: std::collections::HashMap<u32, u32> = std::collections::HashMap::new();
+        for page in &pages {
+            let mut page_bytes = data[page.start as usize..page.end() as usize].to_vec();
+            let crc = u32::from_le_bytes(page_bytes[22..26].try_into().unwrap());
+            page_bytes[22..26].copy_from_slice(&[0u8; 4]);
+            assert_eq!(ogg_crc(&page_bytes), crc);
+
+            let sequence = u32::from_le_bytes(page_bytes[18..22].try_into().unwrap());
+            let expected = sequences.entry(page.serial).or_insert(0);
+            assert_eq!(sequence, *expected);
+            *expected += 1;
+        }
+    }
+
+    #[test]
+    fn test_ogg_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.opus");
+
+        let mut stream = Cursor::new(source.to_vec());
+        let pages = get_ogg_pages(&mut stream).unwrap();
+        assert_eq!(pages.len(), 4);
+        assert!(pages[0].is_bos());
+        assert_eq!(pages.last().unwrap().end(), source.len() as u64);
+
+        let packets = read_packets(&mut stream, &pages, pages[0].serial).unwrap();
+        assert_eq!(&packets[0][0..8], b"OpusHead");
+        assert_eq!(&packets[1][0..8], b"OpusTags");
+        assert_eq!(packets.len(), 52);
+
+        verify_pages(source);
+    }
+
+    #[test]
+    fn test_write_ogg() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("sample1.opus");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-opus.opus");
+        std::fs::copy(source, &output).unwrap();
+
+        let ogg_io = OggIO::new("opus");
+        ogg_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(ogg_io.read_cai_store(&output).unwrap(), more_data);
+
+        verify_pages(&std::fs::read(&output).unwrap());
+    }
+
+    #[test]
+    fn test_write_large_store() {
+        let source = include_bytes!("../../tests/fixtures/sample1.opus");
+        let ogg_io = OggIO::new("ogg");
+
+        // spans several pages and ends on a segment boundary
+        let store: Vec<u8> = (0..(MAX_SEGMENTS * MAX_SEGMENT_LEN * 2 + 255 * 3))
+            .map(|i| i as u8)
+            .collect();
+
+        let mut output_stream = Cursor::new(Vec::new());
+        ogg_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &store,
+            )
+            .unwrap();
+        assert_eq!(ogg_io.read_cai(&mut output_stream).unwrap(), store);
+        verify_pages(output_stream.get_ref());
+
+        // replace with a smaller store
+        let mut replaced_stream = Cursor::new(Vec::new());
+        ogg_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &[1, 2, 3])
+            .unwrap();
+        assert_eq!(
+            ogg_io.read_cai(&mut replaced_stream).unwrap(),
+            vec![1, 2, 3]
+        );
+        verify_pages(replaced_stream.get_ref());
+    }
+
+    #[test]
+    fn test_media_pages_unchanged() {
+        let source = include_bytes!("../../tests/fixtures/sample1.opus");
+        let ogg_io = OggIO::new("ogg");
+
+        let mut source_stream = Cursor::new(source.to_vec());
+        let source_pages = get_ogg_pages(&mut source_stream).unwrap();
+
+        let mut output_stream = Cursor::new(Vec::new());
+        ogg_io
+            .write_cai(&mut source_stream, &mut output_stream, &[1, 2, 3])
+            .unwrap();
+
+        // manifest stream follows the BOS page of the Opus stream
+        let output = output_stream.get_ref();
+        let first_page_end = source_pages[0].end() as usize;
+        assert_eq!(output[..first_page_end], source[..first_page_end]);
+        assert!(output.ends_with(&source[first_page_end..]));
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let ogg_io = OggIO::new("ogg");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            ogg_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_get_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.opus");
+        let ogg_io = OggIO::new("ogg");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        ogg_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let positions = ogg_io
+            .get_object_locations_from_stream(&mut output_stream)
+            .unwrap();
+        let cai = positions
+            .iter()
+            .find(|p| p.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        // exclusion covers exactly the inserted pages
+        let output = output_stream.get_ref();
+        assert_eq!(output.len() - cai.length, source.len());
+        let mut without_cai = output[..cai.offset].to_vec();
+        without_cai.extend_from_slice(&output[cai.offset + cai.length..]);
+        assert_eq!(without_cai, source);
+    }
+
+    #[test]
+    fn test_patch_write_ogg() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("sample1.opus");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-opus.opus");
+        std::fs::copy(source, &output).unwrap();
+
+        let ogg_io = OggIO::new("opus");
+        ogg_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        ogg_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(ogg_io.read_cai_store(&output).unwrap(), new_data);
+
+        verify_pages(&std::fs::read(&output).unwrap());
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.opus");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-opus.opus");
+        std::fs::copy(&source, &output).unwrap();
+
+        let ogg_io = OggIO::new("opus");
+        ogg_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        ogg_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match ogg_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+}
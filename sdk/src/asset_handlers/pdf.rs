@@ -11,13 +11,16 @@
 // specific language governing permissions and limitations under
 // each license.
 
-// TODO: Remove this after we finish the PDF write feature.
+// Writing manifests as annotations and saving full (non-incremental) copies of the PDF are
+// not used by `PdfIO`, which always writes an embedded file using an incremental update.
 #![allow(dead_code)]
 
 use std::io::{Read, Write};
 
 use lopdf::{
-    dictionary, Document, Object,
+    dictionary,
+    xref::XrefEntry,
+    Dictionary, Document, IncrementalDocument, Object,
     Object::{Array, Integer, Name, Reference},
     ObjectId, Stream,
 };
@@ -59,6 +62,11 @@ pub enum Error {
     /// file specification in the array of Associated Files defined in the catalog.
     #[error("Unable to find a C2PA embedded file specification in PDF's associated files array")]
     FindingC2PAFileSpec,
+
+    /// Error occurred while writing the PDF. Look into the wrapped `lopdf::Error` for more
+    /// information on the cause.
+    #[error("Unable to write the PDF: {0}")]
+    UnableToWritePdf(lopdf::Error),
 }
 
 const C2PA_MIME_TYPE: &str = "application/x-c2pa-manifest-store";
@@ -68,6 +76,10 @@ pub(crate) trait C2paPdf: Sized {
     /// Save the `C2paPdf` implementation to the provided `writer`.
     fn save_to<W: Write + 'static>(&mut self, writer: &mut W) -> Result<(), std::io::Error>;
 
+    /// Save the `C2paPdf` implementation to the provided `writer` as an incremental update
+    /// appended to the bytes the PDF was loaded from.
+    fn save_incremental_to<W: Write + 'static>(&self, writer: &mut W) -> Result<(), Error>;
+
     /// Returns `true` if the `PDF` is password protected, `false` otherwise.
     fn is_password_protected(&self) -> bool;
 
@@ -84,6 +96,12 @@ pub(crate) trait C2paPdf: Sized {
     #[allow(clippy::needless_lifetimes)] // required for automock::mockall
     fn read_manifest_bytes<'a>(&'a self) -> Result<Option<Vec<&'a [u8]>>, Error>;
 
+    /// Replaces the bytes of the existing C2PA manifest with the provided `bytes`.
+    fn replace_manifest_bytes(&mut self, bytes: Vec<u8>) -> Result<(), Error>;
+
+    /// Returns the offset and length of the C2PA manifest bytes in the PDF's source bytes.
+    fn manifest_bytes_position(&self) -> Option<(usize, usize)>;
+
     fn remove_manifest_bytes(&mut self) -> Result<(), Error>;
 
     fn read_xmp(&self) -> Option<String>;
@@ -91,6 +109,10 @@ pub(crate) trait C2paPdf: Sized {
 
 pub(crate) struct Pdf {
     document: Document,
+
+    // The bytes the `document` was loaded from. Kept so that changes can be appended as an
+    // incremental update, which leaves existing PDF signatures intact.
+    source: Vec<u8>,
 }
 
 impl C2paPdf for Pdf {
@@ -99,6 +121,45 @@ impl C2paPdf for Pdf {
         self.document.save_to(writer)
     }
 
+    /// Saves the changes made to the in-memory PDF to the provided `writer` as an incremental
+    /// update. The source bytes of the PDF are written unchanged, followed by every object
+    /// that was added or modified and a new cross-reference section.
+    fn save_incremental_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let prev_document = Document::load_mem(&self.source)?;
+
+        let mut changed_objects = Vec::new();
+        for (id, object) in &self.document.objects {
+            let unchanged = prev_document
+                .objects
+                .get(id)
+                .is_some_and(|prev_object| objects_eq(prev_object, object));
+
+            if !unchanged {
+                changed_objects.push((*id, object.clone()));
+            }
+        }
+
+        let mut incremental = IncrementalDocument::create_from(self.source.clone(), prev_document);
+        incremental.new_document.max_id = self.document.max_id;
+        incremental
+            .new_document
+            .version
+            .clone_from(&self.document.version);
+
+        // Keys describing the previous cross-reference section must not carry over.
+        let trailer = &mut incremental.new_document.trailer;
+        trailer.remove(b"XRefStm");
+        trailer.remove(b"DecodeParms");
+
+        for (id, object) in changed_objects {
+            incremental.new_document.objects.insert(id, object);
+        }
+
+        incremental
+            .save_to(writer)
+            .map_err(|e| Error::UnableToWritePdf(lopdf::Error::IO(e)))
+    }
+
     fn is_password_protected(&self) -> bool {
         self.document.is_encrypted()
     }
@@ -233,6 +294,60 @@ impl C2paPdf for Pdf {
         ]))
     }
 
+    /// Replaces the content of the existing C2PA embedded file stream with `bytes`.
+    ///
+    /// Any `/Filter` and `/DecodeParms` of the existing stream are removed, as `bytes` are
+    /// written unencoded.
+    fn replace_manifest_bytes(&mut self, bytes: Vec<u8>) -> Result<(), Error> {
+        let file_stream_ref = self.c2pa_file_stream_object_id()?;
+
+        self.document
+            .get_object_mut(file_stream_ref)?
+            .as_stream_mut()?
+            .set_plain_content(bytes);
+
+        Ok(())
+    }
+
+    /// Finds the C2PA embedded file stream through the PDF's cross-reference table and returns
+    /// the offset and length of its content in the source bytes of the PDF.
+    ///
+    /// Returns `None` if the PDF has no manifest, if the manifest was added to the in-memory
+    /// PDF and has not been saved yet, or if the manifest stream is encoded with a filter.
+    fn manifest_bytes_position(&self) -> Option<(usize, usize)> {
+        let file_stream_ref = self.c2pa_file_stream_object_id().ok()?;
+
+        let Some(XrefEntry::Normal { offset, .. }) =
+            self.document.reference_table.get(file_stream_ref.0)
+        else {
+            return None;
+        };
+
+        let object_bytes = self.source.get(*offset as usize..)?;
+        let keyword_pos = memchr::memmem::find(object_bytes, b"stream")?;
+
+        // The `stream` keyword is followed by either CRLF or LF before the stream's content.
+        let content_start = match object_bytes.get(keyword_pos + 6..keyword_pos + 8)? {
+            b"\r\n" => keyword_pos + 8,
+            [b'\n', _] => keyword_pos + 7,
+            _ => return None,
+        };
+
+        let file_stream = self
+            .document
+            .get_object(file_stream_ref)
+            .and_then(Object::as_stream)
+            .ok()?;
+
+        // encoded content cannot be overwritten with raw manifest bytes
+        if file_stream.dict.has(b"Filter") {
+            return None;
+        }
+        let length = file_stream.content.len();
+
+        Some((*offset as usize + content_start, length))
+    }
+
     fn remove_manifest_bytes(&mut self) -> Result<(), Error> {
         if !self.has_c2pa_manifest() {
             return Err(Error::NoManifest);
@@ -258,7 +373,7 @@ impl C2paPdf for Pdf {
         // We do the operation in this order because a PDF's annotations are attached to a page.
         // It's possible we'd have to iterate over every page of the PDF before determining the
         // manifest is referenced from an Embedded File instead.
-        self.remove_manifest_from_embedded_files()
+        self.remove_manifest_from_embedded_files(file_spec_ref)
             .or_else(|_| self.remove_manifest_from_annotations())?;
 
         // Remove C2PA associated files from the `AF` key in the catalog.
@@ -301,12 +416,45 @@ impl Pdf {
     #[allow(dead_code)]
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
         let document = Document::load_mem(bytes)?;
-        Ok(Self { document })
+        Ok(Self {
+            document,
+            source: bytes.to_vec(),
+        })
+    }
+
+    pub fn from_reader<R: Read>(mut source: R) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        source
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::UnableToReadPdf(lopdf::Error::IO(e)))?;
+
+        let document = Document::load_mem(&bytes)?;
+        Ok(Self {
+            document,
+            source: bytes,
+        })
     }
 
-    pub fn from_reader<R: Read>(source: R) -> Result<Self, Error> {
-        let document = Document::load_from(source)?;
-        Ok(Self { document })
+    /// Returns the length of the bytes the PDF was loaded from.
+    pub fn source_len(&self) -> usize {
+        self.source.len()
+    }
+
+    /// Returns the [Object::ObjectId] of the C2PA embedded file stream referenced by the C2PA
+    /// File Spec.
+    fn c2pa_file_stream_object_id(&self) -> Result<ObjectId, Error> {
+        let file_spec_ref = self
+            .c2pa_file_spec_object_id()
+            .ok_or_else(|| Error::NoManifest)?;
+
+        Ok(self
+            .document
+            .get_object(file_spec_ref)?
+            .as_dict()?
+            .get_deref(b"EF", &self.document)?
+            .as_dict()?
+            .get(b"F")?
+            .as_reference()?)
     }
 
     /// Returns a reference to the Associated Files array from the PDF's Catalog.
@@ -458,13 +606,15 @@ impl Pdf {
     fn add_c2pa_embedded_file_stream(&mut self, bytes: Vec<u8>) -> ObjectId {
         let stream = Stream::new(
             dictionary! {
-                "F" => dictionary! {
-                SUBTYPE_KEY => C2PA_MIME_TYPE,
+                TYPE_KEY => Name("EmbeddedFile".into()),
+                SUBTYPE_KEY => Name(C2PA_MIME_TYPE.into()),
                 "Length" => Integer(bytes.len() as i64),
-                },
             },
             bytes,
-        );
+        )
+        // The manifest must be stored uncompressed so its bytes can be excluded from the
+        // data hash and patched in place.
+        .with_compression(false);
 
         self.document.add_object(stream)
     }
@@ -490,7 +640,10 @@ impl Pdf {
     }
 
     /// Removes the manifest from the PDF's embedded files collection.
-    fn remove_manifest_from_embedded_files(&mut self) -> Result<(), Error> {
+    fn remove_manifest_from_embedded_files(
+        &mut self,
+        file_spec_ref: ObjectId,
+    ) -> Result<(), Error> {
         let Ok(names) = self.document.catalog_mut()?.get_mut(NAMES_KEY) else {
             return Err(Error::NoManifest);
         };
@@ -516,21 +669,22 @@ impl Pdf {
             _ => names_vector_object.as_array_mut()?,
         };
 
-        // Find the "Content Credentials" marker name in the /Names Array.
-        let content_creds_marker_idx = names_vector
+        // Find the reference to the C2PA file spec in the /Names Array. The name preceding it is
+        // usually "Content Credentials", but other writers may use a different name.
+        let content_creds_reference_idx = names_vector
             .iter()
             .position(|value| {
                 value
-                    .as_string()
-                    .map(|value| value == CONTENT_CREDS)
+                    .as_reference()
+                    .map(|reference| reference == file_spec_ref)
                     .unwrap_or_default()
             })
             .ok_or_else(|| Error::UnableToFindEmbeddedFileManifest)?;
 
-        let content_creds_reference_idx = content_creds_marker_idx + 1;
-        if content_creds_reference_idx >= names_vector.len() {
+        if content_creds_reference_idx == 0 {
             return Err(Error::UnableToFindEmbeddedFileManifest);
         }
+        let content_creds_marker_idx = content_creds_reference_idx - 1;
 
         // Delete the "Content Credentials" marker object and the reference to the C2PA
         // manifest in the PDF's embedded files.
@@ -540,6 +694,34 @@ impl Pdf {
     }
 }
 
+/// Returns `true` if both objects have the same value. `lopdf` objects don't implement
+/// `PartialEq`, so this is used to find the objects that changed since the PDF was loaded.
+fn objects_eq(a: &Object, b: &Object) -> bool {
+    match (a, b) {
+        (Object::Null, Object::Null) => true,
+        (Object::Boolean(a), Object::Boolean(b)) => a == b,
+        (Object::Integer(a), Object::Integer(b)) => a == b,
+        (Object::Real(a), Object::Real(b)) => a == b,
+        (Object::Name(a), Object::Name(b)) => a == b,
+        (Object::String(a, _), Object::String(b, _)) => a == b,
+        (Object::Reference(a), Object::Reference(b)) => a == b,
+        (Object::Array(a), Object::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| objects_eq(a, b))
+        }
+        (Object::Dictionary(a), Object::Dictionary(b)) => dictionaries_eq(a, b),
+        (Object::Stream(a), Object::Stream(b)) => {
+            a.content == b.content && dictionaries_eq(&a.dict, &b.dict)
+        }
+        _ => false,
+    }
+}
+
+fn dictionaries_eq(a: &Dictionary, b: &Dictionary) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(key, value)| b.get(key).is_ok_and(|other| objects_eq(value, other)))
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -789,4 +971,106 @@ mod tests {
         assert!(pdf.remove_manifest_bytes().is_ok());
         assert!(!pdf.has_c2pa_manifest());
     }
+
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_save_incremental_to_preserves_source_bytes() {
+        let bytes = include_bytes!("../../tests/fixtures/basic.pdf");
+        let mut pdf = Pdf::from_bytes(bytes).unwrap();
+        let manifest_bytes = vec![0u8, 1u8, 1u8, 2u8, 3u8];
+        pdf.write_manifest_as_embedded_file(manifest_bytes.clone())
+            .unwrap();
+
+        let mut saved_bytes = vec![];
+        pdf.save_incremental_to(&mut saved_bytes).unwrap();
+        assert!(saved_bytes.starts_with(bytes));
+
+        let saved_pdf = Pdf::from_bytes(&saved_bytes).unwrap();
+        assert!(matches!(
+            saved_pdf.read_manifest_bytes(),
+            Ok(Some(manifests)) if manifests[0] == manifest_bytes
+        ));
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_manifest_bytes_position() {
+        let mut pdf = Pdf::from_bytes(include_bytes!("../../tests/fixtures/basic.pdf")).unwrap();
+        assert!(pdf.manifest_bytes_position().is_none());
+
+        let manifest_bytes = vec![9u8; 20];
+        pdf.write_manifest_as_embedded_file(manifest_bytes.clone())
+            .unwrap();
+
+        // not saved yet, so the manifest has no position in the source bytes
+        assert!(pdf.manifest_bytes_position().is_none());
+
+        let mut saved_bytes = vec![];
+        pdf.save_incremental_to(&mut saved_bytes).unwrap();
+
+        let saved_pdf = Pdf::from_bytes(&saved_bytes).unwrap();
+        let (offset, length) = saved_pdf.manifest_bytes_position().unwrap();
+        assert_eq!(&saved_bytes[offset..offset + length], manifest_bytes);
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_replace_manifest_bytes() {
+        let mut pdf = Pdf::from_bytes(include_bytes!("../../tests/fixtures/basic.pdf")).unwrap();
+        assert!(matches!(
+            pdf.replace_manifest_bytes(vec![1u8]),
+            Err(Error::NoManifest)
+        ));
+
+        pdf.write_manifest_as_embedded_file(vec![0u8, 1u8]).unwrap();
+        pdf.replace_manifest_bytes(vec![2u8, 3u8, 4u8]).unwrap();
+        assert!(matches!(
+            pdf.read_manifest_bytes(),
+            Ok(Some(manifests)) if manifests[0] == [2u8, 3u8, 4u8]
+        ));
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_replace_flate_encoded_manifest_bytes() {
+        let mut pdf = Pdf::from_bytes(include_bytes!("../../tests/fixtures/basic.pdf")).unwrap();
+        pdf.write_manifest_as_embedded_file(vec![0u8; 200]).unwrap();
+
+        // encode the manifest stream as another PDF writer might
+        let file_stream_ref = pdf.c2pa_file_stream_object_id().unwrap();
+        let file_stream = pdf
+            .document
+            .get_object_mut(file_stream_ref)
+            .and_then(Object::as_stream_mut)
+            .unwrap();
+        file_stream.compress().unwrap();
+        assert!(file_stream.dict.has(b"Filter"));
+        let encoded_len = file_stream.content.len();
+
+        let mut saved_bytes = vec![];
+        pdf.save_incremental_to(&mut saved_bytes).unwrap();
+        let mut pdf = Pdf::from_bytes(&saved_bytes).unwrap();
+
+        // a manifest of the encoded size is not patched over the encoded bytes
+        assert!(pdf.manifest_bytes_position().is_none());
+
+        let manifest_bytes = vec![7u8; encoded_len];
+        pdf.replace_manifest_bytes(manifest_bytes.clone()).unwrap();
+        let file_stream = pdf
+            .document
+            .get_object(file_stream_ref)
+            .and_then(Object::as_stream)
+            .unwrap();
+        assert!(!file_stream.dict.has(b"Filter"));
+        assert!(!file_stream.dict.has(b"DecodeParms"));
+
+        let mut saved_bytes = vec![];
+        pdf.save_incremental_to(&mut saved_bytes).unwrap();
+        let saved_pdf = Pdf::from_bytes(&saved_bytes).unwrap();
+        assert!(matches!(
+            saved_pdf.read_manifest_bytes(),
+            Ok(Some(manifests)) if manifests[0] == manifest_bytes
+        ));
+        assert!(saved_pdf.manifest_bytes_position().is_some());
+    }
 }
@@ -11,17 +11,26 @@
 // specific language governing permissions and limitations under
 // each license.
 
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    io::{Cursor, Seek, SeekFrom},
+    path::Path,
+};
+
+use tempfile::Builder;
 
 use crate::{
     asset_handlers::pdf::{C2paPdf, Pdf},
-    asset_io::{AssetIO, CAIRead, CAIReader, CAIWriter, ComposedManifestRef, HashObjectPositions},
+    asset_io::{
+        rename_or_copy, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter, ComposedManifestRef,
+        HashBlockObjectType, HashObjectPositions,
+    },
     Error,
     Error::{JumbfNotFound, NotImplemented, PdfReadError},
 };
 
 static SUPPORTED_TYPES: [&str; 2] = ["pdf", "application/pdf"];
-static WRITE_NOT_IMPLEMENTED: &str = "PDF write functionality will be added in a future release";
+static PASSWORD_PROTECTED: &str = "c2pa-rs does not support writing to password protected PDFs";
 
 pub struct PdfIO {}
 
@@ -66,6 +75,62 @@ impl PdfIO {
     fn read_xmp_from_pdf(&self, pdf: impl C2paPdf) -> Option<String> {
         pdf.read_xmp()
     }
+
+    fn load_writable_pdf(&self, input_stream: &mut dyn CAIRead) -> crate::Result<Pdf> {
+        input_stream.rewind()?;
+        let pdf = Pdf::from_reader(input_stream).map_err(|e| Error::InvalidAsset(e.to_string()))?;
+
+        if pdf.is_password_protected() {
+            return Err(NotImplemented(PASSWORD_PROTECTED.into()));
+        }
+
+        Ok(pdf)
+    }
+
+    fn write_manifest_bytes(
+        &self,
+        mut pdf: impl C2paPdf,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> crate::Result<()> {
+        if !pdf.has_c2pa_manifest() {
+            pdf.write_manifest_as_embedded_file(store_bytes.to_vec())
+                .map_err(|_| Error::EmbeddingError)?;
+            return self.save_incremental(pdf, output_stream);
+        }
+
+        // A manifest of the same size is patched in place so the rest of the PDF, and any
+        // hashes computed over it, stay the same.
+        match pdf.manifest_bytes_position() {
+            Some((offset, length)) if length == store_bytes.len() => {
+                input_stream.rewind()?;
+                std::io::copy(input_stream, output_stream)?;
+                output_stream.seek(SeekFrom::Start(offset as u64))?;
+                output_stream.write_all(store_bytes)?;
+                Ok(())
+            }
+            _ => {
+                pdf.replace_manifest_bytes(store_bytes.to_vec())
+                    .map_err(|_| Error::EmbeddingError)?;
+                self.save_incremental(pdf, output_stream)
+            }
+        }
+    }
+
+    fn save_incremental(
+        &self,
+        pdf: impl C2paPdf,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> crate::Result<()> {
+        let mut bytes = Vec::new();
+        pdf.save_incremental_to(&mut bytes)
+            .map_err(|_| Error::EmbeddingError)?;
+
+        output_stream.rewind()?;
+        output_stream.write_all(&bytes)?;
+        Ok(())
+    }
 }
 
 impl AssetIO for PdfIO {
@@ -84,8 +149,8 @@ impl AssetIO for PdfIO {
         self
     }
 
-    fn get_writer(&self, _asset_type: &str) -> Option<Box<dyn CAIWriter>> {
-        None
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(PdfIO::new(asset_type)))
     }
 
     fn read_cai_store(&self, asset_path: &Path) -> crate::Result<Vec<u8>> {
@@ -93,16 +158,37 @@ impl AssetIO for PdfIO {
         self.read_cai(&mut f)
     }
 
-    fn save_cai_store(&self, _asset_path: &Path, _store_bytes: &[u8]) -> crate::Result<()> {
-        Err(NotImplemented(WRITE_NOT_IMPLEMENTED.into()))
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> crate::Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
     }
 
-    fn get_object_locations(&self, _asset_path: &Path) -> crate::Result<Vec<HashObjectPositions>> {
-        Err(NotImplemented(WRITE_NOT_IMPLEMENTED.into()))
+    fn get_object_locations(&self, asset_path: &Path) -> crate::Result<Vec<HashObjectPositions>> {
+        let mut f = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut f)
     }
 
-    fn remove_cai_store(&self, _asset_path: &Path) -> crate::Result<()> {
-        Err(NotImplemented(WRITE_NOT_IMPLEMENTED.into()))
+    fn remove_cai_store(&self, asset_path: &Path) -> crate::Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
     }
 
     fn supported_types(&self) -> &[&str] {
@@ -114,6 +200,76 @@ impl AssetIO for PdfIO {
     }
 }
 
+impl CAIWriter for PdfIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> crate::Result<()> {
+        let pdf = self.load_writable_pdf(input_stream)?;
+        self.write_manifest_bytes(pdf, input_stream, output_stream, store_bytes)
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> crate::Result<Vec<HashObjectPositions>> {
+        let mut pdf = self.load_writable_pdf(input_stream)?;
+
+        // add a placeholder manifest so the position of the embedded file stream is known
+        if !pdf.has_c2pa_manifest() {
+            let mut output_stream = Cursor::new(Vec::new());
+            self.write_cai(input_stream, &mut output_stream, &[1, 2, 3, 4])?;
+
+            output_stream.rewind()?;
+            pdf = self.load_writable_pdf(&mut output_stream)?;
+        }
+
+        let (manifest_pos, manifest_len) =
+            pdf.manifest_bytes_position().ok_or(Error::EmbeddingError)?;
+
+        let file_end = pdf.source_len();
+
+        Ok(vec![
+            HashObjectPositions {
+                offset: manifest_pos,
+                length: manifest_len,
+                htype: HashBlockObjectType::Cai,
+            },
+            HashObjectPositions {
+                offset: 0,
+                length: manifest_pos,
+                htype: HashBlockObjectType::Other,
+            },
+            HashObjectPositions {
+                offset: manifest_pos + manifest_len,
+                length: file_end - (manifest_pos + manifest_len),
+                htype: HashBlockObjectType::Other,
+            },
+        ])
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> crate::Result<()> {
+        let mut pdf = self.load_writable_pdf(input_stream)?;
+
+        if !pdf.has_c2pa_manifest() {
+            // nothing to remove, just clone
+            input_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            return Ok(());
+        }
+
+        pdf.remove_manifest_bytes()
+            .map_err(|_| Error::EmbeddingError)?;
+        self.save_incremental(pdf, output_stream)
+    }
+}
+
 impl ComposedManifestRef for PdfIO {
     // Return entire CAI block as Vec<u8>
     fn compose_manifest(&self, manifest_data: &[u8], _format: &str) -> Result<Vec<u8>, Error> {
@@ -126,12 +282,12 @@ pub mod tests {
     #![allow(clippy::panic)]
     #![allow(clippy::unwrap_used)]
 
-    use std::io::Cursor;
+    use std::io::{Cursor, Seek};
 
     use crate::{
         asset_handlers,
         asset_handlers::{pdf::MockC2paPdf, pdf_io::PdfIO},
-        asset_io::{AssetIO, CAIReader},
+        asset_io::{AssetIO, CAIReader, CAIWriter, HashBlockObjectType},
     };
 
     static MANIFEST_BYTES: &[u8; 2] = &[10u8, 20u8];
@@ -238,4 +394,125 @@ pub mod tests {
         let mut pdf_stream = Cursor::new(source.to_vec());
         assert!(pdf_io.read_cai(&mut pdf_stream).is_ok());
     }
+
+    #[test]
+    fn test_write_cai_appends_incremental_update() {
+        let source = include_bytes!("../../tests/fixtures/basic.pdf");
+        let pdf_io = PdfIO::new("pdf");
+
+        let mut input_stream = Cursor::new(source.to_vec());
+        let mut output_stream = Cursor::new(Vec::new());
+        pdf_io
+            .write_cai(&mut input_stream, &mut output_stream, MANIFEST_BYTES)
+            .unwrap();
+
+        // the original bytes are preserved so existing PDF signatures remain valid
+        let output = output_stream.into_inner();
+        assert!(output.starts_with(source));
+
+        let mut output_stream = Cursor::new(output);
+        assert_eq!(
+            pdf_io.read_cai(&mut output_stream).unwrap(),
+            MANIFEST_BYTES.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_write_cai_replaces_existing_manifest() {
+        let source = include_bytes!("../../tests/fixtures/express-signed.pdf");
+        let pdf_io = PdfIO::new("pdf");
+        let new_manifest = vec![1u8; 100];
+
+        let mut input_stream = Cursor::new(source.to_vec());
+        let mut output_stream = Cursor::new(Vec::new());
+        pdf_io
+            .write_cai(&mut input_stream, &mut output_stream, &new_manifest)
+            .unwrap();
+
+        output_stream.rewind().unwrap();
+        assert_eq!(pdf_io.read_cai(&mut output_stream).unwrap(), new_manifest);
+    }
+
+    #[test]
+    fn test_write_cai_patches_same_size_manifest() {
+        let source = include_bytes!("../../tests/fixtures/basic.pdf");
+        let pdf_io = PdfIO::new("pdf");
+
+        let mut input_stream = Cursor::new(source.to_vec());
+        let mut first_stream = Cursor::new(Vec::new());
+        pdf_io
+            .write_cai(&mut input_stream, &mut first_stream, &[0u8; 50])
+            .unwrap();
+
+        first_stream.rewind().unwrap();
+        let mut second_stream = Cursor::new(Vec::new());
+        pdf_io
+            .write_cai(&mut first_stream, &mut second_stream, &[7u8; 50])
+            .unwrap();
+
+        assert_eq!(first_stream.get_ref().len(), second_stream.get_ref().len());
+
+        second_stream.rewind().unwrap();
+        assert_eq!(pdf_io.read_cai(&mut second_stream).unwrap(), vec![7u8; 50]);
+    }
+
+    #[test]
+    fn test_object_locations_cover_manifest() {
+        let source = include_bytes!("../../tests/fixtures/basic.pdf");
+        let pdf_io = PdfIO::new("pdf");
+        let manifest = vec![5u8; 64];
+
+        let mut input_stream = Cursor::new(source.to_vec());
+        let mut output_stream = Cursor::new(Vec::new());
+        pdf_io
+            .write_cai(&mut input_stream, &mut output_stream, &manifest)
+            .unwrap();
+
+        output_stream.rewind().unwrap();
+        let positions = pdf_io
+            .get_object_locations_from_stream(&mut output_stream)
+            .unwrap();
+
+        let cai = positions
+            .iter()
+            .find(|p| p.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        let output = output_stream.into_inner();
+        assert_eq!(&output[cai.offset..cai.offset + cai.length], manifest);
+
+        let total: usize = positions.iter().map(|p| p.length).sum();
+        assert_eq!(total, output.len());
+    }
+
+    #[test]
+    fn test_remove_cai_store_from_stream() {
+        let source = include_bytes!("../../tests/fixtures/express-signed.pdf");
+        let pdf_io = PdfIO::new("pdf");
+
+        let mut input_stream = Cursor::new(source.to_vec());
+        let mut output_stream = Cursor::new(Vec::new());
+        pdf_io
+            .remove_cai_store_from_stream(&mut input_stream, &mut output_stream)
+            .unwrap();
+
+        output_stream.rewind().unwrap();
+        assert!(matches!(
+            pdf_io.read_cai(&mut output_stream),
+            Err(crate::Error::JumbfNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_write_cai_password_protected_fails() {
+        let source = include_bytes!("../../tests/fixtures/basic-password.pdf");
+        let pdf_io = PdfIO::new("pdf");
+
+        let mut input_stream = Cursor::new(source.to_vec());
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            pdf_io.write_cai(&mut input_stream, &mut output_stream, MANIFEST_BYTES),
+            Err(crate::Error::NotImplemented(_))
+        ));
+    }
 }
@@ -0,0 +1,796 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Photoshop document (PSD and PSB) support.
+//!
+//! The manifest store is kept in a plug-in Image Resource Block named `C2PA` at the end
+//! of the image resources section.  XMP uses the standard XMP resource (0x0424).
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader,
+        CAIWriter, HashBlockObjectType, HashObjectPositions, RemoteRefEmbed, RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+static SUPPORTED_TYPES: [&str; 3] = ["psd", "psb", "image/vnd.adobe.photoshop"];
+
+const PSD_SIGNATURE: [u8; 4] = *b"8BPS";
+const PSD_VERSION: u16 = 1;
+const PSB_VERSION: u16 = 2;
+
+// signature(4) + version(2) + reserved(6) + channels(2) + height(4) + width(4) +
+// depth(2) + color mode(2)
+const PSD_HDR_LEN: u64 = 26;
+
+const RESOURCE_SIGNATURE: [u8; 4] = *b"8BIM";
+
+// the manifest store is kept in a plug-in resource identified by its name
+const C2PA_RESOURCE_ID: u16 = 0x0fc2;
+const C2PA_RESOURCE_NAME: &[u8] = b"C2PA";
+
+const XMP_RESOURCE_ID: u16 = 0x0424;
+
+#[derive(Clone, Debug)]
+struct ResourceBlock {
+    pub start: u64,
+    pub id: u16,
+    pub name: Vec<u8>,
+    pub data_start: u64,
+    pub data_len: u64,
+}
+
+impl ResourceBlock {
+    // resource data is padded to an even length
+    pub fn end(&self) -> u64 {
+        self.data_start + self.data_len + (self.data_len & 1)
+    }
+
+    fn is_c2pa(&self) -> bool {
+        self.id == C2PA_RESOURCE_ID && self.name == C2PA_RESOURCE_NAME
+    }
+}
+
+// section offsets, each section starts with its length field
+struct PsdSections {
+    pub color_mode_end: u64,
+    pub resources_start: u64,
+    pub resources_end: u64,
+    pub resources: Vec<ResourceBlock>,
+    pub layers_end: u64,
+}
+
+fn psd_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("PSD out of range".to_string())
+}
+
+fn get_psd_sections<R: Read + Seek + ?Sized>(f: &mut R) -> Result<PsdSections> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut signature = [0u8; 4];
+    f.read_exact(&mut signature)
+        .map_err(|_err| Error::InvalidAsset("PSD invalid".to_string()))?;
+    let version = f.read_u16::<BigEndian>().map_err(psd_err)?;
+
+    if signature != PSD_SIGNATURE || (version != PSD_VERSION && version != PSB_VERSION) {
+        return Err(Error::InvalidAsset("PSD invalid".to_string()));
+    }
+
+    let check_end = |end: u64| -> Result<u64> {
+        if end > stream_len {
+            Err(Error::InvalidAsset("PSD bad section size".to_string()))
+        } else {
+            Ok(end)
+        }
+    };
+
+    // color mode data
+    f.seek(SeekFrom::Start(PSD_HDR_LEN))?;
+    let color_mode_len = f.read_u32::<BigEndian>().map_err(psd_err)? as u64;
+    let color_mode_end = check_end(PSD_HDR_LEN + 4 + color_mode_len)?;
+
+    // image resources
+    let resources_start = color_mode_end;
+    f.seek(SeekFrom::Start(resources_start))?;
+    let resources_len = f.read_u32::<BigEndian>().map_err(psd_err)? as u64;
+    let resources_end = check_end(resources_start + 4 + resources_len)?;
+
+    let mut resources = Vec::new();
+    let mut current_pos = resources_start + 4;
+    while current_pos < resources_end {
+        f.seek(SeekFrom::Start(current_pos))?;
+
+        let mut resource_signature = [0u8; 4];
+        f.read_exact(&mut resource_signature).map_err(psd_err)?;
+        let id = f.read_u16::<BigEndian>().map_err(psd_err)?;
+
+        // Pascal string padded to an even length
+        let name_len = f.read_u8().map_err(psd_err)? as usize;
+        let mut name = vec![0u8; name_len];
+        f.read_exact(&mut name).map_err(psd_err)?;
+        if name_len & 1 == 0 {
+            f.read_u8().map_err(psd_err)?;
+        }
+
+        let data_len = f.read_u32::<BigEndian>().map_err(psd_err)? as u64;
+        let data_start = f.stream_position()?;
+
+        let block = ResourceBlock {
+            start: current_pos,
+            id,
+            name,
+            data_start,
+            data_len,
+        };
+        if block.end() > resources_end {
+            return Err(Error::InvalidAsset("PSD bad image resource".to_string()));
+        }
+
+        current_pos = block.end();
+        resources.push(block);
+    }
+
+    // layer and mask information, PSB uses a 64 bit length
+    f.seek(SeekFrom::Start(resources_end))?;
+    let layers_len = if version == PSB_VERSION {
+        f.read_u64::<BigEndian>().map_err(psd_err)? + 8
+    } else {
+        f.read_u32::<BigEndian>().map_err(psd_err)? as u64 + 4
+    };
+    let layers_end = check_end(resources_end + layers_len)?;
+
+    Ok(PsdSections {
+        color_mode_end,
+        resources_start,
+        resources_end,
+        resources,
+        layers_end,
+    })
+}
+
+fn read_resource_data<R: Read + Seek + ?Sized>(
+    f: &mut R,
+    block: &ResourceBlock,
+) -> Result<Vec<u8>> {
+    let length = usize::value_from(block.data_len)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(block.data_start))?;
+    let mut data = vec![0u8; length];
+    f.read_exact(&mut data).map_err(psd_err)?;
+
+    Ok(data)
+}
+
+fn make_resource_block(id: u16, name: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+    let name_len = u8::value_from(name.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+    let data_len = u32::value_from(data.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    let mut block = Vec::new();
+    block.extend_from_slice(&RESOURCE_SIGNATURE);
+    block.write_u16::<BigEndian>(id)?;
+    block.write_u8(name_len)?;
+    block.extend_from_slice(name);
+    if name.len() & 1 == 0 {
+        block.write_u8(0)?;
+    }
+    block.write_u32::<BigEndian>(data_len)?;
+    block.extend_from_slice(data);
+    if data.len() & 1 == 1 {
+        block.write_u8(0)?;
+    }
+
+    Ok(block)
+}
+
+// Rewrite the image resources section keeping the blocks not matched by `remove` and
+// appending `new_blocks` at the end.  The other sections are copied unchanged.
+fn rewrite_psd<P>(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    remove: P,
+    new_blocks: &[Vec<u8>],
+) -> Result<()>
+where
+    P: Fn(&ResourceBlock) -> bool,
+{
+    let sections = get_psd_sections(input_stream)?;
+
+    let kept: Vec<&ResourceBlock> = sections.resources.iter().filter(|b| !remove(b)).collect();
+
+    let resources_len = kept.iter().map(|b| b.end() - b.start).sum::<u64>()
+        + new_blocks.iter().map(|b| b.len() as u64).sum::<u64>();
+    let resources_len = u32::value_from(resources_len)
+        .map_err(|_err| Error::InvalidAsset("PSD image resources too large".to_owned()))?;
+
+    // header and color mode data
+    input_stream.rewind()?;
+    output_stream.rewind()?;
+    std::io::copy(
+        &mut input_stream.take(sections.resources_start),
+        output_stream,
+    )?;
+
+    output_stream.write_u32::<BigEndian>(resources_len)?;
+    for block in kept {
+        input_stream.seek(SeekFrom::Start(block.start))?;
+        std::io::copy(
+            &mut input_stream.take(block.end() - block.start),
+            output_stream,
+        )?;
+    }
+    for block in new_blocks {
+        output_stream.write_all(block)?;
+    }
+
+    // layer and mask information and image data
+    input_stream.seek(SeekFrom::Start(sections.resources_end))?;
+    std::io::copy(input_stream, output_stream)?;
+
+    Ok(())
+}
+
+fn get_manifest_block<R: Read + Seek + ?Sized>(f: &mut R) -> Result<ResourceBlock> {
+    let sections = get_psd_sections(f)?;
+
+    let mut c2pa_blocks = sections.resources.into_iter().filter(|b| b.is_c2pa());
+    let c2pa_block = c2pa_blocks.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_blocks.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    Ok(c2pa_block)
+}
+
+fn add_required_blocks(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let psd_io = PsdIO {};
+
+    match psd_io.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            psd_io.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct PsdIO {}
+
+impl CAIReader for PsdIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let c2pa_block = get_manifest_block(input_stream)?;
+        read_resource_data(input_stream, &c2pa_block)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, input_stream: &mut dyn CAIRead) -> Option<String> {
+        let sections = get_psd_sections(input_stream).ok()?;
+        let xmp_block = sections
+            .resources
+            .into_iter()
+            .find(|b| b.id == XMP_RESOURCE_ID)?;
+
+        let data = read_resource_data(input_stream, &xmp_block).ok()?;
+        String::from_utf8(data).ok()
+    }
+}
+
+impl CAIWriter for PsdIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let c2pa_block = make_resource_block(C2PA_RESOURCE_ID, C2PA_RESOURCE_NAME, store_bytes)?;
+        rewrite_psd(input_stream, output_stream, |b| b.is_c2pa(), &[c2pa_block])
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_blocks(input_stream, &mut output_stream)?;
+
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let c2pa_block = get_manifest_block(&mut output_stream)?;
+        let start = usize::value_from(c2pa_block.start)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let end = usize::value_from(c2pa_block.end())
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let file_end = output_stream.get_ref().len();
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of sections and resources before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_psd(input_stream, output_stream, |b| b.is_c2pa(), &[])
+    }
+}
+
+impl AssetIO for PsdIO {
+    fn new(_asset_type: &str) -> Self {
+        PsdIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(PsdIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(PsdIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for PsdIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let c2pa_block = get_manifest_block(&mut asset)?;
+
+        if store_bytes.len() as u64 == c2pa_block.data_len {
+            asset.seek(SeekFrom::Start(c2pa_block.data_start))?;
+            asset.write_all(store_bytes)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+impl RemoteRefEmbed for PsdIO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.embed_reference_to_stream(&mut input_stream, &mut temp_file, embed_ref)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let xmp = match self.read_xmp(input_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+                let new_xmp = add_provenance(&xmp, &manifest_uri)?;
+
+                let mut new_blocks = vec![make_resource_block(
+                    XMP_RESOURCE_ID,
+                    &[],
+                    new_xmp.as_bytes(),
+                )?];
+
+                // keep the manifest store as the last resource
+                match self.read_cai(input_stream) {
+                    Ok(manifest) => new_blocks.push(make_resource_block(
+                        C2PA_RESOURCE_ID,
+                        C2PA_RESOURCE_NAME,
+                        &manifest,
+                    )?),
+                    Err(Error::JumbfNotFound) => (),
+                    Err(e) => return Err(e),
+                }
+
+                rewrite_psd(
+                    input_stream,
+                    output_stream,
+                    |b| b.id == XMP_RESOURCE_ID || b.is_c2pa(),
+                    &new_blocks,
+                )
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+impl AssetBoxHash for PsdIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        let sections = get_psd_sections(input_stream)?;
+        let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+        let make_box_map = |name: &str, start: u64, end: u64| -> Result<BoxMap> {
+            Ok(BoxMap {
+                names: vec![name.to_string()],
+                alg: None,
+                hash: ByteBuf::from(Vec::new()),
+                pad: ByteBuf::from(Vec::new()),
+                range_start: usize::value_from(start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                range_len: usize::value_from(end - start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+            })
+        };
+
+        let mut box_maps = Vec::new();
+
+        box_maps.push(make_box_map("8BPS", 0, PSD_HDR_LEN)?);
+        box_maps.push(make_box_map(
+            "ColorModeData",
+            PSD_HDR_LEN,
+            sections.color_mode_end,
+        )?);
+
+        // the image resources are split around the manifest store
+        let mut current_pos = sections.resources_start;
+        for block in sections.resources.iter().filter(|b| b.is_c2pa()) {
+            box_maps.push(make_box_map("ImageResources", current_pos, block.start)?);
+            box_maps.push(make_box_map(C2PA_BOXHASH, block.start, block.end())?);
+            current_pos = block.end();
+        }
+        if current_pos < sections.resources_end {
+            box_maps.push(make_box_map(
+                "ImageResources",
+                current_pos,
+                sections.resources_end,
+            )?);
+        }
+
+        box_maps.push(make_box_map(
+            "LayerAndMaskInfo",
+            sections.resources_end,
+            sections.layers_end,
+        )?);
+        if sections.layers_end < stream_len {
+            box_maps.push(make_box_map("ImageData", sections.layers_end, stream_len)?);
+        }
+
+        Ok(box_maps)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::{
+        test::{fixture_path, temp_dir_path},
+        xmp_inmemory_utils::extract_provenance,
+    };
+
+    fn resource_ids(data: &[u8]) -> Vec<u16> {
+        let sections = get_psd_sections(&mut Cursor::new(data)).unwrap();
+        sections.resources.iter().map(|b| b.id).collect()
+    }
+
+    #[test]
+    fn test_psd_parse() {
+        let source = include_bytes!("../../tests/fixtures/Purple Square.psd");
+
+        let sections = get_psd_sections(&mut Cursor::new(source.to_vec())).unwrap();
+        assert_eq!(sections.resources.len(), 23);
+        assert_eq!(sections.resources_end, 9022);
+        assert_eq!(sections.layers_end, 9034);
+
+        let psd_io = PsdIO::new("psd");
+        assert!(psd_io
+            .read_xmp(&mut Cursor::new(source.to_vec()))
+            .unwrap()
+            .contains("x:xmpmeta"));
+        assert!(matches!(
+            psd_io.read_cai(&mut Cursor::new(source.to_vec())),
+            Err(Error::JumbfNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_write_psd() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("Purple Square.psd");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "Purple Square-psd.psd");
+        std::fs::copy(&source, &output).unwrap();
+
+        let psd_io = PsdIO::new("psd");
+        psd_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(psd_io.read_cai_store(&output).unwrap(), more_data);
+
+        // the layer and image data are unchanged
+        let source_data = std::fs::read(&source).unwrap();
+        let output_data = std::fs::read(&output).unwrap();
+        let sections = get_psd_sections(&mut Cursor::new(&output_data)).unwrap();
+        assert_eq!(
+            output_data[sections.resources_end as usize..],
+            source_data[9022..]
+        );
+        assert_eq!(resource_ids(&output_data).last(), Some(&C2PA_RESOURCE_ID));
+    }
+
+    #[test]
+    fn test_write_psb_stream() {
+        // convert the fixture to a PSB by widening the layer section length
+        let source = include_bytes!("../../tests/fixtures/Purple Square.psd");
+        let mut psb = source[..9022].to_vec();
+        psb[5] = PSB_VERSION as u8;
+        psb.extend_from_slice(&[0, 0, 0, 0]);
+        psb.extend_from_slice(&source[9022..]);
+
+        let psd_io = PsdIO::new("psb");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        psd_io
+            .write_cai(
+                &mut Cursor::new(psb.clone()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert_eq!(psd_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+
+        let sections = get_psd_sections(&mut output_stream).unwrap();
+        assert_eq!(
+            sections.layers_end - sections.resources_end,
+            9034 - 9022 + 4
+        );
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let psd_io = PsdIO::new("psd");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            psd_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_patch_write_psd() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("Purple Square.psd");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "Purple Square-psd.psd");
+        std::fs::copy(source, &output).unwrap();
+
+        let psd_io = PsdIO::new("psd");
+        psd_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        psd_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(psd_io.read_cai_store(&output).unwrap(), new_data);
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("Purple Square.psd");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "Purple Square-psd.psd");
+        std::fs::copy(&source, &output).unwrap();
+
+        let psd_io = PsdIO::new("psd");
+        psd_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        psd_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match psd_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let source = include_bytes!("../../tests/fixtures/Purple Square.psd");
+        let psd_io = PsdIO::new("psd");
+
+        let mut signed_stream = Cursor::new(Vec::new());
+        psd_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut signed_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let mut output_stream = Cursor::new(Vec::new());
+        psd_io
+            .embed_reference_to_stream(
+                &mut signed_stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let xmp = psd_io.read_xmp(&mut output_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/manifest.c2pa"
+        );
+
+        // the existing XMP is updated and the manifest store stays last
+        let ids = resource_ids(output_stream.get_ref());
+        assert_eq!(ids.iter().filter(|id| **id == XMP_RESOURCE_ID).count(), 1);
+        assert_eq!(ids[ids.len() - 2..], [XMP_RESOURCE_ID, C2PA_RESOURCE_ID]);
+        assert_eq!(psd_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/Purple Square.psd");
+        let psd_io = PsdIO::new("psd");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        psd_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let box_map = psd_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "8BPS",
+                "ColorModeData",
+                "ImageResources",
+                C2PA_BOXHASH,
+                "LayerAndMaskInfo",
+                "ImageData"
+            ]
+        );
+
+        // box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+}
@@ -0,0 +1,826 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! OpenType and TrueType (SFNT) font support.
+//!
+//! The manifest store is kept in a `C2PA` table placed after all the other tables.
+//! The table ends with a balance word that makes its checksum zero, so the table
+//! directory and `head.checkSumAdjustment` do not change when a manifest store is
+//! replaced by one of the same size.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
+use serde_bytes::ByteBuf;
+use tempfile::Builder;
+
+use crate::{
+    assertions::{BoxMap, C2PA_BOXHASH},
+    asset_io::{
+        rename_or_copy, AssetBoxHash, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader,
+        CAIWriter, HashBlockObjectType, HashObjectPositions,
+    },
+    error::{Error, Result},
+};
+
+static SUPPORTED_TYPES: [&str; 5] = ["otf", "ttf", "font/otf", "font/ttf", "font/sfnt"];
+
+const TRUETYPE_VERSION: [u8; 4] = [0x00, 0x01, 0x00, 0x00];
+const OPENTYPE_VERSION: [u8; 4] = *b"OTTO";
+const APPLE_TRUETYPE_VERSION: [u8; 4] = *b"true";
+const COLLECTION_TAG: [u8; 4] = *b"ttcf";
+const WOFF_SIGNATURE: [u8; 4] = *b"wOFF";
+const WOFF2_SIGNATURE: [u8; 4] = *b"wOF2";
+
+const C2PA_TABLE_TAG: [u8; 4] = *b"C2PA";
+const HEAD_TABLE_TAG: [u8; 4] = *b"head";
+
+// sfntVersion(4) + numTables(2) + searchRange(2) + entrySelector(2) + rangeShift(2)
+const SFNT_HDR_LEN: u64 = 12;
+
+// tag(4) + checksum(4) + offset(4) + length(4)
+const TABLE_RECORD_LEN: u64 = 16;
+
+// offset of checkSumAdjustment in the head table
+const CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+const CHECKSUM_MAGIC: u32 = 0xb1b0afba;
+
+// C2PA table: majorVersion(2) + minorVersion(2) + activeManifestUriOffset(4) +
+// activeManifestUriLength(2) + reserved(2) + manifestStoreOffset(4) + manifestStoreLength(4)
+const C2PA_TABLE_HDR_LEN: usize = 20;
+const C2PA_TABLE_MAJOR_VERSION: u16 = 0;
+const C2PA_TABLE_MINOR_VERSION: u16 = 1;
+
+#[derive(Clone, Debug)]
+struct TableRecord {
+    pub tag: [u8; 4],
+    pub checksum: u32,
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl TableRecord {
+    // tables are padded to a 4 byte boundary
+    pub fn end(&self) -> u64 {
+        self.offset + padded_len(self.length)
+    }
+}
+
+struct SfntFont {
+    sfnt_version: [u8; 4],
+    tables: Vec<TableRecord>,
+}
+
+fn sfnt_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("SFNT out of range".to_string())
+}
+
+fn padded_len(len: u64) -> u64 {
+    (len + 3) & !3
+}
+
+// sum of the big endian 32 bit words in data, the last word is zero padded
+fn calc_checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+// parse the offset table and table directory
+fn get_sfnt_font<R: Read + Seek + ?Sized>(f: &mut R) -> Result<SfntFont> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    let mut sfnt_version = [0u8; 4];
+    f.read_exact(&mut sfnt_version)
+        .map_err(|_err| Error::InvalidAsset("SFNT invalid".to_string()))?;
+
+    match sfnt_version {
+        TRUETYPE_VERSION | OPENTYPE_VERSION | APPLE_TRUETYPE_VERSION => (),
+        COLLECTION_TAG => {
+            return Err(Error::InvalidAsset(
+                "SFNT font collections are not supported".to_string(),
+            ))
+        }
+        WOFF_SIGNATURE | WOFF2_SIGNATURE => {
+            return Err(Error::InvalidAsset(
+                "WOFF fonts are not supported".to_string(),
+            ))
+        }
+        _ => return Err(Error::InvalidAsset("SFNT invalid".to_string())),
+    }
+
+    let num_tables = f.read_u16::<BigEndian>().map_err(sfnt_err)?;
+    f.seek(SeekFrom::Start(SFNT_HDR_LEN))?;
+
+    let mut tables = Vec::with_capacity(num_tables as usize);
+    for _ in 0..num_tables {
+        let mut tag = [0u8; 4];
+        f.read_exact(&mut tag).map_err(sfnt_err)?;
+        let checksum = f.read_u32::<BigEndian>().map_err(sfnt_err)?;
+        let offset = f.read_u32::<BigEndian>().map_err(sfnt_err)? as u64;
+        let length = f.read_u32::<BigEndian>().map_err(sfnt_err)? as u64;
+
+        if offset + length > stream_len {
+            return Err(Error::InvalidAsset("SFNT bad table size".to_string()));
+        }
+
+        tables.push(TableRecord {
+            tag,
+            checksum,
+            offset,
+            length,
+        });
+    }
+
+    Ok(SfntFont {
+        sfnt_version,
+        tables,
+    })
+}
+
+fn read_table_data<R: Read + Seek + ?Sized>(f: &mut R, table: &TableRecord) -> Result<Vec<u8>> {
+    let length = usize::value_from(table.length)
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    f.seek(SeekFrom::Start(table.offset))?;
+    let mut data = vec![0u8; length];
+    f.read_exact(&mut data).map_err(sfnt_err)?;
+
+    Ok(data)
+}
+
+// the head table checksum is calculated with checkSumAdjustment set to zero
+fn clear_checksum_adjustment(tag: &[u8; 4], data: &mut [u8]) {
+    if tag == &HEAD_TABLE_TAG && data.len() >= CHECKSUM_ADJUSTMENT_OFFSET + 4 {
+        data[CHECKSUM_ADJUSTMENT_OFFSET..CHECKSUM_ADJUSTMENT_OFFSET + 4].fill(0);
+    }
+}
+
+fn make_c2pa_table(store_bytes: &[u8]) -> Result<Vec<u8>> {
+    let store_len = u32::value_from(store_bytes.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    let mut data = Vec::with_capacity(C2PA_TABLE_HDR_LEN + store_bytes.len() + 8);
+    data.write_u16::<BigEndian>(C2PA_TABLE_MAJOR_VERSION)?;
+    data.write_u16::<BigEndian>(C2PA_TABLE_MINOR_VERSION)?;
+    data.write_u32::<BigEndian>(0)?; // no active manifest URI
+    data.write_u16::<BigEndian>(0)?;
+    data.write_u16::<BigEndian>(0)?; // reserved
+    data.write_u32::<BigEndian>(C2PA_TABLE_HDR_LEN as u32)?;
+    data.write_u32::<BigEndian>(store_len)?;
+    data.extend_from_slice(store_bytes);
+
+    // word align then add the balance word that zeroes the table checksum
+    data.resize(padded_len(data.len() as u64) as usize, 0);
+    let balance = 0u32.wrapping_sub(calc_checksum(&data));
+    data.write_u32::<BigEndian>(balance)?;
+
+    Ok(data)
+}
+
+fn parse_c2pa_table(data: &[u8]) -> Result<Vec<u8>> {
+    let mut reader = Cursor::new(data);
+
+    let major_version = reader.read_u16::<BigEndian>().map_err(sfnt_err)?;
+    if major_version != C2PA_TABLE_MAJOR_VERSION {
+        return Err(Error::InvalidAsset(
+            "SFNT unsupported C2PA table version".to_string(),
+        ));
+    }
+
+    reader.seek(SeekFrom::Start(12))?;
+    let store_offset = reader.read_u32::<BigEndian>().map_err(sfnt_err)? as usize;
+    let store_len = reader.read_u32::<BigEndian>().map_err(sfnt_err)? as usize;
+
+    // the table may only carry an active manifest URI
+    if store_len == 0 {
+        return Err(Error::JumbfNotFound);
+    }
+
+    data.get(store_offset..store_offset + store_len)
+        .map(|store| store.to_vec())
+        .ok_or_else(|| Error::InvalidAsset("SFNT bad C2PA table".to_string()))
+}
+
+// Rewrite the font without its C2PA table, appending `c2pa_table` after the other tables
+// when present.  The table directory, table checksums and head.checkSumAdjustment are
+// recalculated.
+fn rewrite_sfnt(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    c2pa_table: Option<&[u8]>,
+) -> Result<()> {
+    let font = get_sfnt_font(input_stream)?;
+
+    // keep the original table order
+    let mut kept: Vec<TableRecord> = font
+        .tables
+        .into_iter()
+        .filter(|t| t.tag != C2PA_TABLE_TAG)
+        .collect();
+    kept.sort_by_key(|t| t.offset);
+
+    let num_tables = kept.len() + c2pa_table.map_or(0, |_| 1);
+    let num_tables = u16::value_from(num_tables)
+        .map_err(|_err| Error::InvalidAsset("SFNT too many tables".to_owned()))?;
+
+    // lay out the tables, tables that share data in the source keep sharing it
+    let mut new_offsets: HashMap<(u64, u64), u64> = HashMap::new();
+    let mut records = Vec::new();
+    let mut data_checksum = 0u32;
+    let mut current_pos = SFNT_HDR_LEN + TABLE_RECORD_LEN * num_tables as u64;
+    for table in &kept {
+        let mut data = read_table_data(input_stream, table)?;
+        clear_checksum_adjustment(&table.tag, &mut data);
+        let checksum = calc_checksum(&data);
+
+        let offset = match new_offsets.get(&(table.offset, table.length)) {
+            Some(offset) => *offset,
+            None => {
+                let offset = current_pos;
+                new_offsets.insert((table.offset, table.length), offset);
+                data_checksum = data_checksum.wrapping_add(checksum);
+                current_pos += padded_len(table.length);
+                offset
+            }
+        };
+
+        records.push(TableRecord {
+            tag: table.tag,
+            checksum,
+            offset,
+            length: table.length,
+        });
+    }
+
+    if let Some(c2pa_data) = c2pa_table {
+        let checksum = calc_checksum(c2pa_data);
+        data_checksum = data_checksum.wrapping_add(checksum);
+        records.push(TableRecord {
+            tag: C2PA_TABLE_TAG,
+            checksum,
+            offset: current_pos,
+            length: c2pa_data.len() as u64,
+        });
+    }
+
+    // the table directory is sorted by tag
+    records.sort_by_key(|r| r.tag);
+
+    // binary search parameters for the table directory
+    let entry_selector = if num_tables > 0 {
+        15 - num_tables.leading_zeros()
+    } else {
+        0
+    };
+    let search_range = (1u32 << entry_selector) * TABLE_RECORD_LEN as u32;
+    let range_shift = (num_tables as u32 * TABLE_RECORD_LEN as u32).saturating_sub(search_range);
+
+    let mut header = Vec::new();
+    header.write_all(&font.sfnt_version)?;
+    header.write_u16::<BigEndian>(num_tables)?;
+    header.write_u16::<BigEndian>(search_range as u16)?;
+    header.write_u16::<BigEndian>(entry_selector as u16)?;
+    header.write_u16::<BigEndian>(range_shift as u16)?;
+    for record in &records {
+        header.write_all(&record.tag)?;
+        header.write_u32::<BigEndian>(record.checksum)?;
+        header.write_u32::<BigEndian>(
+            u32::value_from(record.offset)
+                .map_err(|_err| Error::InvalidAsset("SFNT too large".to_owned()))?,
+        )?;
+        header.write_u32::<BigEndian>(
+            u32::value_from(record.length)
+                .map_err(|_err| Error::InvalidAsset("SFNT too large".to_owned()))?,
+        )?;
+    }
+
+    let checksum_adjustment =
+        CHECKSUM_MAGIC.wrapping_sub(calc_checksum(&header).wrapping_add(data_checksum));
+
+    output_stream.rewind()?;
+    output_stream.write_all(&header)?;
+
+    let mut written = Vec::new();
+    for table in &kept {
+        if written.contains(&(table.offset, table.length)) {
+            continue;
+        }
+        written.push((table.offset, table.length));
+
+        let mut data = read_table_data(input_stream, table)?;
+        if table.tag == HEAD_TABLE_TAG && data.len() >= CHECKSUM_ADJUSTMENT_OFFSET + 4 {
+            data[CHECKSUM_ADJUSTMENT_OFFSET..CHECKSUM_ADJUSTMENT_OFFSET + 4]
+                .copy_from_slice(&checksum_adjustment.to_be_bytes());
+        }
+        data.resize(padded_len(table.length) as usize, 0);
+        output_stream.write_all(&data)?;
+    }
+
+    if let Some(c2pa_data) = c2pa_table {
+        output_stream.write_all(c2pa_data)?;
+    }
+
+    Ok(())
+}
+
+fn get_manifest_table<R: Read + Seek + ?Sized>(f: &mut R) -> Result<TableRecord> {
+    let font = get_sfnt_font(f)?;
+
+    let mut c2pa_tables = font.tables.into_iter().filter(|t| t.tag == C2PA_TABLE_TAG);
+    let c2pa_table = c2pa_tables.next().ok_or(Error::JumbfNotFound)?;
+    if c2pa_tables.next().is_some() {
+        return Err(Error::TooManyManifestStores);
+    }
+
+    Ok(c2pa_table)
+}
+
+fn add_required_tables(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let sfnt_io = SfntIO {};
+
+    match sfnt_io.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            sfnt_io.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct SfntIO {}
+
+impl CAIReader for SfntIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let c2pa_table = get_manifest_table(input_stream)?;
+        let data = read_table_data(input_stream, &c2pa_table)?;
+        parse_c2pa_table(&data)
+    }
+
+    // XMP is not supported in fonts
+    fn read_xmp(&self, _input_stream: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for SfntIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let c2pa_table = make_c2pa_table(store_bytes)?;
+        rewrite_sfnt(input_stream, output_stream, Some(&c2pa_table))
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_tables(input_stream, &mut output_stream)?;
+
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let c2pa_table = get_manifest_table(&mut output_stream)?;
+        let start = usize::value_from(c2pa_table.offset)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let end = usize::value_from(c2pa_table.end())
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?;
+        let file_end = output_stream.get_ref().len();
+
+        positions.push(HashObjectPositions {
+            offset: start,
+            length: end - start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of tables before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: end,
+            length: file_end - end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_sfnt(input_stream, output_stream, None)
+    }
+}
+
+impl AssetIO for SfntIO {
+    fn new(_asset_type: &str) -> Self {
+        SfntIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(SfntIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(SfntIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for SfntIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let c2pa_table = get_manifest_table(&mut asset)?;
+        let new_table = make_c2pa_table(store_bytes)?;
+
+        // the table checksum is always zero so the directory and head are unchanged
+        if new_table.len() as u64 == c2pa_table.length {
+            asset.seek(SeekFrom::Start(c2pa_table.offset))?;
+            asset.write_all(&new_table)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+impl AssetBoxHash for SfntIO {
+    fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>> {
+        let font = get_sfnt_font(input_stream)?;
+        let stream_len = input_stream.seek(SeekFrom::End(0))?;
+
+        let make_box_map = |name: String, start: u64, end: u64| -> Result<BoxMap> {
+            Ok(BoxMap {
+                names: vec![name],
+                alg: None,
+                hash: ByteBuf::from(Vec::new()),
+                pad: ByteBuf::from(Vec::new()),
+                range_start: usize::value_from(start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+                range_len: usize::value_from(end - start)
+                    .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?,
+            })
+        };
+
+        let directory_end = SFNT_HDR_LEN + TABLE_RECORD_LEN * font.tables.len() as u64;
+
+        // tables in file order, shared table data is only listed once
+        let mut tables = font.tables;
+        tables.sort_by_key(|t| t.offset);
+        tables.dedup_by_key(|t| t.offset);
+
+        let first_table = tables.first().map_or(stream_len, |t| t.offset);
+
+        let mut box_maps = Vec::new();
+
+        // offset table and table directory
+        box_maps.push(make_box_map(
+            "SFNT".to_string(),
+            0,
+            std::cmp::max(directory_end, first_table),
+        )?);
+
+        // each table runs up to the start of the next one to include padding
+        for (index, table) in tables.iter().enumerate() {
+            let name = if table.tag == C2PA_TABLE_TAG {
+                C2PA_BOXHASH.to_string()
+            } else {
+                String::from_utf8_lossy(&table.tag).to_string()
+            };
+
+            let end = tables.get(index + 1).map_or(stream_len, |t| t.offset);
+            box_maps.push(make_box_map(name, table.offset, end)?);
+        }
+
+        Ok(box_maps)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    // check the table checksums and checkSumAdjustment
+    fn verify_checksums(data: &[u8]) {
+        let font = get_sfnt_font(&mut Cursor::new(data)).unwrap();
+
+        for table in &font.tables {
+            let mut table_data =
+                data[table.offset as usize..(table.offset + table.length) as usize].to_vec();
+            clear_checksum_adjustment(&table.tag, &mut table_data);
+            assert_eq!(calc_checksum(&table_data), table.checksum);
+        }
+
+        assert_eq!(calc_checksum(data), CHECKSUM_MAGIC);
+    }
+
+    fn table_tags(data: &[u8]) -> Vec<[u8; 4]> {
+        let font = get_sfnt_font(&mut Cursor::new(data)).unwrap();
+        font.tables.iter().map(|t| t.tag).collect()
+    }
+
+    #[test]
+    fn test_sfnt_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.ttf");
+
+        verify_checksums(source);
+        assert_eq!(table_tags(source).len(), 9);
+
+        let sfnt_io = SfntIO::new("ttf");
+        assert!(matches!(
+            sfnt_io.read_cai(&mut Cursor::new(source.to_vec())),
+            Err(Error::JumbfNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_write_ttf() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("sample1.ttf");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-ttf.ttf");
+        std::fs::copy(source, &output).unwrap();
+
+        let sfnt_io = SfntIO::new("ttf");
+        sfnt_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(sfnt_io.read_cai_store(&output).unwrap(), more_data);
+
+        let data = std::fs::read(&output).unwrap();
+        verify_checksums(&data);
+
+        // directory stays sorted by tag
+        let tags = table_tags(&data);
+        let mut sorted = tags.clone();
+        sorted.sort();
+        assert_eq!(tags, sorted);
+        assert!(tags.contains(&C2PA_TABLE_TAG));
+
+        // the C2PA table is the last table in the file
+        let font = get_sfnt_font(&mut Cursor::new(&data)).unwrap();
+        let c2pa_table = get_manifest_table(&mut Cursor::new(&data)).unwrap();
+        assert!(font.tables.iter().all(|t| t.offset <= c2pa_table.offset));
+        assert_eq!(c2pa_table.checksum, 0);
+    }
+
+    #[test]
+    fn test_write_otf_stream() {
+        // an OpenType version tag is handled the same way
+        let mut source = include_bytes!("../../tests/fixtures/sample1.ttf").to_vec();
+        source[0..4].copy_from_slice(&OPENTYPE_VERSION);
+
+        let sfnt_io = SfntIO::new("otf");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        sfnt_io
+            .write_cai(&mut Cursor::new(source), &mut output_stream, &[1, 2, 3])
+            .unwrap();
+        assert_eq!(sfnt_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+
+        let data = output_stream.into_inner();
+        assert_eq!(&data[0..4], &OPENTYPE_VERSION);
+        verify_checksums(&data);
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let sfnt_io = SfntIO::new("ttf");
+
+        for source in [
+            &include_bytes!("../../tests/fixtures/C.jpg")[..],
+            &b"wOFF\x00\x01\x00\x00"[..],
+        ] {
+            let mut output_stream = Cursor::new(Vec::new());
+            assert!(matches!(
+                sfnt_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+                Err(Error::InvalidAsset(_),)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_get_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.ttf");
+        let sfnt_io = SfntIO::new("ttf");
+
+        let positions = sfnt_io
+            .get_object_locations_from_stream(&mut Cursor::new(source.to_vec()))
+            .unwrap();
+        let cai = positions
+            .iter()
+            .find(|p| p.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        // a store of the same size only changes the C2PA table
+        let mut placeholder = Cursor::new(Vec::new());
+        sfnt_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut placeholder,
+                &[1, 2, 3, 4],
+            )
+            .unwrap();
+        let mut output_stream = Cursor::new(Vec::new());
+        sfnt_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[9, 8, 7, 6],
+            )
+            .unwrap();
+
+        let placeholder = placeholder.into_inner();
+        let output = output_stream.into_inner();
+        assert_eq!(placeholder.len(), output.len());
+        assert_eq!(cai.offset + cai.length, output.len());
+        assert_eq!(placeholder[..cai.offset], output[..cai.offset]);
+        assert_ne!(placeholder[cai.offset..], output[cai.offset..]);
+    }
+
+    #[test]
+    fn test_patch_write_ttf() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("sample1.ttf");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-ttf.ttf");
+        std::fs::copy(source, &output).unwrap();
+
+        let sfnt_io = SfntIO::new("ttf");
+        sfnt_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        sfnt_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(sfnt_io.read_cai_store(&output).unwrap(), new_data);
+
+        verify_checksums(&std::fs::read(&output).unwrap());
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.ttf");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-ttf.ttf");
+        std::fs::copy(&source, &output).unwrap();
+
+        let sfnt_io = SfntIO::new("ttf");
+        sfnt_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        sfnt_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match sfnt_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_box_map() {
+        let source = include_bytes!("../../tests/fixtures/sample1.ttf");
+        let sfnt_io = SfntIO::new("ttf");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        sfnt_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+
+        let box_map = sfnt_io.get_box_map(&mut output_stream).unwrap();
+        let names: Vec<&str> = box_map.iter().map(|b| b.names[0].as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "SFNT",
+                "head",
+                "hhea",
+                "maxp",
+                "hmtx",
+                "cmap",
+                "loca",
+                "glyf",
+                "name",
+                "post",
+                C2PA_BOXHASH
+            ]
+        );
+
+        // box map covers the whole file
+        let total: usize = box_map.iter().map(|b| b.range_len).sum();
+        assert_eq!(total, output_stream.get_ref().len());
+    }
+}
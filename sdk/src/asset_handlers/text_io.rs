@@ -0,0 +1,575 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Unstructured text support.
+//!
+//! The manifest store is wrapped in a `C2PATXT` text manifest wrapper and appended
+//! to the end of the text.  Plain text carries the wrapper as a run of Unicode
+//! variation selectors introduced by U+FEFF, so it is invisible when rendered.
+//! Markdown and HTML carry the wrapper base64 encoded inside an HTML comment.
+
+use std::{fs::File, io::Cursor, path::Path};
+
+use conv::ValueFrom;
+use tempfile::Builder;
+
+use crate::{
+    asset_io::{
+        rename_or_copy, AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter, HashBlockObjectType,
+        HashObjectPositions,
+    },
+    error::{Error, Result},
+    utils::base64,
+};
+
+static SUPPORTED_TYPES: [&str; 8] = [
+    "txt",
+    "md",
+    "markdown",
+    "html",
+    "htm",
+    "text/plain",
+    "text/markdown",
+    "text/html",
+];
+
+// magic(8) + version(1) + manifest length(4)
+const WRAPPER_MAGIC: &[u8; 8] = b"C2PATXT\0";
+const WRAPPER_VERSION: u8 = 1;
+const WRAPPER_HDR_LEN: usize = 13;
+
+// introduces a run of variation selectors
+const ZWNBSP: char = '\u{feff}';
+
+// bytes 0..16 map to VS1..VS16, bytes 16..256 map to VS17..VS256
+const VS_BASE: u32 = 0xfe00;
+const VS_SUPPLEMENT_BASE: u32 = 0xe0100;
+
+const COMMENT_START: &str = "<!-- c2pa-manifest:";
+const COMMENT_END: &str = "-->";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TextEncoding {
+    VariationSelectors,
+    HtmlComment,
+}
+
+// byte range of a manifest block within the text
+#[derive(Clone, Debug)]
+struct TextBlock {
+    pub start: usize,
+    pub end: usize,
+    pub manifest: Vec<u8>,
+}
+
+fn make_wrapper(store_bytes: &[u8]) -> Result<Vec<u8>> {
+    let len = u32::value_from(store_bytes.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    let mut wrapper = Vec::with_capacity(WRAPPER_HDR_LEN + store_bytes.len());
+    wrapper.extend_from_slice(WRAPPER_MAGIC);
+    wrapper.push(WRAPPER_VERSION);
+    wrapper.extend_from_slice(&len.to_be_bytes());
+    wrapper.extend_from_slice(store_bytes);
+
+    Ok(wrapper)
+}
+
+// Returns the manifest from a wrapper, any bytes following the manifest are ignored.
+fn parse_wrapper(wrapper: &[u8]) -> Option<Vec<u8>> {
+    if wrapper.len() < WRAPPER_HDR_LEN
+        || &wrapper[0..8] != WRAPPER_MAGIC
+        || wrapper[8] != WRAPPER_VERSION
+    {
+        return None;
+    }
+
+    let len = u32::from_be_bytes(wrapper[9..13].try_into().ok()?) as usize;
+    wrapper
+        .get(WRAPPER_HDR_LEN..WRAPPER_HDR_LEN + len)
+        .map(|m| m.to_vec())
+}
+
+fn byte_to_vs(b: u8) -> char {
+    let code = match b {
+        0..=15 => VS_BASE + b as u32,
+        _ => VS_SUPPLEMENT_BASE + (b - 16) as u32,
+    };
+    char::from_u32(code).unwrap_or(ZWNBSP)
+}
+
+fn vs_to_byte(c: char) -> Option<u8> {
+    let code = c as u32;
+    match code {
+        0xfe00..=0xfe0f => Some((code - VS_BASE) as u8),
+        0xe0100..=0xe01ef => Some((code - VS_SUPPLEMENT_BASE + 16) as u8),
+        _ => None,
+    }
+}
+
+// The UTF-8 length of a variation selector depends on the byte it encodes, so the
+// run is padded past the end of the wrapper to a length that only depends on the
+// size of the store.  This lets a placeholder be replaced by the final store in place.
+fn encode_variation_selectors(wrapper: &[u8]) -> String {
+    let mut encoded = String::new();
+    encoded.push(ZWNBSP);
+    for b in wrapper {
+        encoded.push(byte_to_vs(*b));
+    }
+
+    // VS1..VS16 are 3 bytes and VS17..VS256 are 4 bytes in UTF-8, at least 8 bytes
+    // of padding are needed so any remainder can be made from those
+    let target_len = ZWNBSP.len_utf8() + 4 * wrapper.len() + 8;
+    let padding = target_len - encoded.len();
+    let four_byte_count = padding % 3;
+    let three_byte_count = (padding - 4 * four_byte_count) / 3;
+
+    for _ in 0..three_byte_count {
+        encoded.push(byte_to_vs(0));
+    }
+    for _ in 0..four_byte_count {
+        encoded.push(byte_to_vs(16));
+    }
+
+    encoded
+}
+
+fn encode_html_comment(wrapper: &[u8]) -> String {
+    format!("{COMMENT_START}{}{COMMENT_END}", base64::encode(wrapper))
+}
+
+// find all the manifest blocks in the text
+fn find_blocks(text: &str) -> Vec<TextBlock> {
+    let mut blocks = Vec::new();
+
+    // variation selector runs
+    for (start, _) in text.match_indices(ZWNBSP) {
+        let run_start = start + ZWNBSP.len_utf8();
+        let mut end = run_start;
+        let mut wrapper = Vec::new();
+        for c in text[run_start..].chars() {
+            match vs_to_byte(c) {
+                Some(b) => {
+                    wrapper.push(b);
+                    end += c.len_utf8();
+                }
+                None => break,
+            }
+        }
+
+        if let Some(manifest) = parse_wrapper(&wrapper) {
+            blocks.push(TextBlock {
+                start,
+                end,
+                manifest,
+            });
+        }
+    }
+
+    // HTML comments
+    for (start, _) in text.match_indices(COMMENT_START) {
+        let data_start = start + COMMENT_START.len();
+        let Some(data_len) = text[data_start..].find(COMMENT_END) else {
+            continue;
+        };
+
+        let manifest = base64::decode(text[data_start..data_start + data_len].trim())
+            .ok()
+            .and_then(|wrapper| parse_wrapper(&wrapper));
+        if let Some(manifest) = manifest {
+            blocks.push(TextBlock {
+                start,
+                end: data_start + data_len + COMMENT_END.len(),
+                manifest,
+            });
+        }
+    }
+
+    blocks
+}
+
+fn read_text(input_stream: &mut dyn CAIRead) -> Result<String> {
+    input_stream.rewind()?;
+    let mut data = Vec::new();
+    input_stream.read_to_end(&mut data)?;
+
+    String::from_utf8(data).map_err(|_err| Error::InvalidAsset("text is not UTF-8".to_string()))
+}
+
+fn get_manifest_block(text: &str) -> Result<TextBlock> {
+    let mut blocks = find_blocks(text);
+    match blocks.len() {
+        0 => Err(Error::JumbfNotFound),
+        1 => Ok(blocks.remove(0)),
+        _ => Err(Error::TooManyManifestStores),
+    }
+}
+
+// text with all manifest blocks removed
+fn strip_blocks(text: &str) -> String {
+    let mut blocks = find_blocks(text);
+    blocks.sort_by_key(|b| b.start);
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut pos = 0;
+    for block in blocks {
+        if block.start >= pos {
+            stripped.push_str(&text[pos..block.start]);
+            pos = block.end;
+        }
+    }
+    stripped.push_str(&text[pos..]);
+
+    stripped
+}
+
+fn add_required_block(
+    text_io: &TextIO,
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    match text_io.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            text_io.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct TextIO {
+    encoding: TextEncoding,
+}
+
+impl CAIReader for TextIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let text = read_text(input_stream)?;
+        Ok(get_manifest_block(&text)?.manifest)
+    }
+
+    // XMP is not supported in text
+    fn read_xmp(&self, _input_stream: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for TextIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let text = read_text(input_stream)?;
+
+        let wrapper = make_wrapper(store_bytes)?;
+        let block = match self.encoding {
+            TextEncoding::VariationSelectors => encode_variation_selectors(&wrapper),
+            TextEncoding::HtmlComment => encode_html_comment(&wrapper),
+        };
+
+        output_stream.rewind()?;
+        output_stream.write_all(strip_blocks(&text).as_bytes())?;
+        output_stream.write_all(block.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_block(self, input_stream, &mut output_stream)?;
+
+        let mut positions: Vec<HashObjectPositions> = Vec::new();
+
+        let text = read_text(&mut output_stream)?;
+        let block = get_manifest_block(&text)?;
+
+        positions.push(HashObjectPositions {
+            offset: block.start,
+            length: block.end - block.start,
+            htype: HashBlockObjectType::Cai,
+        });
+
+        // add hash of text before cai
+        positions.push(HashObjectPositions {
+            offset: 0,
+            length: block.start,
+            htype: HashBlockObjectType::Other,
+        });
+
+        // add position from cai to end
+        positions.push(HashObjectPositions {
+            offset: block.end,
+            length: text.len() - block.end,
+            htype: HashBlockObjectType::Other,
+        });
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        let text = read_text(input_stream)?;
+
+        output_stream.rewind()?;
+        output_stream.write_all(strip_blocks(&text).as_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl AssetIO for TextIO {
+    fn new(asset_type: &str) -> Self {
+        let encoding = match asset_type {
+            "md" | "markdown" | "text/markdown" | "html" | "htm" | "text/html" => {
+                TextEncoding::HtmlComment
+            }
+            _ => TextEncoding::VariationSelectors,
+        };
+
+        TextIO { encoding }
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(TextIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(TextIO::new(asset_type)))
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::test::{fixture_path, temp_dir_path};
+
+    #[test]
+    fn test_variation_selector_round_trip() {
+        let store: Vec<u8> = (0..=255).collect();
+        let encoded = encode_variation_selectors(&make_wrapper(&store).unwrap());
+
+        // invisible characters only
+        assert!(encoded.chars().skip(1).all(|c| vs_to_byte(c).is_some()));
+
+        let blocks = find_blocks(&encoded);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].manifest, store);
+        assert_eq!(blocks[0].end, encoded.len());
+    }
+
+    #[test]
+    fn test_variation_selector_length() {
+        // the encoded length only depends on the store length
+        let small = encode_variation_selectors(&make_wrapper(&[0u8; 100]).unwrap());
+        let large = encode_variation_selectors(&make_wrapper(&[255u8; 100]).unwrap());
+        assert_eq!(small.len(), large.len());
+    }
+
+    #[test]
+    fn test_write_txt() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("sample1.txt");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-txt.txt");
+        std::fs::copy(&source, &output).unwrap();
+
+        let text_io = TextIO::new("txt");
+        text_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(text_io.read_cai_store(&output).unwrap(), more_data);
+
+        // the text is unchanged in front of the block
+        let source_text = std::fs::read_to_string(&source).unwrap();
+        let output_text = std::fs::read_to_string(&output).unwrap();
+        assert!(output_text.starts_with(&source_text));
+        assert!(output_text[source_text.len()..].starts_with(ZWNBSP));
+    }
+
+    #[test]
+    fn test_write_markdown_html() {
+        for (format, source) in [
+            ("md", &include_bytes!("../../tests/fixtures/sample1.md")[..]),
+            (
+                "text/html",
+                &include_bytes!("../../tests/fixtures/sample1.html")[..],
+            ),
+        ] {
+            let text_io = TextIO::new(format);
+
+            let mut output_stream = Cursor::new(Vec::new());
+            text_io
+                .write_cai(
+                    &mut Cursor::new(source.to_vec()),
+                    &mut output_stream,
+                    &[1, 2, 3],
+                )
+                .unwrap();
+            assert_eq!(text_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+
+            let output = String::from_utf8(output_stream.get_ref().clone()).unwrap();
+            assert!(output.ends_with(COMMENT_END));
+            assert!(output[source.len()..].starts_with(COMMENT_START));
+
+            // replace the existing store
+            let mut replaced_stream = Cursor::new(Vec::new());
+            text_io
+                .write_cai(&mut output_stream, &mut replaced_stream, &[4, 5])
+                .unwrap();
+            assert_eq!(text_io.read_cai(&mut replaced_stream).unwrap(), vec![4, 5]);
+        }
+    }
+
+    #[test]
+    fn test_write_cai_not_utf8() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let text_io = TextIO::new("txt");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            text_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_get_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.txt");
+        let text_io = TextIO::new("txt");
+
+        let positions = text_io
+            .get_object_locations_from_stream(&mut Cursor::new(source.to_vec()))
+            .unwrap();
+        let cai = positions
+            .iter()
+            .find(|p| p.htype == HashBlockObjectType::Cai)
+            .unwrap();
+
+        // the exclusion covers exactly the block
+        let mut output_stream = Cursor::new(Vec::new());
+        text_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[9, 9, 9, 9],
+            )
+            .unwrap();
+        let output = output_stream.get_ref();
+        assert_eq!(cai.offset, source.len());
+        assert_eq!(cai.offset + cai.length, output.len());
+    }
+
+    #[test]
+    fn test_too_many_stores() {
+        let source = include_bytes!("../../tests/fixtures/sample1.md");
+        let wrapper = make_wrapper(&[1, 2, 3]).unwrap();
+
+        let mut text = String::from_utf8(source.to_vec()).unwrap();
+        text.push_str(&encode_html_comment(&wrapper));
+        text.push_str(&encode_variation_selectors(&wrapper));
+
+        let text_io = TextIO::new("md");
+        assert!(matches!(
+            text_io.read_cai(&mut Cursor::new(text.into_bytes())),
+            Err(Error::TooManyManifestStores)
+        ));
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.html");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-html.html");
+        std::fs::copy(&source, &output).unwrap();
+
+        let text_io = TextIO::new("html");
+        text_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        text_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match text_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+}
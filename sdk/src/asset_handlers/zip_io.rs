@@ -0,0 +1,1003 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! ZIP package (Office Open XML and EPUB) support.
+//!
+//! The manifest store is written uncompressed to the `META-INF/content_credential.c2pa`
+//! part.  The part is always the last entry of the package, so its local file header
+//! and data come right before the central directory and its central directory record
+//! right before the end of central directory record.  The other entries are copied
+//! as is.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use conv::ValueFrom;
+use tempfile::Builder;
+use zip::ZipArchive;
+
+use crate::{
+    asset_io::{
+        rename_or_copy, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+        HashBlockObjectType, HashObjectPositions, RemoteRefEmbed, RemoteRefEmbedType,
+    },
+    error::{Error, Result},
+    utils::xmp_inmemory_utils::{add_provenance, MIN_XMP},
+};
+
+static SUPPORTED_TYPES: [&str; 8] = [
+    "docx",
+    "xlsx",
+    "pptx",
+    "epub",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/epub+zip",
+];
+
+const C2PA_PART: &str = "META-INF/content_credential.c2pa";
+const XMP_PART: &str = "META-INF/metadata.xmp";
+
+// Office Open XML packages must declare a content type for every part
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const C2PA_CONTENT_TYPE: (&str, &str) = ("c2pa", "application/c2pa");
+const XMP_CONTENT_TYPE: (&str, &str) = ("xmp", "application/rdf+xml");
+
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const EOCD_SIG: u32 = 0x0605_4b50;
+const ZIP64_LOCATOR_SIG: u32 = 0x0706_4b50;
+const DATA_DESCRIPTOR_SIG: u32 = 0x0807_4b50;
+
+const LOCAL_HEADER_LEN: u64 = 30;
+const CENTRAL_HEADER_LEN: u64 = 46;
+const EOCD_LEN: u64 = 22;
+const MAX_COMMENT_LEN: u64 = 0xffff;
+
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+const METHOD_STORED: u16 = 0;
+const VERSION: u16 = 20;
+
+// 1980-01-01 00:00:00, the earliest DOS date
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = 0x0021;
+
+#[derive(Clone, Debug)]
+struct ZipEntry {
+    pub name: String,
+    pub flags: u16,
+    pub method: u16,
+    pub compressed_size: u64,
+    pub cd_start: u64,
+    pub cd_len: u64,
+    pub local_start: u64,
+    pub local_len: u64,
+    pub data_start: u64,
+}
+
+impl ZipEntry {
+    pub fn local_end(&self) -> u64 {
+        self.local_start + self.local_len
+    }
+
+    pub fn cd_end(&self) -> u64 {
+        self.cd_start + self.cd_len
+    }
+}
+
+struct ZipLayout {
+    // central directory order
+    entries: Vec<ZipEntry>,
+    cd_start: u64,
+    eocd_start: u64,
+    comment: Vec<u8>,
+}
+
+impl ZipLayout {
+    fn entry(&self, name: &str) -> Option<&ZipEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+}
+
+fn zip_err<T>(_err: T) -> Error {
+    Error::InvalidAsset("ZIP out of range".to_string())
+}
+
+fn find_eocd<R: Read + Seek + ?Sized>(f: &mut R, stream_len: u64) -> Result<u64> {
+    // the record is at the end of the file followed by a variable length comment
+    let search_len = std::cmp::min(stream_len, EOCD_LEN + MAX_COMMENT_LEN);
+    let search_start = stream_len - search_len;
+
+    let mut buf = vec![0u8; search_len as usize];
+    f.seek(SeekFrom::Start(search_start))?;
+    f.read_exact(&mut buf).map_err(zip_err)?;
+
+    let sig = EOCD_SIG.to_le_bytes();
+    (0..buf.len().saturating_sub(EOCD_LEN as usize - 1))
+        .rev()
+        .find(|i| {
+            let comment_len = u16::from_le_bytes([buf[i + 20], buf[i + 21]]) as usize;
+            buf[*i..*i + 4] == sig && i + EOCD_LEN as usize + comment_len == buf.len()
+        })
+        .map(|i| search_start + i as u64)
+        .ok_or_else(|| Error::InvalidAsset("ZIP invalid".to_string()))
+}
+
+fn get_zip_layout<R: Read + Seek + ?Sized>(f: &mut R) -> Result<ZipLayout> {
+    let stream_len = f.seek(SeekFrom::End(0))?;
+    f.rewind()?;
+
+    if f.read_u32::<LittleEndian>().map_err(zip_err)? != LOCAL_HEADER_SIG {
+        return Err(Error::InvalidAsset("ZIP invalid".to_string()));
+    }
+
+    let eocd_start = find_eocd(f, stream_len)?;
+
+    if eocd_start >= 20 {
+        f.seek(SeekFrom::Start(eocd_start - 20))?;
+        if f.read_u32::<LittleEndian>().map_err(zip_err)? == ZIP64_LOCATOR_SIG {
+            return Err(Error::InvalidAsset("ZIP64 is not supported".to_string()));
+        }
+    }
+
+    f.seek(SeekFrom::Start(eocd_start + 4))?;
+    let disk = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+    let cd_disk = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+    let _disk_entries = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+    let entry_count = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+    let cd_size = f.read_u32::<LittleEndian>().map_err(zip_err)? as u64;
+    let cd_start = f.read_u32::<LittleEndian>().map_err(zip_err)? as u64;
+    let comment_len = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+    let mut comment = vec![0u8; comment_len as usize];
+    f.read_exact(&mut comment).map_err(zip_err)?;
+
+    if disk != 0 || cd_disk != 0 {
+        return Err(Error::InvalidAsset(
+            "ZIP multi-disk archives are not supported".to_string(),
+        ));
+    }
+    if cd_start + cd_size > eocd_start {
+        return Err(Error::InvalidAsset("ZIP bad central directory".to_string()));
+    }
+
+    let mut entries = Vec::new();
+    let mut pos = cd_start;
+    for _ in 0..entry_count {
+        f.seek(SeekFrom::Start(pos))?;
+        if f.read_u32::<LittleEndian>().map_err(zip_err)? != CENTRAL_HEADER_SIG {
+            return Err(Error::InvalidAsset("ZIP bad central directory".to_string()));
+        }
+
+        f.seek(SeekFrom::Start(pos + 8))?;
+        let flags = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+        let method = f.read_u16::<LittleEndian>().map_err(zip_err)?;
+        f.seek(SeekFrom::Start(pos + 20))?;
+        let compressed_size = f.read_u32::<LittleEndian>().map_err(zip_err)? as u64;
+        f.seek(SeekFrom::Start(pos + 28))?;
+        let name_len = f.read_u16::<LittleEndian>().map_err(zip_err)? as u64;
+        let extra_len = f.read_u16::<LittleEndian>().map_err(zip_err)? as u64;
+        let comment_len = f.read_u16::<LittleEndian>().map_err(zip_err)? as u64;
+        f.seek(SeekFrom::Start(pos + 42))?;
+        let local_start = f.read_u32::<LittleEndian>().map_err(zip_err)? as u64;
+        let mut name = vec![0u8; name_len as usize];
+        f.read_exact(&mut name).map_err(zip_err)?;
+
+        let cd_len = CENTRAL_HEADER_LEN + name_len + extra_len + comment_len;
+
+        // the local header may have a different extra field than the central record
+        f.seek(SeekFrom::Start(local_start))?;
+        if f.read_u32::<LittleEndian>().map_err(zip_err)? != LOCAL_HEADER_SIG {
+            return Err(Error::InvalidAsset("ZIP bad local header".to_string()));
+        }
+        f.seek(SeekFrom::Start(local_start + 26))?;
+        let local_name_len = f.read_u16::<LittleEndian>().map_err(zip_err)? as u64;
+        let local_extra_len = f.read_u16::<LittleEndian>().map_err(zip_err)? as u64;
+        let data_start = local_start + LOCAL_HEADER_LEN + local_name_len + local_extra_len;
+
+        let mut local_end = data_start + compressed_size;
+        if flags & FLAG_DATA_DESCRIPTOR != 0 {
+            // the descriptor signature is optional
+            f.seek(SeekFrom::Start(local_end))?;
+            local_end += match f.read_u32::<LittleEndian>().map_err(zip_err)? {
+                DATA_DESCRIPTOR_SIG => 16,
+                _ => 12,
+            };
+        }
+        if local_end > cd_start {
+            return Err(Error::InvalidAsset("ZIP bad local header".to_string()));
+        }
+
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(&name).to_string(),
+            flags,
+            method,
+            compressed_size,
+            cd_start: pos,
+            cd_len,
+            local_start,
+            local_len: local_end - local_start,
+            data_start,
+        });
+        pos += cd_len;
+    }
+
+    Ok(ZipLayout {
+        entries,
+        cd_start,
+        eocd_start,
+        comment,
+    })
+}
+
+// read a part, decompressing it when needed
+fn read_part(input_stream: &mut dyn CAIRead, name: &str) -> Result<Option<Vec<u8>>> {
+    input_stream.rewind()?;
+    let mut archive = ZipArchive::new(input_stream)
+        .map_err(|_err| Error::InvalidAsset("ZIP invalid".to_string()))?;
+
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(_) => return Err(Error::InvalidAsset("ZIP invalid".to_string())),
+    };
+
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).map_err(zip_err)?;
+
+    Ok(Some(data))
+}
+
+// Add a default content type for each extension not already declared.
+fn update_content_types(content_types: &str, defaults: &[(&str, &str)]) -> Option<String> {
+    let mut updated = content_types.to_string();
+    for (extension, content_type) in defaults {
+        let declared = updated
+            .to_lowercase()
+            .contains(&format!("extension=\"{extension}\""));
+        if !declared {
+            let pos = updated.rfind("</Types>")?;
+            updated.insert_str(
+                pos,
+                &format!("<Default Extension=\"{extension}\" ContentType=\"{content_type}\"/>"),
+            );
+        }
+    }
+
+    (updated != content_types).then_some(updated)
+}
+
+struct NewPart {
+    name: &'static str,
+    data: Vec<u8>,
+    crc: u32,
+    offset: u64,
+}
+
+fn write_local_header<W: Write + ?Sized>(w: &mut W, name: &str, crc: u32, size: u32) -> Result<()> {
+    let name_len = u16::value_from(name.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    w.write_u32::<LittleEndian>(LOCAL_HEADER_SIG)?;
+    w.write_u16::<LittleEndian>(VERSION)?;
+    w.write_u16::<LittleEndian>(0)?; // flags
+    w.write_u16::<LittleEndian>(METHOD_STORED)?;
+    w.write_u16::<LittleEndian>(DOS_TIME)?;
+    w.write_u16::<LittleEndian>(DOS_DATE)?;
+    w.write_u32::<LittleEndian>(crc)?;
+    w.write_u32::<LittleEndian>(size)?; // compressed
+    w.write_u32::<LittleEndian>(size)?; // uncompressed
+    w.write_u16::<LittleEndian>(name_len)?;
+    w.write_u16::<LittleEndian>(0)?; // extra field
+    w.write_all(name.as_bytes())?;
+
+    Ok(())
+}
+
+fn write_central_header<W: Write + ?Sized>(
+    w: &mut W,
+    name: &str,
+    crc: u32,
+    size: u32,
+    offset: u32,
+) -> Result<()> {
+    let name_len = u16::value_from(name.len())
+        .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+
+    w.write_u32::<LittleEndian>(CENTRAL_HEADER_SIG)?;
+    w.write_u16::<LittleEndian>(VERSION)?; // made by
+    w.write_u16::<LittleEndian>(VERSION)?; // needed to extract
+    w.write_u16::<LittleEndian>(0)?; // flags
+    w.write_u16::<LittleEndian>(METHOD_STORED)?;
+    w.write_u16::<LittleEndian>(DOS_TIME)?;
+    w.write_u16::<LittleEndian>(DOS_DATE)?;
+    w.write_u32::<LittleEndian>(crc)?;
+    w.write_u32::<LittleEndian>(size)?; // compressed
+    w.write_u32::<LittleEndian>(size)?; // uncompressed
+    w.write_u16::<LittleEndian>(name_len)?;
+    w.write_u16::<LittleEndian>(0)?; // extra field
+    w.write_u16::<LittleEndian>(0)?; // comment
+    w.write_u16::<LittleEndian>(0)?; // disk number
+    w.write_u16::<LittleEndian>(0)?; // internal attributes
+    w.write_u32::<LittleEndian>(0)?; // external attributes
+    w.write_u32::<LittleEndian>(offset)?;
+    w.write_all(name.as_bytes())?;
+
+    Ok(())
+}
+
+fn to_u32(value: u64) -> Result<u32> {
+    u32::value_from(value).map_err(|_err| Error::InvalidAsset("ZIP64 is not supported".to_owned()))
+}
+
+// Rewrite the package without the parts named in `remove`, appending `new_parts`
+// uncompressed at the end.  The remaining entries are copied without recompression.
+fn rewrite_zip(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+    remove: &[&str],
+    new_parts: Vec<(&'static str, Vec<u8>)>,
+) -> Result<()> {
+    let layout = get_zip_layout(input_stream)?;
+
+    // declare the content types of the new parts in Office Open XML packages
+    let mut defaults = Vec::new();
+    for (name, _) in &new_parts {
+        match *name {
+            C2PA_PART => defaults.push(C2PA_CONTENT_TYPE),
+            XMP_PART => defaults.push(XMP_CONTENT_TYPE),
+            _ => (),
+        }
+    }
+    let content_types = match layout.entry(CONTENT_TYPES_PART) {
+        Some(_) if !defaults.is_empty() => read_part(input_stream, CONTENT_TYPES_PART)?
+            .and_then(|data| String::from_utf8(data).ok())
+            .and_then(|xml| update_content_types(&xml, &defaults))
+            .map(|xml| {
+                let data = xml.into_bytes();
+                let crc = crc32fast::hash(&data);
+                (data, crc)
+            }),
+        _ => None,
+    };
+
+    let kept: Vec<&ZipEntry> = layout
+        .entries
+        .iter()
+        .filter(|e| !remove.contains(&e.name.as_str()))
+        .collect();
+
+    output_stream.rewind()?;
+
+    // keep anything in front of the first entry (e.g. self extracting stubs)
+    let first_local = layout
+        .entries
+        .iter()
+        .map(|e| e.local_start)
+        .min()
+        .unwrap_or(layout.cd_start);
+    input_stream.rewind()?;
+    std::io::copy(&mut input_stream.take(first_local), output_stream)?;
+    let mut pos = first_local;
+
+    // local entries in their original order
+    let mut local_order = kept.clone();
+    local_order.sort_by_key(|e| e.local_start);
+    let mut new_offsets = std::collections::HashMap::new();
+    for entry in local_order {
+        new_offsets.insert(entry.cd_start, pos);
+
+        match &content_types {
+            Some((data, crc)) if entry.name == CONTENT_TYPES_PART => {
+                let size = to_u32(data.len() as u64)?;
+                write_local_header(output_stream, &entry.name, *crc, size)?;
+                output_stream.write_all(data)?;
+                pos += LOCAL_HEADER_LEN + entry.name.len() as u64 + data.len() as u64;
+            }
+            _ => {
+                input_stream.seek(SeekFrom::Start(entry.local_start))?;
+                std::io::copy(&mut input_stream.take(entry.local_len), output_stream)?;
+                pos += entry.local_len;
+            }
+        }
+    }
+
+    let mut parts = Vec::new();
+    for (name, data) in new_parts {
+        let crc = crc32fast::hash(&data);
+        let size = to_u32(data.len() as u64)?;
+
+        write_local_header(output_stream, name, crc, size)?;
+        output_stream.write_all(&data)?;
+        parts.push(NewPart {
+            name,
+            data,
+            crc,
+            offset: pos,
+        });
+        pos += LOCAL_HEADER_LEN + name.len() as u64 + size as u64;
+    }
+
+    // central directory
+    let cd_start = pos;
+    for entry in &kept {
+        let len = usize::value_from(entry.cd_len)
+            .map_err(|_err| Error::InvalidAsset("value out of range".to_owned()))?;
+        let mut record = vec![0u8; len];
+        input_stream.seek(SeekFrom::Start(entry.cd_start))?;
+        input_stream.read_exact(&mut record).map_err(zip_err)?;
+
+        let offset = new_offsets
+            .get(&entry.cd_start)
+            .copied()
+            .ok_or(Error::NotFound)?;
+        record[42..46].copy_from_slice(&to_u32(offset)?.to_le_bytes());
+
+        if let Some((data, crc)) = content_types
+            .as_ref()
+            .filter(|_| entry.name == CONTENT_TYPES_PART)
+        {
+            let size = to_u32(data.len() as u64)?;
+            let flags = entry.flags & !FLAG_DATA_DESCRIPTOR;
+            record[8..10].copy_from_slice(&flags.to_le_bytes());
+            record[10..12].copy_from_slice(&METHOD_STORED.to_le_bytes());
+            record[16..20].copy_from_slice(&crc.to_le_bytes());
+            record[20..24].copy_from_slice(&size.to_le_bytes());
+            record[24..28].copy_from_slice(&size.to_le_bytes());
+        }
+
+        output_stream.write_all(&record)?;
+        pos += entry.cd_len;
+    }
+    for part in &parts {
+        let size = to_u32(part.data.len() as u64)?;
+        write_central_header(
+            output_stream,
+            part.name,
+            part.crc,
+            size,
+            to_u32(part.offset)?,
+        )?;
+        pos += CENTRAL_HEADER_LEN + part.name.len() as u64;
+    }
+
+    // end of central directory
+    let entry_count = u16::value_from(kept.len() + parts.len())
+        .map_err(|_err| Error::InvalidAsset("ZIP64 is not supported".to_owned()))?;
+    output_stream.write_u32::<LittleEndian>(EOCD_SIG)?;
+    output_stream.write_u16::<LittleEndian>(0)?; // disk number
+    output_stream.write_u16::<LittleEndian>(0)?; // central directory disk
+    output_stream.write_u16::<LittleEndian>(entry_count)?;
+    output_stream.write_u16::<LittleEndian>(entry_count)?;
+    output_stream.write_u32::<LittleEndian>(to_u32(pos - cd_start)?)?;
+    output_stream.write_u32::<LittleEndian>(to_u32(cd_start)?)?;
+    output_stream.write_u16::<LittleEndian>(layout.comment.len() as u16)?;
+    output_stream.write_all(&layout.comment)?;
+
+    Ok(())
+}
+
+fn add_required_parts(
+    input_stream: &mut dyn CAIRead,
+    output_stream: &mut dyn CAIReadWrite,
+) -> Result<()> {
+    let aio = ZipIO {};
+
+    match aio.read_cai(input_stream) {
+        Ok(_) => {
+            // just clone
+            input_stream.rewind()?;
+            output_stream.rewind()?;
+            std::io::copy(input_stream, output_stream)?;
+            Ok(())
+        }
+        Err(Error::JumbfNotFound) => {
+            aio.write_cai(input_stream, output_stream, &[1, 2, 3, 4]) // save arbitrary data
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub struct ZipIO {}
+
+impl CAIReader for ZipIO {
+    fn read_cai(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let layout = get_zip_layout(input_stream)?;
+        if layout
+            .entries
+            .iter()
+            .filter(|e| e.name == C2PA_PART)
+            .count()
+            > 1
+        {
+            return Err(Error::TooManyManifestStores);
+        }
+
+        read_part(input_stream, C2PA_PART)?.ok_or(Error::JumbfNotFound)
+    }
+
+    // Get XMP block
+    fn read_xmp(&self, input_stream: &mut dyn CAIRead) -> Option<String> {
+        let data = read_part(input_stream, XMP_PART).ok()??;
+        String::from_utf8(data).ok()
+    }
+}
+
+impl CAIWriter for ZipIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        rewrite_zip(
+            input_stream,
+            output_stream,
+            &[C2PA_PART],
+            vec![(C2PA_PART, store_bytes.to_vec())],
+        )
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let mut output_stream = Cursor::new(Vec::new());
+        add_required_parts(input_stream, &mut output_stream)?;
+
+        let layout = get_zip_layout(&mut output_stream)?;
+        let entry = layout.entry(C2PA_PART).ok_or(Error::JumbfNotFound)?;
+        let file_end = output_stream.get_ref().len() as u64;
+
+        // the local header and data, the central directory record and the end of
+        // central directory record all change with the manifest store
+        let ranges = [
+            (0, entry.local_start, HashBlockObjectType::Other),
+            (
+                entry.local_start,
+                entry.local_end(),
+                HashBlockObjectType::Cai,
+            ),
+            (
+                entry.local_end(),
+                entry.cd_start,
+                HashBlockObjectType::Other,
+            ),
+            (entry.cd_start, entry.cd_end(), HashBlockObjectType::Cai),
+            (
+                entry.cd_end(),
+                layout.eocd_start,
+                HashBlockObjectType::Other,
+            ),
+            (layout.eocd_start, file_end, HashBlockObjectType::Cai),
+        ];
+
+        let mut positions = Vec::new();
+        for (start, end, htype) in ranges {
+            if end > start {
+                positions.push(HashObjectPositions {
+                    offset: usize::value_from(start)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?,
+                    length: usize::value_from(end - start)
+                        .map_err(|_err| Error::InvalidAsset("value out of range".to_string()))?,
+                    htype,
+                });
+            }
+        }
+
+        Ok(positions)
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        rewrite_zip(input_stream, output_stream, &[C2PA_PART], Vec::new())
+    }
+}
+
+impl AssetIO for ZipIO {
+    fn new(_asset_type: &str) -> Self {
+        ZipIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(ZipIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(ZipIO::new(asset_type)))
+    }
+
+    fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
+        Some(self)
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        let mut f = File::open(asset_path)?;
+        self.read_cai(&mut f)
+    }
+
+    fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.write_cai(&mut input_stream, &mut temp_file, store_bytes)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        let mut input_stream = File::open(asset_path)?;
+        self.get_object_locations_from_stream(&mut input_stream)
+    }
+
+    fn remove_cai_store(&self, asset_path: &Path) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.remove_cai_store_from_stream(&mut input_stream, &mut temp_file)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
+        Some(self)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &SUPPORTED_TYPES
+    }
+}
+
+impl AssetPatch for ZipIO {
+    fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()> {
+        let mut asset = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(false)
+            .open(asset_path)?;
+
+        let layout = get_zip_layout(&mut asset)?;
+        let entry = layout.entry(C2PA_PART).ok_or(Error::JumbfNotFound)?;
+
+        if entry.method == METHOD_STORED && store_bytes.len() as u64 == entry.compressed_size {
+            let crc = crc32fast::hash(store_bytes).to_le_bytes();
+
+            asset.seek(SeekFrom::Start(entry.data_start))?;
+            asset.write_all(store_bytes)?;
+
+            // the checksum is in both the local header and the central directory
+            asset.seek(SeekFrom::Start(entry.local_start + 14))?;
+            asset.write_all(&crc)?;
+            asset.seek(SeekFrom::Start(entry.cd_start + 16))?;
+            asset.write_all(&crc)?;
+            Ok(())
+        } else {
+            Err(Error::InvalidAsset(
+                "patch_cai_store store size mismatch.".to_string(),
+            ))
+        }
+    }
+}
+
+impl RemoteRefEmbed for ZipIO {
+    #[allow(unused_variables)]
+    fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()> {
+        let mut input_stream = File::open(asset_path)?;
+
+        let mut temp_file = Builder::new()
+            .prefix("c2pa_temp")
+            .rand_bytes(5)
+            .tempfile()?;
+
+        self.embed_reference_to_stream(&mut input_stream, &mut temp_file, embed_ref)?;
+
+        // copy temp file to asset
+        rename_or_copy(temp_file, asset_path)
+    }
+
+    fn embed_reference_to_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        embed_ref: RemoteRefEmbedType,
+    ) -> Result<()> {
+        match embed_ref {
+            crate::asset_io::RemoteRefEmbedType::Xmp(manifest_uri) => {
+                let xmp = match self.read_xmp(input_stream) {
+                    Some(s) => s,
+                    None => MIN_XMP.to_string(),
+                };
+                let new_xmp = add_provenance(&xmp, &manifest_uri)?;
+
+                let mut new_parts = vec![(XMP_PART, new_xmp.into_bytes())];
+
+                // keep the manifest store as the last part
+                match self.read_cai(input_stream) {
+                    Ok(manifest) => new_parts.push((C2PA_PART, manifest)),
+                    Err(Error::JumbfNotFound) => (),
+                    Err(e) => return Err(e),
+                }
+
+                rewrite_zip(
+                    input_stream,
+                    output_stream,
+                    &[XMP_PART, C2PA_PART],
+                    new_parts,
+                )
+            }
+            crate::asset_io::RemoteRefEmbedType::StegoS(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::StegoB(_) => Err(Error::UnsupportedType),
+            crate::asset_io::RemoteRefEmbedType::Watermark(_) => Err(Error::UnsupportedType),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::expect_used)]
+    #![allow(clippy::panic)]
+    #![allow(clippy::unwrap_used)]
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::utils::{
+        test::{fixture_path, temp_dir_path},
+        xmp_inmemory_utils::extract_provenance,
+    };
+
+    fn part_names(data: &[u8]) -> Vec<String> {
+        let layout = get_zip_layout(&mut Cursor::new(data)).unwrap();
+        layout.entries.iter().map(|e| e.name.clone()).collect()
+    }
+
+    // every part can be read back with the zip crate, checking the checksums
+    fn verify_parts(data: &[u8]) {
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_zip_parse() {
+        let source = include_bytes!("../../tests/fixtures/sample1.docx");
+        assert_eq!(
+            part_names(source),
+            vec!["[Content_Types].xml", "_rels/.rels", "word/document.xml"]
+        );
+
+        let source = include_bytes!("../../tests/fixtures/sample1.epub");
+        assert_eq!(part_names(source)[0], "mimetype");
+    }
+
+    #[test]
+    fn test_write_docx() {
+        let more_data = "some more test data".as_bytes();
+        let source = fixture_path("sample1.docx");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-docx.docx");
+        std::fs::copy(source, &output).unwrap();
+
+        let zip_io = ZipIO::new("docx");
+        zip_io.save_cai_store(&output, more_data).unwrap();
+        assert_eq!(zip_io.read_cai_store(&output).unwrap(), more_data);
+
+        let output_bytes = std::fs::read(&output).unwrap();
+        verify_parts(&output_bytes);
+        assert_eq!(part_names(&output_bytes).last().unwrap(), C2PA_PART);
+
+        // the manifest part has a content type
+        let content_types = String::from_utf8(
+            read_part(&mut Cursor::new(output_bytes), CONTENT_TYPES_PART)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(content_types
+            .contains("<Default Extension=\"c2pa\" ContentType=\"application/c2pa\"/></Types>"));
+    }
+
+    #[test]
+    fn test_write_epub() {
+        let source = include_bytes!("../../tests/fixtures/sample1.epub");
+        let zip_io = ZipIO::new("epub");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        zip_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut output_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert_eq!(zip_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+
+        // the uncompressed mimetype must stay the first entry
+        let output = output_stream.get_ref();
+        assert_eq!(&output[30..58], b"mimetypeapplication/epub+zip");
+        verify_parts(output);
+
+        // replace the existing store
+        let mut replaced_stream = Cursor::new(Vec::new());
+        zip_io
+            .write_cai(&mut output_stream, &mut replaced_stream, &[4, 5])
+            .unwrap();
+        assert_eq!(zip_io.read_cai(&mut replaced_stream).unwrap(), vec![4, 5]);
+        assert_eq!(part_names(replaced_stream.get_ref()).len(), 5);
+    }
+
+    #[test]
+    fn test_write_cai_data_to_stream_wrong_format() {
+        let source = include_bytes!("../../tests/fixtures/C.jpg");
+        let zip_io = ZipIO::new("docx");
+
+        let mut output_stream = Cursor::new(Vec::new());
+        assert!(matches!(
+            zip_io.write_cai(&mut Cursor::new(source.to_vec()), &mut output_stream, &[]),
+            Err(Error::InvalidAsset(_),)
+        ));
+    }
+
+    #[test]
+    fn test_get_object_locations() {
+        let source = include_bytes!("../../tests/fixtures/sample1.epub");
+        let zip_io = ZipIO::new("epub");
+
+        let positions = zip_io
+            .get_object_locations_from_stream(&mut Cursor::new(source.to_vec()))
+            .unwrap();
+        let cai: Vec<&HashObjectPositions> = positions
+            .iter()
+            .filter(|p| p.htype == HashBlockObjectType::Cai)
+            .collect();
+        assert_eq!(cai.len(), 3);
+
+        // the ranges that change with the store are excluded, the rest is identical
+        // for stores of the same size
+        let hashed = |data: &[u8]| -> Vec<u8> {
+            positions
+                .iter()
+                .filter(|p| p.htype != HashBlockObjectType::Cai)
+                .flat_map(|p| data[p.offset..p.offset + p.length].to_vec())
+                .collect()
+        };
+
+        let mut first = Cursor::new(Vec::new());
+        zip_io
+            .write_cai(&mut Cursor::new(source.to_vec()), &mut first, &[1, 2, 3, 4])
+            .unwrap();
+        let mut second = Cursor::new(Vec::new());
+        zip_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut second,
+                &[5, 6, 7, 8],
+            )
+            .unwrap();
+        assert_eq!(hashed(first.get_ref()), hashed(second.get_ref()));
+
+        // the other central directory records are hashed
+        let layout = get_zip_layout(&mut first).unwrap();
+        let other_cd = positions
+            .iter()
+            .find(|p| p.offset == layout.cd_start as usize)
+            .unwrap();
+        assert_eq!(other_cd.htype, HashBlockObjectType::Other);
+    }
+
+    #[test]
+    fn test_patch_write_zip() {
+        let test_data = "some test data".as_bytes();
+        let source = fixture_path("sample1.docx");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-docx.docx");
+        std::fs::copy(source, &output).unwrap();
+
+        let zip_io = ZipIO::new("docx");
+        zip_io.save_cai_store(&output, test_data).unwrap();
+
+        // create replacement data of same size
+        let new_data = "more test data".as_bytes();
+        zip_io.patch_cai_store(&output, new_data).unwrap();
+        assert_eq!(zip_io.read_cai_store(&output).unwrap(), new_data);
+
+        verify_parts(&std::fs::read(&output).unwrap());
+    }
+
+    #[test]
+    fn test_remove_c2pa() {
+        let source = fixture_path("sample1.epub");
+
+        let temp_dir = tempdir().unwrap();
+        let output = temp_dir_path(&temp_dir, "sample1-epub.epub");
+        std::fs::copy(&source, &output).unwrap();
+
+        let zip_io = ZipIO::new("epub");
+        zip_io.save_cai_store(&output, &[1, 2, 3, 4]).unwrap();
+        zip_io.remove_cai_store(&output).unwrap();
+
+        // read back in asset, JumbfNotFound is expected since it was removed
+        match zip_io.read_cai_store(&output) {
+            Err(Error::JumbfNotFound) => (),
+            _ => unreachable!(),
+        }
+
+        // epub has no content types so the package is unchanged
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_xmp() {
+        let source = include_bytes!("../../tests/fixtures/sample1.docx");
+        let zip_io = ZipIO::new("docx");
+
+        let mut signed_stream = Cursor::new(Vec::new());
+        zip_io
+            .write_cai(
+                &mut Cursor::new(source.to_vec()),
+                &mut signed_stream,
+                &[1, 2, 3],
+            )
+            .unwrap();
+        assert!(zip_io.read_xmp(&mut signed_stream).is_none());
+
+        let mut output_stream = Cursor::new(Vec::new());
+        zip_io
+            .embed_reference_to_stream(
+                &mut signed_stream,
+                &mut output_stream,
+                RemoteRefEmbedType::Xmp("https://example.com/manifest.c2pa".to_string()),
+            )
+            .unwrap();
+
+        let xmp = zip_io.read_xmp(&mut output_stream).unwrap();
+        assert_eq!(
+            extract_provenance(&xmp).unwrap(),
+            "https://example.com/manifest.c2pa"
+        );
+
+        let names = part_names(output_stream.get_ref());
+        assert_eq!(names[names.len() - 2..], [XMP_PART, C2PA_PART]);
+        assert_eq!(zip_io.read_cai(&mut output_stream).unwrap(), vec![1, 2, 3]);
+        verify_parts(output_stream.get_ref());
+    }
+}
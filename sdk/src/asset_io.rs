@@ -21,6 +21,7 @@ use tempfile::NamedTempFile;
 
 use crate::{assertions::BoxMap, error::Result};
 
+/// The kind of data in a [`HashObjectPositions`] range.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HashBlockObjectType {
     Cai,
@@ -33,6 +34,7 @@ impl fmt::Display for HashBlockObjectType {
         write!(f, "{self:?}")
     }
 }
+/// A range of an asset reported by [`CAIWriter::get_object_locations_from_stream`].
 #[derive(Debug)]
 pub struct HashObjectPositions {
     pub offset: usize, // offset from beginning of file to the beginning of object
@@ -40,6 +42,18 @@ pub struct HashObjectPositions {
     pub htype: HashBlockObjectType, // type of hash block object
 }
 
+/// How an [`AssetIO`] handler keeps a manifest store and binds it to the asset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestBinding {
+    /// The manifest store is embedded and the asset is bound with a `c2pa.hash.data` assertion.
+    DataHash,
+    /// The manifest store is embedded and the asset is bound with a `c2pa.hash.bmff` assertion.
+    BmffHash,
+    /// The asset is a manifest store on its own, kept as a sidecar with nothing to hash or remove.
+    Sidecar,
+}
+
+/// A readable and seekable asset stream.
 pub trait CAIRead: Read + Seek + Send {}
 
 impl<T> CAIRead for T where T: Read + Seek + Send {}
@@ -71,6 +85,7 @@ impl Seek for CAIReadWrapper<'_> {
     }
 }
 
+/// A readable, writable and seekable asset stream.
 pub trait CAIReadWrite: CAIRead + Write {}
 
 impl<T> CAIReadWrite for T where T: CAIRead + Write {}
@@ -108,17 +123,18 @@ impl Seek for CAIReadWriteWrapper<'_> {
 }
 
 /// CAIReader trait to insure CAILoader method support both Read & Seek
-// Interface for in memory CAI reading
+/// Interface for in memory CAI reading.
 pub trait CAIReader: Sync + Send {
-    // Return entire CAI block as Vec<u8>
+    /// Return entire CAI block as Vec<u8>
     fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>>;
 
-    // Get XMP block
+    /// Get XMP block
     fn read_xmp(&self, asset_reader: &mut dyn CAIRead) -> Option<String>;
 }
 
+/// Streaming interface for embedding and removing the C2PA manifest store.
 pub trait CAIWriter: Sync + Send {
-    // Writes store_bytes into output_steam using input_stream as the source asset
+    /// Writes store_bytes into output_steam using input_stream as the source asset
     fn write_cai(
         &self,
         input_stream: &mut dyn CAIRead,
@@ -126,13 +142,13 @@ pub trait CAIWriter: Sync + Send {
         store_bytes: &[u8],
     ) -> Result<()>;
 
-    // Finds location where the C2PA manifests will be placed in the asset specified by input_stream
+    /// Finds location where the C2PA manifests will be placed in the asset specified by input_stream
     fn get_object_locations_from_stream(
         &self,
         input_stream: &mut dyn CAIRead,
     ) -> Result<Vec<HashObjectPositions>>;
 
-    // Remove entire C2PA manifest store from asset
+    /// Remove entire C2PA manifest store from asset
     fn remove_cai_store_from_stream(
         &self,
         input_stream: &mut dyn CAIRead,
@@ -140,29 +156,33 @@ pub trait CAIWriter: Sync + Send {
     ) -> Result<()>;
 }
 
+/// An asset format handler.
+///
+/// Handlers for formats the SDK does not support natively can be added with
+/// [`register_asset_handler`](crate::register_asset_handler).
 pub trait AssetIO: Sync + Send {
-    // Create instance of AssetIO handler.  The extension type is passed in so
-    // that format specific customizations can be used during manifest embedding
+    /// Create instance of AssetIO handler.  The extension type is passed in so
+    /// that format specific customizations can be used during manifest embedding
     fn new(asset_type: &str) -> Self
     where
         Self: Sized;
 
-    // Return AssetIO handler for this asset type
+    /// Return AssetIO handler for this asset type
     fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO>;
 
-    // Return streaming reader for this asset type
+    /// Return streaming reader for this asset type
     fn get_reader(&self) -> &dyn CAIReader;
 
-    // Return streaming writer if available
+    /// Return streaming writer if available
     fn get_writer(&self, _asset_type: &str) -> Option<Box<dyn CAIWriter>> {
         None
     }
 
-    // Return entire CAI block as Vec<u8>
+    /// Return entire CAI block as Vec<u8>
     #[allow(dead_code)]
     fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>>;
 
-    // Write the CAI block to an asset
+    /// Write the CAI block to an asset
     fn save_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()>;
 
     /// List of standard object offsets
@@ -172,61 +192,66 @@ pub trait AssetIO: Sync + Send {
     #[allow(dead_code)] // this here for wasm builds to pass clippy  (todo: remove)
     fn get_object_locations(&self, asset_path: &Path) -> Result<Vec<HashObjectPositions>>;
 
-    // Remove entire C2PA manifest store from asset
+    /// Remove entire C2PA manifest store from asset
     #[allow(dead_code)] // this here for wasm builds to pass clippy  (todo: remove)
     fn remove_cai_store(&self, asset_path: &Path) -> Result<()>;
 
-    // List of supported extensions and mime types
+    /// List of supported extensions and mime types
     fn supported_types(&self) -> &[&str];
 
-    /// OPTIONAL INTERFACES
+    /// How manifest stores are kept with and bound to assets of this type.
+    fn manifest_binding(&self) -> ManifestBinding {
+        ManifestBinding::DataHash
+    }
+
+    // OPTIONAL INTERFACES
 
-    // Returns [`AssetPatch`] trait if this I/O handler supports patching.
+    /// Returns [`AssetPatch`] trait if this I/O handler supports patching.
     #[allow(dead_code)] // this here for wasm builds to pass clippy  (todo: remove)
     fn asset_patch_ref(&self) -> Option<&dyn AssetPatch> {
         None
     }
 
-    // Returns [`RemoteRefEmbed`] trait if this I/O handler supports remote reference embedding.
+    /// Returns [`RemoteRefEmbed`] trait if this I/O handler supports remote reference embedding.
     fn remote_ref_writer_ref(&self) -> Option<&dyn RemoteRefEmbed> {
         None
     }
 
-    // Returns [`AssetBoxHash`] trait if this I/O handler supports box hashing.
+    /// Returns [`AssetBoxHash`] trait if this I/O handler supports box hashing.
     fn asset_box_hash_ref(&self) -> Option<&dyn AssetBoxHash> {
         None
     }
 
-    // Returns [`ComposedManifestRefEmbed`] trait if this I/O handler supports composed data.
+    /// Returns [`ComposedManifestRef`] trait if this I/O handler supports composed data.
     fn composed_data_ref(&self) -> Option<&dyn ComposedManifestRef> {
         None
     }
 }
 
-// `AssetPatch` optimizes output generation for asset_io handlers that
-// are able to patch blocks of data without changing any other data. The
-// resultant file must still be a valid asset. This saves having to rewrite
-// assets since only the patched bytes are modified.
+/// `AssetPatch` optimizes output generation for asset_io handlers that
+/// are able to patch blocks of data without changing any other data. The
+/// resultant file must still be a valid asset. This saves having to rewrite
+/// assets since only the patched bytes are modified.
 pub trait AssetPatch {
-    // Patches an existing manifest store with new manifest store.
-    // Only existing manifest stores of the same size may be patched
-    // since any other changes will invalidate asset hashes.
+    /// Patches an existing manifest store with new manifest store.
+    /// Only existing manifest stores of the same size may be patched
+    /// since any other changes will invalidate asset hashes.
     #[allow(dead_code)] // this here for wasm builds to pass clippy  (todo: remove)
     fn patch_cai_store(&self, asset_path: &Path, store_bytes: &[u8]) -> Result<()>;
 }
 
-// `AssetBoxHash` provides interfaces needed to support C2PA BoxHash functionality.
-//  This trait is only implemented for supported types
+/// `AssetBoxHash` provides interfaces needed to support C2PA BoxHash functionality.
+///  This trait is only implemented for supported types
 pub trait AssetBoxHash {
-    // Returns Vec containing all BoxMap level objects in the asset in the order
-    // they occur in the asset.  The hashes do not need to be calculated, only the
-    // name and the positional information.  The list should be flat with each BoxMap
-    // representing a single entry.
+    /// Returns Vec containing all BoxMap level objects in the asset in the order
+    /// they occur in the asset.  The hashes do not need to be calculated, only the
+    /// name and the positional information.  The list should be flat with each BoxMap
+    /// representing a single entry.
     fn get_box_map(&self, input_stream: &mut dyn CAIRead) -> Result<Vec<BoxMap>>;
 }
 
-// Type of remote reference to embed.  Some of the listed
-// emums are for future uses and experiments.
+/// Type of remote reference to embed.  Some of the listed
+/// emums are for future uses and experiments.
 #[allow(dead_code)]
 pub enum RemoteRefEmbedType {
     Xmp(String),
@@ -235,14 +260,14 @@ pub enum RemoteRefEmbedType {
     Watermark(String),
 }
 
-// `RemoteRefEmbed` is used to embed remote references to external manifests.  The
-// technique used to embed a reference varies bases on the type of embedding.  Not
-// all embedding choices need be supported.
+/// `RemoteRefEmbed` is used to embed remote references to external manifests.  The
+/// technique used to embed a reference varies bases on the type of embedding.  Not
+/// all embedding choices need be supported.
 pub trait RemoteRefEmbed {
-    // Embed RemoteRefEmbedType into the asset
+    /// Embed RemoteRefEmbedType into the asset
     #[allow(dead_code)] // this here for wasm builds to pass clippy  (todo: remove)
     fn embed_reference(&self, asset_path: &Path, embed_ref: RemoteRefEmbedType) -> Result<()>;
-    // Embed RemoteRefEmbedType into the asset stream
+    /// Embed RemoteRefEmbedType into the asset stream
     fn embed_reference_to_stream(
         &self,
         source_stream: &mut dyn CAIRead,
@@ -255,7 +280,7 @@ pub trait RemoteRefEmbed {
 /// returned `Vec<u8>` contains data preformatted to be directly compatible
 /// with the type specified in `format`.  
 pub trait ComposedManifestRef {
-    // Return entire CAI block as Vec<u8>
+    /// Return entire CAI block as Vec<u8>
     fn compose_manifest(&self, manifest_data: &[u8], format: &str) -> Result<Vec<u8>>;
 }
 
@@ -24,15 +24,21 @@ use zip::{write::FileOptions, ZipArchive, ZipWriter};
 
 use crate::{
     assertion::AssertionBase,
-    assertions::{labels, Actions, CreativeWork, Exif, SoftwareAgent, Thumbnail, User, UserCbor},
+    assertions::{
+        c2pa_action, labels, Action, Actions, BoxHash, CreativeWork, DataHash, Exif, SoftwareAgent,
+        Thumbnail, User, UserCbor,
+    },
     claim::Claim,
     error::{Error, Result},
     ingredient::Ingredient,
+    jumbf::labels::{manifest_label_from_uri, to_assertion_uri},
     resource_store::{ResourceRef, ResourceResolver, ResourceStore},
     salt::DefaultSalt,
+    status_tracker::OneShotStatusTracker,
     store::Store,
-    utils::mime::format_to_mime,
-    AsyncSigner, ClaimGeneratorInfo, Signer,
+    utils::{forward_stream::ForwardStream, mime::format_to_mime},
+    validation_status::{self, ValidationStatus},
+    AsyncSigner, ClaimGeneratorInfo, Reader, Signer,
 };
 
 /// A Manifest Definition
@@ -199,6 +205,19 @@ pub struct Builder {
     // If true, the manifest store will not be embedded in the asset on sign
     pub no_embed: bool,
 
+    /// If true, sign an update manifest that adds to the existing manifest store of the
+    /// source without changing the asset content.
+    ///
+    /// The source is added as the parentOf ingredient if one has not been added, and
+    /// hard bindings, actions and other ingredients are not allowed.
+    #[serde(default)]
+    pub update_manifest: bool,
+
+    /// If true, signing fails with [`Error::ManifestLint`] when [`Builder::lint`] finds
+    /// problems in the manifest, otherwise they are logged as warnings.
+    #[serde(default)]
+    pub strict_lint: bool,
+
     /// container for binary assets (like thumbnails)
     #[serde(skip)]
     resources: ResourceStore,
@@ -224,6 +243,106 @@ impl Builder {
         })
     }
 
+    /// Creates a new builder for a derivative of an asset, with the asset as its parent.
+    ///
+    /// The asset is added as the parentOf ingredient, with its manifest store and thumbnail,
+    /// and a `c2pa.opened` action referencing that ingredient is added to the manifest.
+    /// The title of the asset's active manifest and the format are carried over.
+    /// # Arguments
+    /// * `format` - The format of the asset.
+    /// * `stream` - A stream to read the asset from.
+    /// # Returns
+    /// * A new [`Builder`].
+    /// # Example
+    /// ```no_run
+    /// # use c2pa::Result;
+    /// use c2pa::{create_signer, Builder, SigningAlg};
+    ///
+    /// # fn main() -> Result<()> {
+    /// let signer = create_signer::from_files(
+    ///     "tests/fixtures/certs/ps256.pub",
+    ///     "tests/fixtures/certs/ps256.pem",
+    ///     SigningAlg::Ps256,
+    ///     None,
+    /// )?;
+    /// let mut source = std::fs::File::open("tests/fixtures/CA.jpg")?;
+    /// let mut builder = Builder::from_parent("image/jpeg", &mut source)?;
+    /// // ... the editor writes the edited asset to `edited` ...
+    /// # let mut edited = std::fs::File::open("tests/fixtures/CA.jpg")?;
+    /// builder.sign(
+    ///     signer.as_ref(),
+    ///     "image/jpeg",
+    ///     &mut edited,
+    ///     &mut std::fs::File::create("edited.jpg")?,
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[async_generic(async_signature(format: &str, stream: &mut R))]
+    pub fn from_parent<R>(format: &str, stream: &mut R) -> Result<Self>
+    where
+        R: Read + Seek + Send,
+    {
+        let parent = if _sync {
+            Ingredient::from_stream(format, stream)
+        } else {
+            Ingredient::from_stream_async(format, stream).await
+        }?;
+
+        let title = match parent.manifest_data() {
+            Some(manifest_data) => {
+                Store::from_jumbf(&manifest_data, &mut OneShotStatusTracker::default())
+                    .ok()
+                    .and_then(|store| store.provenance_claim()?.title().cloned())
+            }
+            None => None,
+        };
+
+        Self::with_parent(parent, title)
+    }
+
+    /// Creates a new builder for a derivative of an asset already loaded with a [`Reader`].
+    ///
+    /// This is the same as [`Builder::from_parent`], but uses the validation results of
+    /// the reader instead of validating the asset's manifest store again.
+    /// # Arguments
+    /// * `reader` - The [`Reader`] loaded from the asset.
+    /// * `format` - The format of the asset.
+    /// * `stream` - A stream to read the asset from.
+    /// # Returns
+    /// * A new [`Builder`].
+    pub fn from_parent_reader<R>(reader: &Reader, format: &str, stream: &mut R) -> Result<Self>
+    where
+        R: Read + Seek + Send,
+    {
+        let parent = Ingredient::from_reader(reader, format, stream)?;
+        let title = reader
+            .active_manifest()
+            .and_then(|m| m.title())
+            .map(str::to_owned);
+
+        Self::with_parent(parent, title)
+    }
+
+    // Starts a manifest that opens the parent ingredient
+    fn with_parent(mut parent: Ingredient, title: Option<String>) -> Result<Self> {
+        parent.set_is_parent();
+        if let Some(title) = title.as_ref() {
+            parent.set_title(title);
+        }
+
+        let opened = Action::new(c2pa_action::OPENED).set_instance_id(parent.instance_id());
+
+        let mut builder = Self::default();
+        builder.definition.title = title;
+        builder.definition.format = format_to_mime(parent.format());
+        builder.definition.instance_id = default_instance_id();
+        builder.definition.ingredients.push(parent);
+        builder.add_assertion(labels::ACTIONS, &Actions::new().add_action(opened))?;
+
+        Ok(builder)
+    }
+
     /// Sets the MIME format for this [`Builder`].
     ///
     /// # Arguments
@@ -329,6 +448,143 @@ impl Builder {
         Ok(self.definition.ingredients.last_mut().unwrap()) // ok since we just added it
     }
 
+    /// Redacts an assertion from a manifest in one of the ingredients.
+    ///
+    /// The assertion is removed from the ingredient's manifest store when signing, and a
+    /// `c2pa.redacted` action referencing it is added to the manifest, except for update
+    /// manifests, which cannot contain actions.
+    /// # Arguments
+    ///
+    /// The label of the manifest being built is fixed on the first redaction, if it was not
+    /// set, so the manifest cannot redact from itself.
+    /// # Arguments
+    /// * `ingredient_label` - The label of the manifest containing the assertion, which can
+    ///   be any manifest in the manifest store of one of the ingredients.
+    /// * `assertion_label` - The label of the assertion to redact, e.g. `stds.exif`.
+    /// # Returns
+    /// * A mutable reference to the [`Builder`].
+    /// # Errors
+    /// * [`Error::ClaimSelfRedact`] if the manifest is the one being built.
+    /// * [`Error::ClaimDisallowedRedaction`] for actions and hard binding assertions.
+    /// * [`Error::AssertionRedactionNotFound`] if no ingredient has the assertion.
+    pub fn redact(&mut self, ingredient_label: &str, assertion_label: &str) -> Result<&mut Self> {
+        if self.claim_label() == ingredient_label {
+            return Err(Error::ClaimSelfRedact);
+        }
+
+        if [
+            labels::ACTIONS,
+            labels::DATA_HASH,
+            labels::BOX_HASH,
+            labels::BMFF_HASH,
+        ]
+        .iter()
+        .any(|label| assertion_label.starts_with(label))
+        {
+            return Err(Error::ClaimDisallowedRedaction);
+        }
+
+        // the assertion must exist in a manifest in the store of one of the ingredients
+        let relative_uri = format!("/{assertion_label}");
+        let mut found = false;
+        for ingredient in &self.definition.ingredients {
+            if let Some(store) = self.ingredient_store(ingredient)? {
+                found = store.get_claim(ingredient_label).is_some_and(|claim| {
+                    claim
+                        .assertions()
+                        .iter()
+                        .any(|assertion| assertion.url().ends_with(&relative_uri))
+                });
+                if found {
+                    break;
+                }
+            }
+        }
+        if !found {
+            return Err(Error::AssertionRedactionNotFound);
+        }
+
+        let uri = to_assertion_uri(ingredient_label, assertion_label);
+        let redactions = self.definition.redactions.get_or_insert_with(Vec::new);
+        if !redactions.contains(&uri) {
+            redactions.push(uri);
+        }
+        Ok(self)
+    }
+
+    // Returns the label the manifest will be signed with, generating it if it was not set
+    fn claim_label(&mut self) -> &str {
+        let vendor = self.definition.vendor.as_deref();
+        if self.definition.label.is_none() {
+            let label = Claim::new("", vendor).label().to_owned();
+            self.definition.label = Some(label);
+        }
+        self.definition.label.as_deref().unwrap_or_default()
+    }
+
+    // Loads the manifest store embedded in an ingredient, if it has one
+    fn ingredient_store(&self, ingredient: &Ingredient) -> Result<Option<Store>> {
+        let Some(manifest_data_ref) = ingredient.manifest_data_ref() else {
+            return Ok(None);
+        };
+
+        let manifest_data = ingredient
+            .resources()
+            .get(&manifest_data_ref.identifier)
+            .or_else(|_| self.resources.get(&manifest_data_ref.identifier))?;
+
+        Store::from_jumbf(&manifest_data, &mut OneShotStatusTracker::default()).map(Some)
+    }
+
+    // Returns the redactions that apply to an ingredient, labels only apply to the parent
+    fn ingredient_redactions(&self, ingredient: &Ingredient) -> Result<Option<Vec<String>>> {
+        let Some(redactions) = self.definition.redactions.as_ref() else {
+            return Ok(None);
+        };
+
+        let store = match redactions
+            .iter()
+            .any(|r| manifest_label_from_uri(r).is_some())
+        {
+            true => self.ingredient_store(ingredient)?,
+            false => None,
+        };
+
+        Ok(Some(
+            redactions
+                .iter()
+                .filter(|r| match manifest_label_from_uri(r) {
+                    Some(label) => store
+                        .as_ref()
+                        .is_some_and(|store| store.get_claim(&label).is_some()),
+                    None => true,
+                })
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    // Creates the c2pa.redacted actions for the redactions in the definition
+    fn redacted_actions(&self) -> Result<Vec<Action>> {
+        let parent_label = self
+            .definition
+            .ingredients
+            .iter()
+            .find(|i| i.is_parent())
+            .and_then(|i| i.active_manifest());
+
+        self.definition
+            .redactions
+            .iter()
+            .flatten()
+            .filter_map(|r| match manifest_label_from_uri(r) {
+                Some(_) => Some(r.to_owned()),
+                None => parent_label.map(|label| to_assertion_uri(label, r)),
+            })
+            .map(|uri| Action::new(c2pa_action::REDACTED).set_parameter("redacted", uri))
+            .collect()
+    }
+
     /// Adds a resource to the manifest.
     /// The id should match up with an identifier in the manifest.
     /// # Arguments
@@ -452,8 +708,135 @@ impl Builder {
         Ok(builder)
     }
 
+    /// Checks the manifest for problems before it is signed.
+    ///
+    /// Actions that act on an ingredient must reference one that is in the manifest,
+    /// the first action must be `c2pa.created` or `c2pa.opened`, `c2pa.created` actions
+    /// must have a `digitalSourceType`, and there can only be one parentOf ingredient.
+    /// The actions checked are the ones that will be signed, including the
+    /// `c2pa.redacted` actions added for [`Builder::redact`].
+    /// # Returns
+    /// * A [`ValidationStatus`] for each problem found, empty if there are none.
+    /// # Errors
+    /// * If an actions assertion cannot be decoded.
+    pub fn lint(&self) -> Result<Vec<ValidationStatus>> {
+        let definition = &self.definition;
+        let mut problems = Vec::new();
+
+        // the redacted actions are added to the first actions assertion, as in to_claim
+        let mut redacted_actions = match self.update_manifest {
+            true => Vec::new(),
+            false => self.redacted_actions()?,
+        };
+        let mut signed_actions = Vec::new();
+        for manifest_assertion in &definition.assertions {
+            if manifest_assertion.label.starts_with(Actions::LABEL) {
+                let actions: Actions = manifest_assertion.to_assertion()?;
+                let mut actions = actions.actions().to_vec();
+                actions.append(&mut redacted_actions);
+                signed_actions.push((manifest_assertion.label.as_str(), actions));
+            }
+        }
+        if !redacted_actions.is_empty() {
+            signed_actions.push((Actions::LABEL, redacted_actions));
+        }
+
+        let mut first_action = true;
+        for (label, actions) in &signed_actions {
+            for action in actions {
+                let name = action.action();
+                let malformed = |explanation: String| {
+                    ValidationStatus::new(validation_status::ACTION_ASSERTION_MALFORMED)
+                        .set_url(label.to_string())
+                        .set_explanation(explanation)
+                };
+                let mismatch = |explanation: String| {
+                    ValidationStatus::new(validation_status::ACTION_ASSERTION_INGREDIENT_MISMATCH)
+                        .set_url(label.to_string())
+                        .set_explanation(explanation)
+                };
+
+                if first_action && name != c2pa_action::CREATED && name != c2pa_action::OPENED {
+                    problems.push(malformed(format!(
+                        "the first action must be {} or {}, not {name}",
+                        c2pa_action::CREATED,
+                        c2pa_action::OPENED
+                    )));
+                }
+                first_action = false;
+
+                if name == c2pa_action::CREATED && action.source_type().is_none() {
+                    problems.push(malformed(format!("{name} requires a digitalSourceType")));
+                }
+
+                match action.instance_id() {
+                    Some(id) => match definition
+                        .ingredients
+                        .iter()
+                        .find(|i| i.instance_id() == id)
+                    {
+                        Some(ingredient) => {
+                            if name == c2pa_action::OPENED && !ingredient.is_parent() {
+                                problems.push(mismatch(format!(
+                                    "{name} ingredient {id} is not the parentOf ingredient"
+                                )));
+                            }
+                        }
+                        None => problems.push(mismatch(format!(
+                            "{name} references unknown ingredient {id}"
+                        ))),
+                    },
+                    None => {
+                        let needs_ingredient = [
+                            c2pa_action::OPENED,
+                            c2pa_action::PLACED,
+                            c2pa_action::REMOVED,
+                        ]
+                        .contains(&name);
+                        let has_ingredient = action.get_parameter("ingredient").is_some()
+                            || action.get_parameter("ingredients").is_some();
+                        if needs_ingredient && !has_ingredient {
+                            problems
+                                .push(mismatch(format!("{name} does not reference an ingredient")));
+                        }
+                    }
+                }
+            }
+        }
+
+        let parents = definition
+            .ingredients
+            .iter()
+            .filter(|i| i.is_parent())
+            .count();
+        if parents > 1 {
+            problems.push(
+                ValidationStatus::new(validation_status::MANIFEST_MULTIPLE_PARENTS)
+                    .set_explanation(format!(
+                        "found {parents} parentOf ingredients, only one is allowed"
+                    )),
+            );
+        }
+
+        Ok(problems)
+    }
+
     // Convert a Manifest into a Claim
     fn to_claim(&self) -> Result<Claim> {
+        let problems = self.lint()?;
+        if !problems.is_empty() {
+            if self.strict_lint {
+                return Err(Error::ManifestLint(problems));
+            }
+            for problem in &problems {
+                log::warn!(
+                    "manifest check {}: {}",
+                    problem.code(),
+                    problem.explanation().unwrap_or_default()
+                );
+            }
+        }
+
         let definition = &self.definition;
         let mut claim_generator_info = definition.claim_generator_info.clone();
         // add the default claim generator info for this library
@@ -497,6 +880,10 @@ impl Builder {
             claim.set_external_manifest()
         }
 
+        if self.update_manifest {
+            claim.set_update_manifest(true);
+        }
+
         if let Some(title) = definition.title.as_ref() {
             claim.set_title(Some(title.to_owned()));
         }
@@ -523,7 +910,7 @@ impl Builder {
             //let ingredient = ingredient_builder.build(self)?;
             let uri = ingredient.add_to_claim(
                 &mut claim,
-                definition.redactions.clone(),
+                self.ingredient_redactions(ingredient)?,
                 Some(&self.resources),
             )?;
             ingredient_map.insert(ingredient.instance_id().to_string(), uri);
@@ -531,6 +918,12 @@ impl Builder {
 
         let salt = DefaultSalt::default();
 
+        // record redactions in the actions, update manifests cannot have actions
+        let mut redacted_actions = match self.update_manifest {
+            true => Vec::new(),
+            false => self.redacted_actions()?,
+        };
+
         // add any additional assertions
         for manifest_assertion in &definition.assertions {
             match manifest_assertion.label.as_str() {
@@ -623,6 +1016,10 @@ impl Builder {
                         }
                     }
 
+                    for action in redacted_actions.drain(..) {
+                        actions = actions.add_action(action);
+                    }
+
                     claim.add_assertion(&actions)
                 }
                 CreativeWork::LABEL => {
@@ -634,6 +1031,14 @@ impl Builder {
                     let exif: Exif = manifest_assertion.to_assertion()?;
                     claim.add_assertion_with_salt(&exif, &salt)
                 }
+                DataHash::LABEL => {
+                    let dh: DataHash = manifest_assertion.to_assertion()?;
+                    claim.add_assertion(&dh)
+                }
+                BoxHash::LABEL => {
+                    let bh: BoxHash = manifest_assertion.to_assertion()?;
+                    claim.add_assertion(&bh)
+                }
                 _ => match &manifest_assertion.data {
                     AssertionData::Json(value) => claim.add_assertion_with_salt(
                         &User::new(&manifest_assertion.label, &serde_json::to_string(&value)?),
@@ -647,12 +1052,23 @@ impl Builder {
             }?;
         }
 
+        if !redacted_actions.is_empty() {
+            let actions = redacted_actions
+                .into_iter()
+                .fold(Actions::new(), |actions, action| actions.add_action(action));
+            claim.add_assertion(&actions)?;
+        }
+
         Ok(claim)
     }
 
     // Convert a Manifest into a Store
     fn to_store(&self) -> Result<Store> {
         let claim = self.to_claim()?;
+        if claim.update_manifest() {
+            // the parent's manifests are added from the parent ingredient on commit
+            Store::check_update_manifest(&claim)?;
+        }
         // commit the claim
         let mut store = Store::new();
         let _provenance = store.commit_claim(claim)?;
@@ -716,9 +1132,23 @@ impl Builder {
         // todo:: read instance_id from xmp from stream ?
         self.definition.instance_id = format!("xmp:iid:{}", Uuid::new_v4());
 
-        // generate thumbnail if we don't already have one
-        #[cfg(feature = "add_thumbnails")]
-        self.maybe_add_thumbnail(&format, source)?;
+        if self.update_manifest {
+            // an update manifest is always bound to the manifest it updates
+            if !self.definition.ingredients.iter().any(|i| i.is_parent()) {
+                let mut parent = if _sync {
+                    Ingredient::from_stream(&format, source)
+                } else {
+                    Ingredient::from_stream_async(&format, source).await
+                }?;
+                parent.set_is_parent();
+                self.definition.ingredients.push(parent);
+                source.rewind()?;
+            }
+        } else {
+            // generate thumbnail if we don't already have one
+            #[cfg(feature = "add_thumbnails")]
+            self.maybe_add_thumbnail(&format, source)?;
+        }
 
         // convert the manifest to a store
         let mut store = self.to_store()?;
@@ -733,6 +1163,153 @@ impl Builder {
         }
     }
 
+    /// Embed a signed manifest into a stream, reading the asset from a source that cannot seek.
+    /// # Arguments
+    /// * `format` - The format of the stream
+    /// * `source` - The stream to read from, such as a network response body
+    /// * `dest` - The stream to write to
+    /// * `signer` - The signer to use
+    /// # Returns
+    /// * The bytes of c2pa_manifest that was embedded.
+    /// # Errors
+    /// * If the manifest cannot be signed, or the source is larger than the `core.max_spool_size` setting.
+    /// # Note
+    /// The source is read once, front to back. JPEG and MP3 are hashed and copied to `dest`
+    /// in a single pass, other formats are spooled first, see
+    /// [`Reader::from_read`](crate::Reader::from_read).
+    #[async_generic(async_signature(
+        &mut self,
+        signer: &dyn AsyncSigner,
+        format: &str,
+        source: R,
+        dest: &mut W,
+    ))]
+    pub fn sign_from_read<R, W>(
+        &mut self,
+        signer: &dyn Signer,
+        format: &str,
+        source: R,
+        dest: &mut W,
+    ) -> Result<Vec<u8>>
+    where
+        R: Read + Send,
+        W: Write + Read + Seek + Send,
+    {
+        let mut source = ForwardStream::for_format(format, source)?;
+        let result = if _sync {
+            self.sign(signer, format, &mut source, dest)
+        } else {
+            self.sign_async(signer, format, &mut source, dest).await
+        };
+        source.check_single_pass()?;
+        result
+    }
+
+    /// Creates a placeholder for a data hashed manifest, ready to be written into an asset.
+    ///
+    /// The placeholder is the size of the signed manifest returned by
+    /// [`Builder::sign_data_hashed_embeddable`], so the asset can be built around it and the
+    /// placeholder overwritten with the signed manifest once the asset hash is known.
+    ///
+    /// The format recorded in the manifest is the one set with [`Builder::set_format`].
+    /// # Arguments
+    /// * `reserve_size` - The space to reserve for the signature, see [`Signer::reserve_size`].
+    /// * `format` - The format to compose the placeholder for.
+    /// # Returns
+    /// * The placeholder composed for the format, e.g. a set of APP11 segments for JPEG.
+    /// # Errors
+    /// * [`Error::UnsupportedType`] if the format does not support composed manifests.
+    pub fn data_hashed_placeholder(
+        &mut self,
+        reserve_size: usize,
+        format: &str,
+    ) -> Result<Vec<u8>> {
+        let mut store = self.to_store()?;
+        store.get_data_hashed_manifest_placeholder(reserve_size, format)
+    }
+
+    /// Signs a data hashed manifest using a hash of the asset supplied by the caller.
+    ///
+    /// The exclusions of `data_hash` must cover the placeholder written into the asset, and
+    /// its hash must be calculated over the asset with those exclusions.
+    /// Use the same definition, format and signer as for the placeholder.
+    /// # Arguments
+    /// * `signer` - The signer to use.
+    /// * `data_hash` - The hash of the asset.
+    /// * `format` - The format to compose the manifest for, `application/c2pa` for none.
+    /// # Returns
+    /// * The signed manifest composed for the format, the same size as the placeholder from
+    ///   [`Builder::data_hashed_placeholder`] when using the same signer.
+    /// # Errors
+    /// * [`Error::UnsupportedType`] if the format does not support composed manifests.
+    #[async_generic(async_signature(
+        &mut self,
+        signer: &dyn AsyncSigner,
+        data_hash: &DataHash,
+        format: &str,
+    ))]
+    pub fn sign_data_hashed_embeddable(
+        &mut self,
+        signer: &dyn Signer,
+        data_hash: &DataHash,
+        format: &str,
+    ) -> Result<Vec<u8>> {
+        // the placeholder is added to the store so the definition is left as it was
+        let mut store = self.to_store()?;
+        store.add_data_hash_placeholder()?;
+        if _sync {
+            store.get_data_hashed_embeddable_manifest(data_hash, signer, format, None)
+        } else {
+            store
+                .get_data_hashed_embeddable_manifest_async(data_hash, signer, format, None)
+                .await
+        }
+    }
+
+    /// Signs a box hashed manifest.
+    ///
+    /// The manifest must contain a [`BoxHash`] assertion, added with
+    /// [`Builder::add_assertion`], that hashes the boxes of the asset.
+    /// # Arguments
+    /// * `signer` - The signer to use.
+    /// * `format` - The format to compose the manifest for, `application/c2pa` for none.
+    /// # Returns
+    /// * The signed manifest composed for the format.
+    /// # Errors
+    /// * [`Error::BadParam`] if the manifest does not have exactly one hard binding, a box hash.
+    /// * [`Error::UnsupportedType`] if the format does not support composed manifests.
+    #[async_generic(async_signature(
+        &mut self,
+        signer: &dyn AsyncSigner,
+        format: &str,
+    ))]
+    pub fn sign_box_hashed_embeddable(
+        &mut self,
+        signer: &dyn Signer,
+        format: &str,
+    ) -> Result<Vec<u8>> {
+        let mut store = self.to_store()?;
+        let manifest_bytes = if _sync {
+            store.get_box_hashed_embeddable_manifest(signer)
+        } else {
+            store.get_box_hashed_embeddable_manifest_async(signer).await
+        }?;
+        Self::composed_manifest(&manifest_bytes, format)
+    }
+
+    /// Formats a signed manifest for embedding in an asset.
+    ///
+    /// For example, JPEG manifests are split into APP11 segments and PNG manifests are
+    /// wrapped in a caBX chunk. Use `application/c2pa` to get the manifest store itself.
+    /// # Arguments
+    /// * `manifest_bytes` - The signed manifest store.
+    /// * `format` - The format of the asset.
+    /// # Errors
+    /// * [`Error::UnsupportedType`] if the format does not support composed manifests.
+    pub fn composed_manifest(manifest_bytes: &[u8], format: &str) -> Result<Vec<u8>> {
+        Store::get_composed_manifest(manifest_bytes, format)
+    }
+
     #[cfg(feature = "file_io")]
     /// Sign a file using a supplied signer.
     /// # Arguments
@@ -787,7 +1364,7 @@ mod tests {
     use wasm_bindgen_test::*;
 
     use super::*;
-    use crate::{utils::test::temp_signer, Reader};
+    use crate::{utils::test::temp_signer, HashRange, Reader};
     #[cfg(target_arch = "wasm32")]
     wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
@@ -1146,4 +1723,631 @@ mod tests {
         println!("{}", reader.json());
         assert!(reader.validation_status().is_none());
     }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_update_manifest() {
+        use crate::{jumbf_io::load_jumbf_from_stream, status_tracker::OneShotStatusTracker};
+
+        let format = "image/jpeg";
+        let signer = temp_signer();
+
+        // sign a clean image with a standard manifest
+        let mut source = Cursor::new(TEST_IMAGE_CLEAN);
+        let mut signed = Cursor::new(Vec::new());
+        let mut builder = Builder::from_json(&json!({"title": "original"}).to_string()).unwrap();
+        builder
+            .sign(signer.as_ref(), format, &mut source, &mut signed)
+            .unwrap();
+
+        // then add publication metadata with an update manifest
+        let mut builder = Builder::from_json(&json!({"title": "published"}).to_string()).unwrap();
+        builder.update_manifest = true;
+        builder
+            .add_assertion("org.test.publication", &json!({"outlet": "test"}))
+            .unwrap();
+
+        signed.rewind().unwrap();
+        let mut updated = Cursor::new(Vec::new());
+        builder
+            .sign(signer.as_ref(), format, &mut signed, &mut updated)
+            .unwrap();
+
+        updated.rewind().unwrap();
+        let reader = Reader::from_stream(format, &mut updated).unwrap();
+        assert!(reader.validation_status().is_none());
+
+        // the parent ingredient was added and refers to the original manifest
+        let manifest = reader.active_manifest().unwrap();
+        assert_eq!(manifest.ingredients().len(), 1);
+        let parent = &manifest.ingredients()[0];
+        assert!(parent.is_parent());
+        let parent_label = parent.active_manifest().unwrap();
+        assert_eq!(
+            reader.get_manifest(parent_label).unwrap().title(),
+            Some("original")
+        );
+
+        // and the active manifest is stored as an update manifest
+        updated.rewind().unwrap();
+        let jumbf = load_jumbf_from_stream(format, &mut updated).unwrap();
+        let store = Store::from_jumbf(&jumbf, &mut OneShotStatusTracker::default()).unwrap();
+        assert!(store.provenance_claim().unwrap().update_manifest());
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_update_manifest_rules() {
+        let format = "image/jpeg";
+        let signer = temp_signer();
+
+        // actions are not allowed
+        let mut builder = Builder::from_json(&json!({}).to_string()).unwrap();
+        builder.update_manifest = true;
+        builder
+            .add_assertion(
+                labels::ACTIONS,
+                &Actions::new().add_action(crate::assertions::Action::new("c2pa.edited")),
+            )
+            .unwrap();
+        let result = builder.sign(
+            signer.as_ref(),
+            format,
+            &mut Cursor::new(TEST_IMAGE),
+            &mut Cursor::new(Vec::new()),
+        );
+        assert!(matches!(result, Err(Error::ClaimInvalidContent)));
+
+        // the parent must have a manifest to update
+        let mut builder = Builder::from_json(&json!({}).to_string()).unwrap();
+        builder.update_manifest = true;
+        let result = builder.sign(
+            signer.as_ref(),
+            format,
+            &mut Cursor::new(TEST_IMAGE_CLEAN),
+            &mut Cursor::new(Vec::new()),
+        );
+        assert!(matches!(result, Err(Error::IngredientNotFound)));
+
+        // and it must be the only ingredient
+        let mut builder = Builder::from_json(&json!({}).to_string()).unwrap();
+        builder.update_manifest = true;
+        builder
+            .add_ingredient(parent_json(), format, &mut Cursor::new(TEST_IMAGE))
+            .unwrap();
+        builder
+            .add_ingredient(
+                json!({"title": "component", "relationship": "componentOf"}).to_string(),
+                format,
+                &mut Cursor::new(TEST_IMAGE),
+            )
+            .unwrap();
+        let result = builder.sign(
+            signer.as_ref(),
+            format,
+            &mut Cursor::new(TEST_IMAGE),
+            &mut Cursor::new(Vec::new()),
+        );
+        assert!(matches!(result, Err(Error::ClaimInvalidContent)));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_redact() {
+        let format = "image/jpeg";
+        let signer = temp_signer();
+
+        // sign a clean image with an assertion to redact later
+        let mut builder = Builder::from_json(&json!({"title": "original"}).to_string()).unwrap();
+        builder
+            .add_assertion("org.test.secret", &json!({"location": "home"}))
+            .unwrap();
+        let mut signed = Cursor::new(Vec::new());
+        builder
+            .sign(
+                signer.as_ref(),
+                format,
+                &mut Cursor::new(TEST_IMAGE_CLEAN),
+                &mut signed,
+            )
+            .unwrap();
+
+        // use it as the parent of a new manifest
+        let mut builder = Builder::from_json(&json!({"title": "redacted"}).to_string()).unwrap();
+        signed.rewind().unwrap();
+        let parent_label = builder
+            .add_ingredient(parent_json(), format, &mut signed)
+            .unwrap()
+            .active_manifest()
+            .unwrap()
+            .to_owned();
+
+        // actions, hard bindings and unknown assertions are rejected up front
+        assert!(matches!(
+            builder.redact(&parent_label, labels::ACTIONS),
+            Err(Error::ClaimDisallowedRedaction)
+        ));
+        assert!(matches!(
+            builder.redact(&parent_label, labels::DATA_HASH),
+            Err(Error::ClaimDisallowedRedaction)
+        ));
+        assert!(matches!(
+            builder.redact(&parent_label, "org.test.missing"),
+            Err(Error::AssertionRedactionNotFound)
+        ));
+
+        builder.redact(&parent_label, "org.test.secret").unwrap();
+
+        signed.rewind().unwrap();
+        let mut dest = Cursor::new(Vec::new());
+        builder
+            .sign(signer.as_ref(), format, &mut signed, &mut dest)
+            .unwrap();
+
+        dest.rewind().unwrap();
+        let reader = Reader::from_stream(format, &mut dest).unwrap();
+        assert!(reader.validation_status().is_none());
+
+        // the assertion is gone from the parent manifest
+        let parent = reader.get_manifest(&parent_label).unwrap();
+        assert!(parent
+            .assertions()
+            .iter()
+            .all(|a| a.label() != "org.test.secret"));
+
+        // and the redaction is recorded as an action
+        let actions: Actions = reader
+            .active_manifest()
+            .unwrap()
+            .find_assertion(labels::ACTIONS)
+            .unwrap();
+        let redacted = actions
+            .actions()
+            .iter()
+            .find(|a| a.action() == c2pa_action::REDACTED)
+            .unwrap();
+        assert_eq!(
+            redacted.get_parameter("redacted"),
+            Some(&serde_cbor::Value::Text(to_assertion_uri(
+                &parent_label,
+                "org.test.secret"
+            )))
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_redact_ingredient_history() {
+        let format = "image/jpeg";
+        let signer = temp_signer();
+
+        // sign an image with an assertion, then sign it again so it is no longer active
+        let mut builder = Builder::from_json(&json!({"title": "original"}).to_string()).unwrap();
+        builder
+            .add_assertion("org.test.secret", &json!({"location": "home"}))
+            .unwrap();
+        let mut original = Cursor::new(Vec::new());
+        builder
+            .sign(
+                signer.as_ref(),
+                format,
+                &mut Cursor::new(TEST_IMAGE_CLEAN),
+                &mut original,
+            )
+            .unwrap();
+        original.rewind().unwrap();
+        let original_label = Reader::from_stream(format, &mut original)
+            .unwrap()
+            .active_label()
+            .unwrap()
+            .to_owned();
+
+        let mut builder = Builder::from_json(&json!({"title": "edited"}).to_string()).unwrap();
+        original.rewind().unwrap();
+        builder
+            .add_ingredient(parent_json(), format, &mut original)
+            .unwrap();
+        let mut edited = Cursor::new(Vec::new());
+        original.rewind().unwrap();
+        builder
+            .sign(signer.as_ref(), format, &mut original, &mut edited)
+            .unwrap();
+
+        // redact from the earlier manifest of the parent
+        let mut builder = Builder::from_json(&json!({"title": "redacted"}).to_string()).unwrap();
+        edited.rewind().unwrap();
+        let parent_label = builder
+            .add_ingredient(parent_json(), format, &mut edited)
+            .unwrap()
+            .active_manifest()
+            .unwrap()
+            .to_owned();
+        assert_ne!(parent_label, original_label);
+        builder.redact(&original_label, "org.test.secret").unwrap();
+
+        // the manifest being built cannot redact from itself
+        let own_label = builder.definition.label.clone().unwrap();
+        assert!(matches!(
+            builder.redact(&own_label, "org.test.secret"),
+            Err(Error::ClaimSelfRedact)
+        ));
+
+        edited.rewind().unwrap();
+        let mut dest = Cursor::new(Vec::new());
+        builder
+            .sign(signer.as_ref(), format, &mut edited, &mut dest)
+            .unwrap();
+
+        dest.rewind().unwrap();
+        let reader = Reader::from_stream(format, &mut dest).unwrap();
+        assert!(reader.validation_status().is_none());
+        assert_eq!(reader.active_label(), Some(own_label.as_str()));
+        assert!(reader
+            .get_manifest(&original_label)
+            .unwrap()
+            .assertions()
+            .iter()
+            .all(|a| a.label() != "org.test.secret"));
+    }
+
+    #[test]
+    #[cfg(feature = "file_io")]
+    fn test_builder_data_hashed_embeddable() {
+        use crate::utils::test::{fixture_path, write_jpeg_placeholder_file};
+
+        let signer = temp_signer();
+        let mut builder = Builder::from_json(&json!({"title": "embeddable"}).to_string()).unwrap();
+        builder.set_format("image/jpeg");
+
+        let placeholder = builder
+            .data_hashed_placeholder(signer.reserve_size(), "jpeg")
+            .unwrap();
+
+        // build the asset around the placeholder
+        let mut asset = Cursor::new(Vec::new());
+        let offset =
+            write_jpeg_placeholder_file(&placeholder, &fixture_path("cloud.jpg"), &mut asset, None)
+                .unwrap();
+
+        // hash the asset excluding the placeholder
+        let mut dh = DataHash::new("source_hash", "sha256");
+        dh.add_exclusion(HashRange::new(offset, placeholder.len()));
+        asset.rewind().unwrap();
+        dh.gen_hash_from_stream(&mut asset).unwrap();
+
+        let signed = builder
+            .sign_data_hashed_embeddable(signer.as_ref(), &dh, "image/jpeg")
+            .unwrap();
+        assert_eq!(signed.len(), placeholder.len());
+
+        asset.get_mut()[offset..offset + signed.len()].copy_from_slice(&signed);
+
+        asset.rewind().unwrap();
+        let reader = Reader::from_stream("image/jpeg", &mut asset).unwrap();
+        assert!(reader.validation_status().is_none());
+        assert_eq!(
+            reader.active_manifest().unwrap().title().unwrap(),
+            "embeddable"
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_sign_after_data_hashed_placeholder() {
+        let signer = temp_signer();
+        let mut builder = Builder::from_json(&json!({"title": "embeddable"}).to_string()).unwrap();
+        builder.set_format("image/jpeg");
+
+        builder
+            .data_hashed_placeholder(signer.reserve_size(), "jpeg")
+            .unwrap();
+        assert!(builder.definition.assertions.is_empty());
+
+        // the placeholder hash must not end up in a normally signed manifest
+        let mut source = Cursor::new(TEST_IMAGE_CLEAN);
+        let mut dest = Cursor::new(Vec::new());
+        builder
+            .sign(signer.as_ref(), "image/jpeg", &mut source, &mut dest)
+            .unwrap();
+
+        dest.rewind().unwrap();
+        let reader = Reader::from_stream("image/jpeg", &mut dest).unwrap();
+        assert!(reader.validation_status().is_none());
+    }
+
+    #[cfg(all(feature = "file_io", feature = "openssl_sign"))]
+    #[actix::test]
+    async fn test_builder_data_hashed_embeddable_async() {
+        use crate::utils::test::{fixture_path, temp_async_signer, write_jpeg_placeholder_file};
+
+        let signer = temp_async_signer();
+        let mut builder = Builder::from_json(&json!({"title": "embeddable"}).to_string()).unwrap();
+        builder.set_format("image/jpeg");
+
+        let placeholder = builder
+            .data_hashed_placeholder(signer.reserve_size(), "jpeg")
+            .unwrap();
+
+        let mut asset = Cursor::new(Vec::new());
+        let offset =
+            write_jpeg_placeholder_file(&placeholder, &fixture_path("cloud.jpg"), &mut asset, None)
+                .unwrap();
+
+        let mut dh = DataHash::new("source_hash", "sha256");
+        dh.add_exclusion(HashRange::new(offset, placeholder.len()));
+        asset.rewind().unwrap();
+        dh.gen_hash_from_stream(&mut asset).unwrap();
+
+        // sign without composing, then compose for the asset
+        let signed = builder
+            .sign_data_hashed_embeddable_async(signer.as_ref(), &dh, "application/c2pa")
+            .await
+            .unwrap();
+        let signed = Builder::composed_manifest(&signed, "image/jpeg").unwrap();
+        assert_eq!(signed.len(), placeholder.len());
+
+        asset.get_mut()[offset..offset + signed.len()].copy_from_slice(&signed);
+
+        asset.rewind().unwrap();
+        let reader = Reader::from_stream("image/jpeg", &mut asset).unwrap();
+        assert!(reader.validation_status().is_none());
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_box_hashed_embeddable() {
+        let asset_bytes = include_bytes!("../tests/fixtures/boxhash.jpg");
+        let box_hash: BoxHash =
+            serde_json::from_slice(include_bytes!("../tests/fixtures/boxhash.json")).unwrap();
+
+        let signer = temp_signer();
+        let mut builder = Builder::from_json(&json!({"title": "embeddable"}).to_string()).unwrap();
+        builder.set_format("image/jpeg");
+
+        // a box hash is required
+        assert!(matches!(
+            builder.sign_box_hashed_embeddable(signer.as_ref(), "application/c2pa"),
+            Err(Error::BadParam(_))
+        ));
+
+        builder.add_assertion(labels::BOX_HASH, &box_hash).unwrap();
+
+        let manifest_bytes = builder
+            .sign_box_hashed_embeddable(signer.as_ref(), "application/c2pa")
+            .unwrap();
+
+        let reader = Reader::from_manifest_data_and_stream(
+            &manifest_bytes,
+            "image/jpeg",
+            Cursor::new(asset_bytes),
+        )
+        .unwrap();
+        assert!(reader.validation_status().is_none());
+
+        // and composed for embedding in a JPEG
+        let composed = Builder::composed_manifest(&manifest_bytes, "image/jpeg").unwrap();
+        assert!(composed.len() > manifest_bytes.len());
+        assert!(matches!(
+            Builder::composed_manifest(&manifest_bytes, "text/unknown"),
+            Err(Error::UnsupportedType)
+        ));
+    }
+
+    // signs a clean image to use as the parent of an edit
+    #[cfg(not(target_arch = "wasm32"))]
+    fn signed_parent(format: &str, title: &str) -> Cursor<Vec<u8>> {
+        let mut builder = Builder::from_json(&json!({ "title": title }).to_string()).unwrap();
+        builder
+            .set_thumbnail("image/jpeg", &mut Cursor::new(TEST_IMAGE_CLEAN))
+            .unwrap();
+        let mut signed = Cursor::new(Vec::new());
+        builder
+            .sign(
+                temp_signer().as_ref(),
+                format,
+                &mut Cursor::new(TEST_IMAGE_CLEAN),
+                &mut signed,
+            )
+            .unwrap();
+        signed.rewind().unwrap();
+        signed
+    }
+
+    // signs an edit of the parent and checks it is linked with an opened action
+    #[cfg(not(target_arch = "wasm32"))]
+    fn check_opened(mut builder: Builder, format: &str, parent: &mut Cursor<Vec<u8>>) {
+        assert_eq!(builder.definition.title.as_deref(), Some("original"));
+        assert_eq!(builder.definition.format, format);
+        assert_eq!(builder.definition.ingredients.len(), 1);
+        assert!(builder.definition.ingredients[0].is_parent());
+        assert!(builder.definition.ingredients[0].thumbnail_ref().is_some());
+
+        parent.rewind().unwrap();
+        let mut dest = Cursor::new(Vec::new());
+        builder
+            .sign(temp_signer().as_ref(), format, parent, &mut dest)
+            .unwrap();
+
+        dest.rewind().unwrap();
+        let reader = Reader::from_stream(format, &mut dest).unwrap();
+        assert!(reader.validation_status().is_none());
+
+        let manifest = reader.active_manifest().unwrap();
+        assert_eq!(manifest.title(), Some("original"));
+        let parent = &manifest.ingredients()[0];
+        assert!(parent.is_parent());
+        assert_eq!(parent.title(), "original");
+
+        let actions: Actions = manifest.find_assertion(labels::ACTIONS).unwrap();
+        let opened = &actions.actions()[0];
+        assert_eq!(opened.action(), c2pa_action::OPENED);
+        assert!(opened.get_parameter("ingredient").is_some());
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_from_parent() {
+        let format = "image/jpeg";
+        let mut parent = signed_parent(format, "original");
+
+        let builder = Builder::from_parent(format, &mut parent).unwrap();
+        check_opened(builder, format, &mut parent);
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_builder_from_parent_reader() {
+        let format = "image/jpeg";
+        let mut parent = signed_parent(format, "original");
+
+        let reader = Reader::from_stream(format, &mut parent).unwrap();
+        let builder = Builder::from_parent_reader(&reader, format, &mut parent).unwrap();
+        check_opened(builder, format, &mut parent);
+    }
+
+    #[test]
+    fn test_builder_lint() {
+        let definition = json!({
+            "ingredients": [
+                { "title": "A", "format": "image/jpeg", "instance_id": "a", "relationship": "parentOf" },
+                { "title": "B", "format": "image/jpeg", "instance_id": "b", "relationship": "parentOf" },
+                { "title": "C", "format": "image/jpeg", "instance_id": "c", "relationship": "componentOf" }
+            ],
+            "assertions": [
+                {
+                    "label": "c2pa.actions",
+                    "data": {
+                        "actions": [
+                            { "action": "c2pa.edited" },
+                            { "action": "c2pa.created" },
+                            { "action": "c2pa.opened", "instanceId": "c" },
+                            { "action": "c2pa.placed", "instanceId": "missing" },
+                            { "action": "c2pa.removed" }
+                        ]
+                    }
+                }
+            ]
+        })
+        .to_string();
+
+        let mut builder = Builder::from_json(&definition).unwrap();
+        let problems = builder.lint().unwrap();
+        let codes: Vec<&str> = problems.iter().map(|p| p.code()).collect();
+        assert_eq!(
+            codes,
+            [
+                validation_status::ACTION_ASSERTION_MALFORMED, // edited is first
+                validation_status::ACTION_ASSERTION_MALFORMED, // created has no source type
+                validation_status::ACTION_ASSERTION_INGREDIENT_MISMATCH, // opened a component
+                validation_status::ACTION_ASSERTION_INGREDIENT_MISMATCH, // placed is missing
+                validation_status::ACTION_ASSERTION_INGREDIENT_MISMATCH, // removed nothing
+                validation_status::MANIFEST_MULTIPLE_PARENTS,
+            ]
+        );
+        assert!(problems[..5]
+            .iter()
+            .all(|p| p.url() == Some(labels::ACTIONS)));
+
+        // strict mode refuses to sign
+        builder.strict_lint = true;
+        let result = builder.sign(
+            temp_signer().as_ref(),
+            "image/jpeg",
+            &mut Cursor::new(TEST_IMAGE),
+            &mut Cursor::new(Vec::new()),
+        );
+        assert!(
+            matches!(result, Err(Error::ManifestLint(statuses)) if statuses.len() == problems.len())
+        );
+    }
+
+    #[test]
+    fn test_builder_lint_redact() {
+        let format = "image/jpeg";
+        let signer = temp_signer();
+
+        let mut builder = Builder::from_json(&json!({"title": "original"}).to_string()).unwrap();
+        builder
+            .add_assertion("org.test.secret", &json!({"location": "home"}))
+            .unwrap();
+        let mut signed = Cursor::new(Vec::new());
+        builder
+            .sign(
+                signer.as_ref(),
+                format,
+                &mut Cursor::new(TEST_IMAGE_CLEAN),
+                &mut signed,
+            )
+            .unwrap();
+
+        let mut builder = Builder::from_json(&json!({"title": "redacted"}).to_string()).unwrap();
+        builder.strict_lint = true;
+        signed.rewind().unwrap();
+        let parent = builder
+            .add_ingredient(parent_json(), format, &mut signed)
+            .unwrap();
+        let parent_label = parent.active_manifest().unwrap().to_owned();
+        let parent_id = parent.instance_id().to_owned();
+        builder.redact(&parent_label, "org.test.secret").unwrap();
+
+        // the only actions are the c2pa.redacted ones added when signing
+        let problems = builder.lint().unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].code(),
+            validation_status::ACTION_ASSERTION_MALFORMED
+        );
+        signed.rewind().unwrap();
+        let mut dest = Cursor::new(Vec::new());
+        assert!(matches!(
+            builder.sign(signer.as_ref(), format, &mut signed, &mut dest),
+            Err(Error::ManifestLint(_))
+        ));
+
+        // opening the parent first makes the redaction valid
+        builder
+            .add_assertion(
+                labels::ACTIONS,
+                &Actions::new()
+                    .add_action(Action::new(c2pa_action::OPENED).set_instance_id(parent_id)),
+            )
+            .unwrap();
+        assert!(builder.lint().unwrap().is_empty());
+        signed.rewind().unwrap();
+        builder
+            .sign(signer.as_ref(), format, &mut signed, &mut dest)
+            .unwrap();
+
+        dest.rewind().unwrap();
+        let reader = Reader::from_stream(format, &mut dest).unwrap();
+        assert!(reader.validation_status().is_none());
+    }
+
+    #[test]
+    fn test_builder_lint_clean() {
+        let definition = json!({
+            "assertions": [
+                {
+                    "label": "c2pa.actions",
+                    "data": {
+                        "actions": [
+                            {
+                                "action": "c2pa.created",
+                                "digitalSourceType": "http://cv.iptc.org/newscodes/digitalsourcetype/digitalCapture"
+                            },
+                            { "action": "c2pa.edited" }
+                        ]
+                    }
+                }
+            ]
+        })
+        .to_string();
+
+        let builder = Builder::from_json(&definition).unwrap();
+        assert!(builder.lint().unwrap().is_empty());
+
+        // an edit started from a parent passes too
+        let builder = Builder::from_parent("image/jpeg", &mut Cursor::new(TEST_IMAGE)).unwrap();
+        assert!(builder.lint().unwrap().is_empty());
+    }
 }
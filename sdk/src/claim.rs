@@ -1024,6 +1024,7 @@ impl Claim {
         claim: &Claim,
         asset_data: &mut ClaimAssetData<'_>,
         is_provenance: bool,
+        redactions: &[String],
         th: &dyn TrustHandlerConfig,
         validation_log: &mut impl StatusTracker,
     ) -> Result<()> {
@@ -1062,7 +1063,14 @@ impl Claim {
             validation_log,
         )
         .await;
-        Claim::verify_internal(claim, asset_data, is_provenance, verified, validation_log)
+        Claim::verify_internal(
+            claim,
+            asset_data,
+            is_provenance,
+            redactions,
+            verified,
+            validation_log,
+        )
     }
 
     /// Verify claim signature, assertion store and asset hashes
@@ -1072,6 +1080,7 @@ impl Claim {
         claim: &Claim,
         asset_data: &mut ClaimAssetData<'_>,
         is_provenance: bool,
+        redactions: &[String],
         th: &dyn TrustHandlerConfig,
         validation_log: &mut impl StatusTracker,
     ) -> Result<()> {
@@ -1110,7 +1119,14 @@ impl Claim {
             validation_log,
         );
 
-        Claim::verify_internal(claim, asset_data, is_provenance, verified, validation_log)
+        Claim::verify_internal(
+            claim,
+            asset_data,
+            is_provenance,
+            redactions,
+            verified,
+            validation_log,
+        )
     }
 
     /// Get the signing certificate chain as PEM bytes
@@ -1128,6 +1144,7 @@ impl Claim {
         claim: &Claim,
         asset_data: &mut ClaimAssetData<'_>,
         is_provenance: bool,
+        redactions: &[String],
         verified: Result<ValidationInfo>,
         validation_log: &mut impl StatusTracker,
     ) -> Result<()> {
@@ -1242,6 +1259,8 @@ impl Claim {
                         validation_log.log_silent(log_item);
                     }
                 }
+                // assertions redacted by a later claim are no longer in the assertion store
+                None if claim.is_redacted(&assertion.url(), redactions) => (),
                 None => {
                     let log_item = log_item!(
                         assertion.url(),
@@ -1396,23 +1415,22 @@ impl Claim {
                     let hash_result = match asset_data {
                         #[cfg(feature = "file_io")]
                         ClaimAssetData::Path(asset_path) => {
-                            let box_hash_processor =
+                            let handler =
                                 crate::jumbf_io::get_assetio_handler_from_path(asset_path)
-                                    .ok_or(Error::UnsupportedType)?
-                                    .asset_box_hash_ref()
-                                    .ok_or(Error::HashMismatch(
-                                        "Box hash not supported".to_string(),
-                                    ))?;
+                                    .ok_or(Error::UnsupportedType)?;
+                            let box_hash_processor = handler
+                                .asset_box_hash_ref()
+                                .ok_or(Error::HashMismatch("Box hash not supported".to_string()))?;
 
                             bh.verify_hash(asset_path, Some(claim.alg()), box_hash_processor)
                         }
                         ClaimAssetData::Bytes(asset_bytes, asset_type) => {
-                            let box_hash_processor = get_assetio_handler(asset_type)
-                                .ok_or(Error::UnsupportedType)?
-                                .asset_box_hash_ref()
-                                .ok_or(Error::HashMismatch(format!(
-                                    "Box hash not supported for: {asset_type}"
-                                )))?;
+                            let handler =
+                                get_assetio_handler(asset_type).ok_or(Error::UnsupportedType)?;
+                            let box_hash_processor =
+                                handler.asset_box_hash_ref().ok_or(Error::HashMismatch(
+                                    format!("Box hash not supported for: {asset_type}"),
+                                ))?;
 
                             bh.verify_in_memory_hash(
                                 asset_bytes,
@@ -1421,12 +1439,12 @@ impl Claim {
                             )
                         }
                         ClaimAssetData::Stream(stream_data, asset_type) => {
-                            let box_hash_processor = get_assetio_handler(asset_type)
-                                .ok_or(Error::UnsupportedType)?
-                                .asset_box_hash_ref()
-                                .ok_or(Error::HashMismatch(format!(
-                                    "Box hash not supported for: {asset_type}"
-                                )))?;
+                            let handler =
+                                get_assetio_handler(asset_type).ok_or(Error::UnsupportedType)?;
+                            let box_hash_processor =
+                                handler.asset_box_hash_ref().ok_or(Error::HashMismatch(
+                                    format!("Box hash not supported for: {asset_type}"),
+                                ))?;
 
                             bh.verify_stream_hash(
                                 *stream_data,
@@ -1563,8 +1581,12 @@ impl Claim {
             }
         }
 
-        // all have been removed (if necessary) so replace redaction list
-        self.redacted_assertions = redactions_opt;
+        // all have been removed (if necessary) so add them to the redaction list
+        if let Some(redactions) = redactions_opt {
+            self.redacted_assertions
+                .get_or_insert_with(Vec::new)
+                .extend(redactions);
+        }
 
         // add ingredients
         self.ingredients_store
@@ -1578,6 +1600,17 @@ impl Claim {
         self.redacted_assertions.as_ref()
     }
 
+    /// Returns true if an assertion of this claim is in a list of redacted assertion URIs.
+    pub(crate) fn is_redacted(&self, assertion_uri: &str, redactions: &[String]) -> bool {
+        let uri = jumbf::labels::to_normalized_uri(&jumbf::labels::to_absolute_uri(
+            &self.label,
+            assertion_uri,
+        ));
+        redactions
+            .iter()
+            .any(|r| jumbf::labels::to_normalized_uri(r) == uri)
+    }
+
     /// Return snapshot clone of the claim's assertions.
     pub fn assertion_store(&self) -> Vec<Assertion> {
         self.assertion_store
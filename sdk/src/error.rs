@@ -15,6 +15,8 @@
 
 use thiserror::Error;
 
+use crate::validation_status::ValidationStatus;
+
 /// `Error` enumerates errors returned by most C2PA toolkit operations.
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -202,6 +204,9 @@ pub enum Error {
     #[error("type is unsupported")]
     UnsupportedType,
 
+    #[error("declared format {declared} does not match detected format {detected}")]
+    FormatMismatch { declared: String, detected: String },
+
     #[error("embedding error")]
     EmbeddingError,
 
@@ -209,6 +214,9 @@ pub enum Error {
     #[error("ingredient file not found")]
     IngredientNotFound,
 
+    #[error("manifest failed pre-signing checks: {}", .0.iter().map(|s| s.code()).collect::<Vec<_>>().join(", "))]
+    ManifestLint(Vec<ValidationStatus>),
+
     #[error("file not found: {0}")]
     FileNotFound(String),
 
@@ -26,6 +26,8 @@ use uuid::Uuid;
 use crate::utils::mime::extension_to_mime;
 #[cfg(doc)]
 use crate::Manifest;
+#[cfg(feature = "unstable_api")]
+use crate::Reader;
 use crate::{
     assertion::{get_thumbnail_image_type, Assertion, AssertionBase},
     assertions::{self, labels, Metadata, Relationship, Thumbnail},
@@ -774,6 +776,50 @@ impl Ingredient {
         ingredient.add_stream_internal(format, stream)
     }
 
+    /// Creates an `Ingredient` from a stream and a [`Reader`] already loaded from it.
+    ///
+    /// The manifest store is copied from the stream without validating it again,
+    /// the title, validation status and thumbnail are taken from the reader.
+    #[cfg(feature = "unstable_api")]
+    pub(crate) fn from_reader(
+        reader: &Reader,
+        format: &str,
+        stream: &mut dyn CAIRead,
+    ) -> Result<Self> {
+        let manifest = reader.active_manifest();
+        let title = manifest.and_then(|m| m.title()).unwrap_or("untitled");
+        let mut ingredient = Self::from_stream_info(stream, format, title);
+
+        if let Some(manifest) = manifest {
+            stream.rewind()?;
+            ingredient.set_manifest_data(load_jumbf_from_stream(format, stream)?)?;
+            ingredient.active_manifest = reader.active_label().map(str::to_owned);
+            ingredient.validation_status = reader.validation_status().map(|s| s.to_vec());
+
+            if let Some(thumb_ref) = manifest.thumbnail_ref() {
+                let mut thumbnail = Cursor::new(Vec::new());
+                reader.resource_to_stream(&thumb_ref.identifier, &mut thumbnail)?;
+                ingredient.set_thumbnail(thumb_ref.format.clone(), thumbnail.into_inner())?;
+            }
+        }
+
+        // create a thumbnail if the manifest did not have one
+        #[cfg(feature = "add_thumbnails")]
+        if ingredient.thumbnail.is_none() {
+            stream.rewind()?;
+            match crate::utils::thumbnail::make_thumbnail_from_stream(format, stream) {
+                Ok((format, image)) => {
+                    ingredient.set_thumbnail(format, image)?;
+                }
+                Err(err) => {
+                    log::warn!("Could not create thumbnail. {err}");
+                }
+            }
+        }
+
+        Ok(ingredient)
+    }
+
     /// Create an Ingredient from JSON
     pub fn from_json(json: &str) -> Result<Self> {
         serde_json::from_str(json).map_err(Error::JsonError)
@@ -1090,16 +1136,30 @@ impl Ingredient {
                     .clone()
                     .ok_or(Error::IngredientNotFound)?;
 
-                //if this is the parent ingredient then apply any redactions, converting from labels to uris
-                let redactions = match self.is_parent() {
-                    true => redactions.as_ref().map(|redactions| {
+                // redactions given as uris already name their manifest, labels
+                // only apply to the parent ingredient so convert them to uris
+                let redactions = redactions
+                    .as_ref()
+                    .map(|redactions| {
                         redactions
                             .iter()
-                            .map(|r| to_assertion_uri(&manifest_label, r))
-                            .collect()
-                    }),
-                    false => None,
-                };
+                            .filter_map(|r| match manifest_label_from_uri(r) {
+                                Some(_) => Some(r.to_owned()),
+                                None if self.is_parent() => {
+                                    Some(to_assertion_uri(&manifest_label, r))
+                                }
+                                None => None,
+                            })
+                            .collect::<Vec<String>>()
+                    })
+                    .filter(|redactions| !redactions.is_empty());
+
+                // redacting from the active manifest changes its box hash
+                let active_redacted = redactions.as_ref().is_some_and(|redactions| {
+                    redactions.iter().any(|r| {
+                        manifest_label_from_uri(r).is_some_and(|label| label == manifest_label)
+                    })
+                });
 
                 // get the c2pa manifest bytes
                 let manifest_data = get_resource(&resource_ref.identifier)?;
@@ -1120,8 +1180,13 @@ impl Ingredient {
                             .iter()
                             .find(|c| c.label() == manifest_label)
                         {
-                            let hash =
-                                ingredient_store.get_manifest_box_hash(ingredient_active_claim); // get C2PA 1.2 JUMBF box hash
+                            // get C2PA 1.2 JUMBF box hash, or the claim hash if the box has been redacted
+                            let hash = match active_redacted {
+                                true => ingredient_active_claim.hash(),
+                                false => {
+                                    ingredient_store.get_manifest_box_hash(ingredient_active_claim)
+                                }
+                            };
 
                             let uri = jumbf::labels::to_manifest_uri(&manifest_label);
 
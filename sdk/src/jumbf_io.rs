@@ -11,7 +11,11 @@
 // specific language governing permissions and limitations under
 // each license.
 
-use std::{collections::HashMap, io::Cursor};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard},
+};
 #[cfg(feature = "file_io")]
 use std::{
     fs::{self, File},
@@ -19,75 +23,125 @@ use std::{
 };
 
 use lazy_static::lazy_static;
+use serde::Serialize;
 
 #[cfg(feature = "pdf")]
 use crate::asset_handlers::pdf_io::PdfIO;
 use crate::{
     asset_handlers::{
-        bmff_io::BmffIO, c2pa_io::C2paIO, jpeg_io::JpegIO, mp3_io::Mp3IO, png_io::PngIO,
-        riff_io::RiffIO, svg_io::SvgIO, tiff_io::TiffIO,
+        aiff_io::AiffIO, bmff_io::BmffIO, c2pa_io::C2paIO, exr_io::ExrIO, flac_io::FlacIO,
+        gif_io::GifIO, gltf_io::GltfIO, jpeg2000_io::Jpeg2000IO, jpeg_io::JpegIO,
+        jpegxl_io::JpegXlIO, matroska_io::MatroskaIO, mp3_io::Mp3IO, mpegts_io::MpegTsIO,
+        ogg_io::OggIO, png_io::PngIO, psd_io::PsdIO, riff_io::RiffIO, sfnt_io::SfntIO,
+        svg_io::SvgIO, text_io::TextIO, tiff_io::TiffIO, zip_io::ZipIO,
+    },
+    asset_io::{
+        AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter, HashObjectPositions, ManifestBinding,
     },
-    asset_io::{AssetIO, CAIRead, CAIReadWrite, CAIReader, CAIWriter, HashObjectPositions},
     error::{Error, Result},
 };
 
-// initialize asset handlers
-lazy_static! {
-    static ref ASSET_HANDLERS: HashMap<String, Box<dyn AssetIO>> = {
-        let handlers: Vec<Box<dyn AssetIO>> = vec![
-            #[cfg(feature = "pdf")]
-            Box::new(PdfIO::new("")),
-            Box::new(BmffIO::new("")),
-            Box::new(C2paIO::new("")),
-            Box::new(JpegIO::new("")),
-            Box::new(PngIO::new("")),
-            Box::new(RiffIO::new("")),
-            Box::new(SvgIO::new("")),
-            Box::new(TiffIO::new("")),
-            Box::new(Mp3IO::new("")),
-        ];
+// built-in handlers, registered when the registry is first used
+fn builtin_handlers() -> Vec<Box<dyn AssetIO>> {
+    vec![
+        #[cfg(feature = "pdf")]
+        Box::new(PdfIO::new("")),
+        Box::new(BmffIO::new("")),
+        Box::new(C2paIO::new("")),
+        Box::new(GifIO::new("")),
+        Box::new(JpegIO::new("")),
+        Box::new(JpegXlIO::new("")),
+        Box::new(PngIO::new("")),
+        Box::new(RiffIO::new("")),
+        Box::new(SvgIO::new("")),
+        Box::new(TiffIO::new("")),
+        Box::new(Mp3IO::new("")),
+        Box::new(FlacIO::new("")),
+        Box::new(AiffIO::new("")),
+        Box::new(OggIO::new("")),
+        Box::new(MatroskaIO::new("")),
+        Box::new(ZipIO::new("")),
+        Box::new(TextIO::new("")),
+        Box::new(SfntIO::new("")),
+        Box::new(MpegTsIO::new("")),
+        Box::new(PsdIO::new("")),
+        Box::new(Jpeg2000IO::new("")),
+        Box::new(GltfIO::new("")),
+        Box::new(ExrIO::new("")),
+    ]
+}
 
-        let mut handler_map = HashMap::new();
+// handlers and streaming writers keyed by supported extension and mime type
+#[derive(Default)]
+struct HandlerRegistry {
+    handlers: HashMap<String, Arc<dyn AssetIO>>,
+    writers: HashMap<String, Arc<dyn CAIWriter>>,
+}
 
-        // build handler map
-        for h in handlers {
-            // get the supported types add entry for each
-            for supported_type in h.supported_types() {
-                handler_map.insert(supported_type.to_string(), h.get_handler(supported_type));
-            }
+impl HandlerRegistry {
+    fn register(&mut self, handler: Box<dyn AssetIO>) {
+        // get the supported types add entry for each
+        for supported_type in handler.supported_types() {
+            let key = supported_type.to_lowercase();
+
+            self.handlers
+                .insert(key.clone(), Arc::from(handler.get_handler(supported_type)));
+
+            // get streaming writer if supported, an overridden writer must not outlive its handler
+            match handler.get_writer(supported_type) {
+                Some(writer) => self.writers.insert(key, Arc::from(writer)),
+                None => self.writers.remove(&key),
+            };
         }
+    }
+}
 
-        handler_map
+lazy_static! {
+    static ref ASSET_HANDLERS: RwLock<HandlerRegistry> = {
+        let mut registry = HandlerRegistry::default();
+        for h in builtin_handlers() {
+            registry.register(h);
+        }
+        RwLock::new(registry)
     };
 }
 
-// initialize streaming write handlers
-lazy_static! {
-    static ref CAI_WRITERS: HashMap<String, Box<dyn CAIWriter>> = {
-        let handlers: Vec<Box<dyn AssetIO>> = vec![
-            Box::new(BmffIO::new("")),
-            Box::new(C2paIO::new("")),
-            Box::new(JpegIO::new("")),
-            Box::new(PngIO::new("")),
-            Box::new(RiffIO::new("")),
-            Box::new(SvgIO::new("")),
-            Box::new(TiffIO::new("")),
-            Box::new(Mp3IO::new("")),
-        ];
-        let mut handler_map = HashMap::new();
+fn registry() -> RwLockReadGuard<'static, HandlerRegistry> {
+    ASSET_HANDLERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+}
 
-        // build handler map
-        for h in handlers {
-            // get the supported types add entry for each
-            for supported_type in h.supported_types() {
-                if let Some(writer) = h.get_writer(supported_type) { // get streaming writer if supported
-                    handler_map.insert(supported_type.to_string(), writer);
-                }
-            }
-        }
+/// Registers an [`AssetIO`] handler for each of its [`AssetIO::supported_types`].
+///
+/// A registered handler replaces the handler, built-in or previously registered, for every
+/// type it supports.  Other types are not affected.  Registration is thread safe and applies
+/// to all reading, signing and ingredient operations started after it returns, including
+/// [`get_supported_types`].
+///
+/// # Example
+/// ```no_run
+/// # fn my_handler() -> Box<dyn c2pa::AssetIO> { unimplemented!() }
+/// c2pa::register_asset_handler(my_handler());
+/// ```
+pub fn register_asset_handler(handler: Box<dyn AssetIO>) {
+    ASSET_HANDLERS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(handler);
+}
 
-        handler_map
-    };
+// Keeps a registered handler alive while its reader is in use.
+pub(crate) struct CAIReaderRef(Arc<dyn AssetIO>);
+
+impl CAIReader for CAIReaderRef {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        self.0.get_reader().read_cai(asset_reader)
+    }
+
+    fn read_xmp(&self, asset_reader: &mut dyn CAIRead) -> Option<String> {
+        self.0.get_reader().read_xmp(asset_reader)
+    }
 }
 
 pub(crate) fn is_bmff_format(asset_type: &str) -> bool {
@@ -95,6 +149,11 @@ pub(crate) fn is_bmff_format(asset_type: &str) -> bool {
     bmff_io.supported_types().contains(&asset_type)
 }
 
+pub(crate) fn is_tiff_format(asset_type: &str) -> bool {
+    let tiff_io = TiffIO::new("");
+    tiff_io.supported_types().contains(&asset_type)
+}
+
 /// Return jumbf block from in memory asset
 #[allow(dead_code)]
 pub fn load_jumbf_from_memory(asset_type: &str, data: &[u8]) -> Result<Vec<u8>> {
@@ -144,28 +203,32 @@ pub fn save_jumbf_to_memory(asset_type: &str, data: &[u8], store_bytes: &[u8]) -
 }
 
 #[cfg(feature = "file_io")]
-pub(crate) fn get_assetio_handler_from_path(asset_path: &Path) -> Option<&dyn AssetIO> {
+pub(crate) fn get_assetio_handler_from_path(asset_path: &Path) -> Option<Arc<dyn AssetIO>> {
     let ext = get_file_extension(asset_path)?;
 
-    ASSET_HANDLERS.get(&ext).map(|h| h.as_ref())
+    registry().handlers.get(&ext).cloned()
 }
 
-pub(crate) fn get_assetio_handler(ext: &str) -> Option<&dyn AssetIO> {
+pub(crate) fn get_assetio_handler(ext: &str) -> Option<Arc<dyn AssetIO>> {
     let ext = ext.to_lowercase();
 
-    ASSET_HANDLERS.get(&ext).map(|h| h.as_ref())
+    registry().handlers.get(&ext).cloned()
 }
 
-pub(crate) fn get_cailoader_handler(asset_type: &str) -> Option<&dyn CAIReader> {
+pub(crate) fn get_cailoader_handler(asset_type: &str) -> Option<CAIReaderRef> {
     let asset_type = asset_type.to_lowercase();
 
-    ASSET_HANDLERS.get(&asset_type).map(|h| h.get_reader())
+    registry()
+        .handlers
+        .get(&asset_type)
+        .cloned()
+        .map(CAIReaderRef)
 }
 
-pub(crate) fn get_caiwriter_handler(asset_type: &str) -> Option<&dyn CAIWriter> {
+pub(crate) fn get_caiwriter_handler(asset_type: &str) -> Option<Arc<dyn CAIWriter>> {
     let asset_type = asset_type.to_lowercase();
 
-    CAI_WRITERS.get(&asset_type).map(|h| h.as_ref())
+    registry().writers.get(&asset_type).cloned()
 }
 
 #[cfg(feature = "file_io")]
@@ -181,7 +244,7 @@ pub(crate) fn get_file_extension(path: &Path) -> Option<String> {
 pub(crate) fn get_supported_file_extension(path: &Path) -> Option<String> {
     let ext = get_file_extension(path)?;
 
-    if ASSET_HANDLERS.get(&ext).is_some() {
+    if registry().handlers.contains_key(&ext) {
         Some(ext)
     } else {
         None
@@ -309,7 +372,70 @@ pub fn remove_jumbf_from_file(path: &Path) -> Result<()> {
 
 /// returns a list of supported file extensions and mime types
 pub fn get_supported_types() -> Vec<String> {
-    ASSET_HANDLERS.keys().map(|k| k.to_owned()).collect()
+    registry().handlers.keys().map(|k| k.to_owned()).collect()
+}
+
+/// The manifest operations supported by the handler for a format.
+///
+/// Returned by [`format_capabilities`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct FormatCapabilities {
+    /// Manifests can be read from the asset.
+    pub read: bool,
+    /// Manifests can be embedded in the asset.
+    pub write: bool,
+    /// Embedded manifests can be removed from the asset.
+    pub remove: bool,
+    /// The asset can be bound to a manifest with a `c2pa.hash.boxes` assertion.
+    pub box_hash: bool,
+    /// The asset can be bound to a manifest with a `c2pa.hash.data` assertion.
+    pub data_hash: bool,
+    /// The asset can be bound to a manifest with a `c2pa.hash.bmff` assertion.
+    pub bmff_hash: bool,
+    /// A remote manifest URL can be referenced from the asset's XMP.
+    pub remote_ref: bool,
+    /// Manifests can be read but must be kept in a sidecar rather than embedded.
+    pub sidecar_only: bool,
+    /// An embedded manifest can be replaced in place by a manifest of the same size.
+    pub patch: bool,
+    /// Manifests can be composed into format specific bytes for embedding by the caller.
+    pub composed_manifest: bool,
+}
+
+/// Returns the [`FormatCapabilities`] of the handler registered for `format`.
+///
+/// `format` may be a file extension or a MIME type. Unsupported formats have no capabilities.
+///
+/// # Example
+/// ```
+/// let capabilities = c2pa::format_capabilities("image/jpeg");
+/// assert!(capabilities.write && capabilities.box_hash);
+/// assert!(!capabilities.bmff_hash);
+/// ```
+pub fn format_capabilities(format: &str) -> FormatCapabilities {
+    let format = format.to_lowercase();
+    let Some(handler) = get_assetio_handler(&format) else {
+        return FormatCapabilities::default();
+    };
+
+    // a handler without a writer can only read manifests kept beside the asset
+    let write = get_caiwriter_handler(&format).is_some();
+    let binding = handler.manifest_binding();
+    let embedded = write && binding != ManifestBinding::Sidecar;
+
+    FormatCapabilities {
+        read: true,
+        write,
+        remove: embedded,
+        box_hash: handler.asset_box_hash_ref().is_some(),
+        data_hash: embedded && binding == ManifestBinding::DataHash,
+        bmff_hash: embedded && binding == ManifestBinding::BmffHash,
+        remote_ref: handler.remote_ref_writer_ref().is_some(),
+        sidecar_only: !embedded,
+        patch: handler.asset_patch_ref().is_some(),
+        composed_manifest: handler.composed_data_ref().is_some(),
+    }
 }
 
 #[cfg(test)]
@@ -330,12 +456,26 @@ pub mod tests {
         let handlers: Vec<Box<dyn AssetIO>> = vec![
             Box::new(C2paIO::new("")),
             Box::new(BmffIO::new("")),
+            Box::new(GifIO::new("")),
             Box::new(JpegIO::new("")),
+            Box::new(JpegXlIO::new("")),
             Box::new(PngIO::new("")),
             Box::new(RiffIO::new("")),
             Box::new(TiffIO::new("")),
             Box::new(SvgIO::new("")),
             Box::new(Mp3IO::new("")),
+            Box::new(FlacIO::new("")),
+            Box::new(AiffIO::new("")),
+            Box::new(OggIO::new("")),
+            Box::new(MatroskaIO::new("")),
+            Box::new(ZipIO::new("")),
+            Box::new(TextIO::new("")),
+            Box::new(SfntIO::new("")),
+            Box::new(MpegTsIO::new("")),
+            Box::new(PsdIO::new("")),
+            Box::new(Jpeg2000IO::new("")),
+            Box::new(GltfIO::new("")),
+            Box::new(ExrIO::new("")),
         ];
 
         // build handler map
@@ -352,7 +492,9 @@ pub mod tests {
         let handlers: Vec<Box<dyn AssetIO>> = vec![
             Box::new(C2paIO::new("")),
             Box::new(BmffIO::new("")),
+            Box::new(GifIO::new("")),
             Box::new(JpegIO::new("")),
+            Box::new(JpegXlIO::new("")),
             #[cfg(feature = "pdf")]
             Box::new(PdfIO::new("")),
             Box::new(PngIO::new("")),
@@ -360,6 +502,18 @@ pub mod tests {
             Box::new(TiffIO::new("")),
             Box::new(SvgIO::new("")),
             Box::new(Mp3IO::new("")),
+            Box::new(FlacIO::new("")),
+            Box::new(AiffIO::new("")),
+            Box::new(OggIO::new("")),
+            Box::new(MatroskaIO::new("")),
+            Box::new(ZipIO::new("")),
+            Box::new(TextIO::new("")),
+            Box::new(SfntIO::new("")),
+            Box::new(MpegTsIO::new("")),
+            Box::new(PsdIO::new("")),
+            Box::new(Jpeg2000IO::new("")),
+            Box::new(GltfIO::new("")),
+            Box::new(ExrIO::new("")),
         ];
 
         // build handler map
@@ -374,9 +528,25 @@ pub mod tests {
     #[test]
     fn test_get_writer() {
         let handlers: Vec<Box<dyn AssetIO>> = vec![
+            #[cfg(feature = "pdf")]
+            Box::new(PdfIO::new("")),
+            Box::new(GifIO::new("")),
             Box::new(JpegIO::new("")),
+            Box::new(JpegXlIO::new("")),
             Box::new(PngIO::new("")),
             Box::new(Mp3IO::new("")),
+            Box::new(FlacIO::new("")),
+            Box::new(AiffIO::new("")),
+            Box::new(OggIO::new("")),
+            Box::new(MatroskaIO::new("")),
+            Box::new(ZipIO::new("")),
+            Box::new(TextIO::new("")),
+            Box::new(SfntIO::new("")),
+            Box::new(MpegTsIO::new("")),
+            Box::new(PsdIO::new("")),
+            Box::new(Jpeg2000IO::new("")),
+            Box::new(GltfIO::new("")),
+            Box::new(ExrIO::new("")),
             Box::new(SvgIO::new("")),
             Box::new(RiffIO::new("")),
         ];
@@ -390,6 +560,75 @@ pub mod tests {
         }
     }
 
+    // a reader only handler used to override built-in types
+    struct ReadOnlyIO {}
+
+    impl CAIReader for ReadOnlyIO {
+        fn read_cai(&self, _asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+            Err(Error::JumbfNotFound)
+        }
+
+        fn read_xmp(&self, _asset_reader: &mut dyn CAIRead) -> Option<String> {
+            None
+        }
+    }
+
+    impl AssetIO for ReadOnlyIO {
+        fn new(_asset_type: &str) -> Self {
+            ReadOnlyIO {}
+        }
+
+        fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+            Box::new(ReadOnlyIO::new(asset_type))
+        }
+
+        fn get_reader(&self) -> &dyn CAIReader {
+            self
+        }
+
+        fn read_cai_store(&self, _asset_path: &Path) -> Result<Vec<u8>> {
+            Err(Error::JumbfNotFound)
+        }
+
+        fn save_cai_store(&self, _asset_path: &Path, _store_bytes: &[u8]) -> Result<()> {
+            Err(Error::UnsupportedType)
+        }
+
+        fn get_object_locations(&self, _asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+            Err(Error::UnsupportedType)
+        }
+
+        fn remove_cai_store(&self, _asset_path: &Path) -> Result<()> {
+            Err(Error::UnsupportedType)
+        }
+
+        fn supported_types(&self) -> &[&str] {
+            &["JPG", "x-read-only"]
+        }
+    }
+
+    #[test]
+    fn test_registry_override() {
+        let mut registry = HandlerRegistry::default();
+        for h in builtin_handlers() {
+            registry.register(h);
+        }
+
+        registry.register(Box::new(ReadOnlyIO {}));
+
+        // the overridden type uses the new handler and loses the built-in writer
+        let jpg = registry.handlers.get("jpg").unwrap();
+        assert_eq!(jpg.supported_types(), ["JPG", "x-read-only"]);
+        assert!(!registry.writers.contains_key("jpg"));
+
+        // other types from the built-in handler are untouched
+        let jpeg = registry.handlers.get("jpeg").unwrap();
+        assert!(jpeg.supported_types().contains(&"image/jpeg"));
+        assert!(registry.writers.contains_key("jpeg"));
+
+        assert!(registry.handlers.contains_key("x-read-only"));
+    }
+
     #[test]
     fn test_get_supported_list() {
         let supported = get_supported_types();
@@ -411,6 +650,55 @@ pub mod tests {
         assert!(supported.iter().any(|s| s == "dng"));
         assert!(supported.iter().any(|s| s == "svg"));
         assert!(supported.iter().any(|s| s == "mp3"));
+        assert!(supported.iter().any(|s| s == "gif"));
+        assert!(supported.iter().any(|s| s == "jxl"));
+        assert!(supported.iter().any(|s| s == "flac"));
+        assert!(supported.iter().any(|s| s == "aiff"));
+        assert!(supported.iter().any(|s| s == "aifc"));
+        assert!(supported.iter().any(|s| s == "ogg"));
+        assert!(supported.iter().any(|s| s == "opus"));
+        assert!(supported.iter().any(|s| s == "webm"));
+        assert!(supported.iter().any(|s| s == "mkv"));
+        assert!(supported.iter().any(|s| s == "docx"));
+        assert!(supported.iter().any(|s| s == "epub"));
+        assert!(supported.iter().any(|s| s == "txt"));
+        assert!(supported.iter().any(|s| s == "md"));
+        assert!(supported.iter().any(|s| s == "html"));
+        assert!(supported.iter().any(|s| s == "ttf"));
+        assert!(supported.iter().any(|s| s == "otf"));
+        assert!(supported.iter().any(|s| s == "ts"));
+        assert!(supported.iter().any(|s| s == "m2ts"));
+        assert!(supported.iter().any(|s| s == "psd"));
+        assert!(supported.iter().any(|s| s == "psb"));
+        assert!(supported.iter().any(|s| s == "jp2"));
+        assert!(supported.iter().any(|s| s == "jpx"));
+        assert!(supported.iter().any(|s| s == "glb"));
+        assert!(supported.iter().any(|s| s == "gltf"));
+        assert!(supported.iter().any(|s| s == "exr"));
+    }
+
+    #[test]
+    fn test_format_capabilities() {
+        let jpeg = format_capabilities("image/jpeg");
+        assert!(jpeg.read && jpeg.write && jpeg.remove);
+        assert!(jpeg.box_hash && jpeg.data_hash && !jpeg.bmff_hash);
+        assert!(jpeg.remote_ref && jpeg.composed_manifest);
+        assert!(!jpeg.patch && !jpeg.sidecar_only);
+        assert_eq!(format_capabilities("JPG"), jpeg);
+
+        let mp4 = format_capabilities("mp4");
+        assert!(mp4.read && mp4.write && mp4.bmff_hash && mp4.patch);
+        assert!(!mp4.data_hash && !mp4.box_hash);
+        assert_eq!(format_capabilities("image/heic"), mp4);
+
+        let c2pa = format_capabilities("c2pa");
+        assert!(c2pa.read && c2pa.write && c2pa.sidecar_only && c2pa.composed_manifest);
+        assert!(!c2pa.remove && !c2pa.data_hash && !c2pa.bmff_hash && !c2pa.box_hash);
+
+        assert_eq!(
+            format_capabilities("x-unknown"),
+            FormatCapabilities::default()
+        );
     }
 
     fn test_jumbf(asset_type: &str, reader: &mut dyn CAIRead) {
@@ -471,6 +759,36 @@ pub mod tests {
         test_remote_ref("png", &mut reader);
     }
 
+    #[test]
+    fn test_streams_gif() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.gif").unwrap();
+        test_jumbf("gif", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("gif", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_jxl() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.jxl").unwrap();
+        test_jumbf("jxl", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("jxl", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_flac() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.flac").unwrap();
+        test_jumbf("flac", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_aiff() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.aiff").unwrap();
+        test_jumbf("aiff", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("aiff", &mut reader);
+    }
+
     #[test]
     fn test_streams_webp() {
         let mut reader = std::fs::File::open("tests/fixtures/sample1.webp").unwrap();
@@ -520,6 +838,107 @@ pub mod tests {
         //test_remote_ref("mp3", &mut reader); // not working
     }
 
+    #[test]
+    fn test_streams_opus() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.opus").unwrap();
+        test_jumbf("opus", &mut reader);
+        // ogg doesn't support remote refs
+    }
+
+    #[test]
+    fn test_streams_webm() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.webm").unwrap();
+        test_jumbf("webm", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("webm", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_docx() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.docx").unwrap();
+        test_jumbf("docx", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("docx", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_epub() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.epub").unwrap();
+        test_jumbf("epub", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("epub", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_txt() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.txt").unwrap();
+        test_jumbf("txt", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_md() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.md").unwrap();
+        test_jumbf("md", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_html() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.html").unwrap();
+        test_jumbf("html", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_ttf() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.ttf").unwrap();
+        test_jumbf("ttf", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_ts() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.ts").unwrap();
+        test_jumbf("ts", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_m2ts() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.m2ts").unwrap();
+        test_jumbf("m2ts", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_psd() {
+        let mut reader = std::fs::File::open("tests/fixtures/Purple Square.psd").unwrap();
+        test_jumbf("psd", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("psd", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_jp2() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.jp2").unwrap();
+        test_jumbf("jp2", &mut reader);
+        reader.rewind().unwrap();
+        test_remote_ref("jp2", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_glb() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.glb").unwrap();
+        test_jumbf("glb", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_gltf() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1.gltf").unwrap();
+        test_jumbf("gltf", &mut reader);
+    }
+
+    #[test]
+    fn test_streams_exr() {
+        let mut reader = std::fs::File::open("tests/fixtures/sample1_multipart.exr").unwrap();
+        test_jumbf("exr", &mut reader);
+    }
+
     #[test]
     fn test_streams_avif() {
         let mut reader = std::fs::File::open("tests/fixtures/sample1.avif").unwrap();
@@ -550,6 +969,13 @@ pub mod tests {
         test_remote_ref("mp4", &mut reader);
     }
 
+    #[test]
+    #[cfg(feature = "pdf")]
+    fn test_streams_pdf() {
+        let mut reader = std::fs::File::open("tests/fixtures/basic.pdf").unwrap();
+        test_jumbf("pdf", &mut reader);
+    }
+
     #[test]
     fn test_streams_c2pa() {
         let mut reader = std::fs::File::open("tests/fixtures/cloud_manifest.c2pa").unwrap();
@@ -110,8 +110,11 @@ pub mod validation_status;
 pub mod wasm;
 
 // Public exports
-#[cfg(feature = "v1_api")]
-pub use asset_io::{CAIRead, CAIReadWrite};
+pub use asset_io::{
+    AssetBoxHash, AssetIO, AssetPatch, CAIRead, CAIReadWrite, CAIReader, CAIWriter,
+    ComposedManifestRef, HashBlockObjectType, HashObjectPositions, ManifestBinding, RemoteRefEmbed,
+    RemoteRefEmbedType,
+};
 #[cfg(feature = "unstable_api")]
 pub use builder::{Builder, ManifestDefinition};
 pub use callback_signer::{CallbackFunc, CallbackSigner};
@@ -121,6 +124,7 @@ pub use hash_utils::{hash_stream_by_alg, HashRange};
 pub use ingredient::Ingredient;
 #[cfg(feature = "file_io")]
 pub use ingredient::{DefaultOptions, IngredientOptions};
+pub use jumbf_io::{format_capabilities, register_asset_handler, FormatCapabilities};
 pub use manifest::Manifest;
 pub use manifest_assertion::{ManifestAssertion, ManifestAssertionKind};
 #[cfg(feature = "v1_api")]
@@ -132,7 +136,7 @@ pub use reader::Reader;
 pub use resource_store::ResourceRef;
 pub use signer::{AsyncSigner, RemoteSigner, Signer};
 pub use signing_alg::SigningAlg;
-pub use utils::mime::format_from_path;
+pub use utils::{format_detect::detect_format, mime::format_from_path};
 
 // Internal modules
 #[allow(dead_code, clippy::enum_variant_names)]
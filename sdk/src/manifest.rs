@@ -545,10 +545,15 @@ impl Manifest {
         manifest.set_format(claim.format());
         manifest.set_instance_id(claim.instance_id());
 
+        let redactions = store.redactions();
         for assertion in claim.assertions() {
-            let claim_assertion = store.get_claim_assertion_from_uri(
+            let claim_assertion = match store.get_claim_assertion_from_uri(
                 &jumbf::labels::to_absolute_uri(claim.label(), &assertion.url()),
-            )?;
+            ) {
+                Ok(claim_assertion) => claim_assertion,
+                Err(_) if claim.is_redacted(&assertion.url(), &redactions) => continue,
+                Err(e) => return Err(e),
+            };
             let assertion = claim_assertion.assertion();
             let label = claim_assertion.label();
             let base_label = assertion.label();
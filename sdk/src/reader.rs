@@ -20,12 +20,19 @@ use std::io::{Read, Seek, Write};
 
 use async_generic::async_generic;
 
-#[cfg(feature = "file_io")]
-use crate::error::Error;
 use crate::{
-    claim::ClaimAssetData, error::Result, manifest_store::ManifestStore,
-    settings::get_settings_value, status_tracker::DetailedStatusTracker, store::Store,
-    validation_status::ValidationStatus, Manifest,
+    claim::ClaimAssetData,
+    error::{Error, Result},
+    manifest_store::ManifestStore,
+    settings::get_settings_value,
+    status_tracker::DetailedStatusTracker,
+    store::Store,
+    utils::{
+        format_detect::{detect_format, format_matches},
+        forward_stream::ForwardStream,
+    },
+    validation_status::ValidationStatus,
+    Manifest,
 };
 
 /// A reader for the manifest store.
@@ -66,6 +73,88 @@ impl Reader {
         })
     }
 
+    /// Create a manifest store Reader from a stream, identifying the format from its content.
+    /// # Arguments
+    /// * `format` - The declared format of the stream, if known.
+    /// * `stream` - The stream to read from.
+    /// # Returns
+    /// A reader for the manifest store.
+    /// # Errors
+    /// [`Error::FormatMismatch`] if the declared format does not match the content.
+    /// [`Error::UnsupportedType`] if no format is declared and the content is not recognized.
+    /// # Note
+    /// A declared format is trusted for content that cannot be identified from its leading bytes,
+    /// such as ZIP based documents and plain text.
+    /// # Example
+    /// ```no_run
+    /// use std::io::Cursor;
+    ///
+    /// use c2pa::Reader;
+    /// let mut stream = Cursor::new(include_bytes!("../tests/fixtures/CA.jpg"));
+    /// let reader = Reader::from_stream_autodetect(None, stream).unwrap();
+    /// println!("{}", reader.json());
+    /// ```
+    #[async_generic()]
+    pub fn from_stream_autodetect(
+        format: Option<&str>,
+        mut stream: impl Read + Seek + Send,
+    ) -> Result<Reader> {
+        let detected = detect_format(&mut stream);
+        let format = match (format, detected) {
+            (Some(declared), Some(detected)) => {
+                if !format_matches(declared, &detected) {
+                    return Err(Error::FormatMismatch {
+                        declared: declared.to_owned(),
+                        detected,
+                    });
+                }
+                declared.to_owned()
+            }
+            (Some(declared), None) => declared.to_owned(),
+            (None, Some(detected)) => detected,
+            (None, None) => return Err(Error::UnsupportedType),
+        };
+
+        if _sync {
+            Self::from_stream(&format, stream)
+        } else {
+            Self::from_stream_async(&format, stream).await
+        }
+    }
+
+    /// Create a manifest store Reader from a stream that cannot seek.
+    /// # Arguments
+    /// * `format` - The format of the stream.
+    /// * `stream` - The stream to read from, such as a network response body.
+    /// # Returns
+    /// A reader for the manifest store.
+    /// # Errors
+    /// If the stream is not a valid manifest store, is larger than the `core.max_spool_size` setting,
+    /// or is a JPEG or MP3 whose manifest store is not within the first `core.max_memory_usage` bytes.
+    /// # Note
+    /// The stream is read once, front to back. JPEG and MP3 are processed in a single pass,
+    /// keeping only the first `core.max_memory_usage` bytes in memory. Other formats are
+    /// spooled in full first, in memory up to `core.max_memory_usage` bytes and in a
+    /// temporary file beyond that.
+    /// # Example
+    /// ```no_run
+    /// use c2pa::Reader;
+    /// let stream = std::fs::File::open("tests/fixtures/CA.jpg").unwrap();
+    /// let reader = Reader::from_read("image/jpeg", stream).unwrap();
+    /// println!("{}", reader.json());
+    /// ```
+    #[async_generic()]
+    pub fn from_read(format: &str, stream: impl Read + Send) -> Result<Reader> {
+        let mut stream = ForwardStream::for_format(format, stream)?;
+        let reader = if _sync {
+            Self::from_stream(format, &mut stream)
+        } else {
+            Self::from_stream_async(format, &mut stream).await
+        };
+        stream.check_single_pass()?;
+        reader
+    }
+
     #[cfg(feature = "file_io")]
     /// Create a manifest store Reader from a file.
     /// # Arguments
@@ -125,6 +125,7 @@ pub(crate) struct Core {
     prefer_bmff_merkle_tree: bool,
     compress_manifests: bool,
     max_memory_usage: Option<u64>,
+    max_spool_size: u64,
 }
 
 impl Default for Core {
@@ -137,6 +138,7 @@ impl Default for Core {
             prefer_bmff_merkle_tree: false,
             compress_manifests: true,
             max_memory_usage: None,
+            max_spool_size: 4 * 1024 * 1024 * 1024,
         }
     }
 }
@@ -284,15 +284,13 @@ impl Store {
         Ok(claim_label)
     }
 
-    /// Add a new update manifest to this Store. The manifest label
-    /// may be updated to reflect is position in the manifest Store
-    /// if there are conflicting label names.  The function
-    /// will return the label of the claim used
-    pub fn commit_update_manifest(&mut self, mut claim: Claim) -> Result<String> {
-        claim.set_update_manifest(true);
-
+    /// Checks the content rules for an update manifest, returning its parent ingredient.
+    /// An update manifest must not contain hard bindings or actions, and must have exactly
+    /// one ingredient, which is the parentOf ingredient referencing the manifest it updates.
+    pub(crate) fn check_update_manifest(claim: &Claim) -> Result<Ingredient> {
         // check for disallowed assertions
         if claim.has_assertion_type(labels::DATA_HASH)
+            || claim.has_assertion_type(labels::BOX_HASH)
             || claim.has_assertion_type(labels::ACTIONS)
             || claim.has_assertion_type(labels::BMFF_HASH)
         {
@@ -318,6 +316,23 @@ impl Store {
             return Err(Error::IngredientNotFound);
         }
 
+        // must refer to the manifest being updated
+        if ingredient_helper.c2pa_manifest.is_none() {
+            return Err(Error::IngredientNotFound);
+        }
+
+        Ok(ingredient_helper)
+    }
+
+    /// Add a new update manifest to this Store. The manifest label
+    /// may be updated to reflect is position in the manifest Store
+    /// if there are conflicting label names.  The function
+    /// will return the label of the claim used
+    pub fn commit_update_manifest(&mut self, mut claim: Claim) -> Result<String> {
+        claim.set_update_manifest(true);
+
+        let ingredient_helper = Self::check_update_manifest(&claim)?;
+
         // make sure ingredient c2pa.manifest points to provenance claim
         if let Some(c2pa_manifest) = ingredient_helper.c2pa_manifest {
             // the manifest should refer to provenance claim
@@ -328,13 +343,31 @@ impl Store {
             } else {
                 return Err(Error::IngredientNotFound);
             }
-        } else {
-            return Err(Error::IngredientNotFound);
         }
 
         self.commit_claim(claim)
     }
 
+    /// Returns the URIs of the assertions redacted by the claims in this store.
+    ///
+    /// Only redactions of an earlier claim by a later one are returned, self redactions
+    /// and redactions of later claims are reported as invalid when the claim is verified.
+    pub(crate) fn redactions(&self) -> Vec<String> {
+        self.claims
+            .iter()
+            .enumerate()
+            .filter_map(|(index, claim)| Some((index, claim.redactions()?)))
+            .flat_map(|(index, redactions)| {
+                redactions.iter().filter(move |r| {
+                    jumbf::labels::manifest_label_from_uri(r)
+                        .and_then(|label| self.claims_map.get(&label))
+                        .is_some_and(|&redacted| redacted < index)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Get Claim by label
     // Returns Option<&Claim>
     pub fn get_claim(&self, label: &str) -> Option<&Claim> {
@@ -1320,6 +1353,7 @@ impl Store {
                         ingredient,
                         asset_data,
                         false,
+                        &store.redactions(),
                         store.trust_handler(),
                         validation_log,
                     )?;
@@ -1436,6 +1470,7 @@ impl Store {
                         ingredient,
                         asset_data,
                         false,
+                        &store.redactions(),
                         store.trust_handler(),
                         validation_log,
                     )
@@ -1491,6 +1526,7 @@ impl Store {
             claim,
             asset_data,
             true,
+            &store.redactions(),
             store.trust_handler(),
             validation_log,
         )
@@ -1529,6 +1565,7 @@ impl Store {
             claim,
             asset_data,
             true,
+            &store.redactions(),
             store.trust_handler(),
             validation_log,
         )?;
@@ -1573,35 +1610,36 @@ impl Store {
         // sort blocks by offset
         block_locations.sort_by(|a, b| a.offset.cmp(&b.offset));
 
-        // generate default data hash that excludes jumbf block
-        // find the first jumbf block (ours are always in order)
-        // find the first block after the jumbf blocks
-        let mut block_start: usize = 0;
-        let mut block_end: usize = 0;
-        let mut found_jumbf = false;
+        // generate default data hash that excludes jumbf blocks
+        // adjacent jumbf blocks (ours are always in order) share one exclusion,
+        // formats that must also exclude structures elsewhere in the file
+        // (e.g. ZIP directories) report them as separate jumbf blocks
+        let mut jumbf_ranges: Vec<(usize, usize)> = Vec::new();
+        let mut in_jumbf = false;
         for item in block_locations {
-            // find start of jumbf
-            if !found_jumbf && item.htype == HashBlockObjectType::Cai {
-                block_start = item.offset;
-                found_jumbf = true;
-            }
-
-            // find start of block after jumbf blocks
-            if found_jumbf && item.htype == HashBlockObjectType::Cai {
-                block_end = item.offset + item.length;
+            if item.htype == HashBlockObjectType::Cai {
+                match jumbf_ranges.last_mut() {
+                    Some((_, block_end)) if in_jumbf => *block_end = item.offset + item.length,
+                    _ => jumbf_ranges.push((item.offset, item.offset + item.length)),
+                }
+                in_jumbf = true;
+            } else {
+                in_jumbf = false;
             }
         }
 
-        if found_jumbf {
+        if let Some((_, last_block_end)) = jumbf_ranges.last() {
             // add exclusion hash for bytes before and after jumbf
             let mut dh = DataHash::new("jumbf manifest", alg);
-            if block_end > block_start {
-                dh.add_exclusion(HashRange::new(block_start, block_end - block_start));
+            for (block_start, block_end) in &jumbf_ranges {
+                if block_end > block_start {
+                    dh.add_exclusion(HashRange::new(*block_start, block_end - block_start));
+                }
             }
 
             if calc_hashes {
                 // this check is only valid on the final sized asset
-                if block_end as u64 > stream_len {
+                if *last_block_end as u64 > stream_len {
                     return Err(Error::BadParam(
                         "data hash exclusions out of range".to_string(),
                     ));
@@ -1772,6 +1810,18 @@ impl Store {
         reserve_size: usize,
         format: &str,
     ) -> Result<Vec<u8>> {
+        self.add_data_hash_placeholder()?;
+
+        let jumbf_bytes = self.to_jumbf_internal(reserve_size)?;
+
+        let composed = Self::get_composed_manifest(&jumbf_bytes, format)?;
+
+        Ok(composed)
+    }
+
+    /// Adds a placeholder DataHash to the provenance claim if it has no hash assertions.
+    /// The placeholder is replaced by the caller's hash when the embeddable manifest is signed.
+    pub(crate) fn add_data_hash_placeholder(&mut self) -> Result<()> {
         let pc = self.provenance_claim_mut().ok_or(Error::ClaimEncoding)?;
 
         // if user did not supply a hash
@@ -1788,11 +1838,7 @@ impl Store {
             pc.add_assertion_with_salt(&ph, &DefaultSalt::default())?;
         }
 
-        let jumbf_bytes = self.to_jumbf_internal(reserve_size)?;
-
-        let composed = Self::get_composed_manifest(&jumbf_bytes, format)?;
-
-        Ok(composed)
+        Ok(())
     }
 
     fn prep_embeddable_store(
@@ -3219,7 +3265,7 @@ pub mod tests {
         assertion::AssertionJson,
         assertions::{labels::BOX_HASH, Action, Actions, BoxHash, Uuid},
         claim::AssertionStoreJsonFormat,
-        jumbf_io::{get_assetio_handler_from_path, update_file_jumbf},
+        jumbf_io::{get_assetio_handler_from_path, get_caiwriter_handler, update_file_jumbf},
         status_tracker::*,
         utils::{
             hash_utils::Hasher,
@@ -4230,8 +4276,12 @@ pub mod tests {
     }
 
     #[test]
+    #[cfg(feature = "file_io")]
     fn test_unsupported_type_without_external_manifest() {
-        let ap = fixture_path("Purple Square.psd");
+        let temp_dir = tempdir().expect("temp dir");
+        let ap = temp_dir_path(&temp_dir, "unsupported_type.xyz");
+        std::fs::copy(fixture_path("Purple Square.psd"), &ap).unwrap();
+
         let mut report = DetailedStatusTracker::new();
         let result = Store::load_from_asset(&ap, true, &mut report);
         assert!(matches!(result, Err(Error::UnsupportedType)));
@@ -4861,6 +4911,259 @@ pub mod tests {
         // std::fs::write("target/test.jpg", result).unwrap();
     }
 
+    #[actix::test]
+    #[cfg(feature = "pdf")]
+    async fn test_pdf_jumbf_generation_stream() {
+        let file_buffer = include_bytes!("../tests/fixtures/basic.pdf").to_vec();
+        let mut buf_io = Cursor::new(file_buffer.clone());
+
+        let mut store = Store::new();
+        let claim1 = create_test_claim().unwrap();
+        let signer = temp_signer();
+
+        store.commit_claim(claim1).unwrap();
+
+        let mut result_stream = Cursor::new(Vec::new());
+        store
+            .save_to_stream("pdf", &mut buf_io, &mut result_stream, signer.as_ref())
+            .unwrap();
+
+        // the manifest is added with an incremental update
+        let result = result_stream.into_inner();
+        assert!(result.starts_with(&file_buffer));
+
+        let mut report = DetailedStatusTracker::new();
+        let _new_store = Store::load_from_memory("pdf", &result, true, &mut report).unwrap();
+
+        let errors = report_split_errors(report.get_log_mut());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_formats_jumbf_generation_stream() {
+        let fixtures: [(&str, &[u8]); 18] = [
+            ("gif", include_bytes!("../tests/fixtures/sample1.gif")),
+            // bare codestreams are converted to the container format
+            (
+                "jxl",
+                include_bytes!("../tests/fixtures/sample1_codestream.jxl"),
+            ),
+            ("flac", include_bytes!("../tests/fixtures/sample1.flac")),
+            ("aiff", include_bytes!("../tests/fixtures/sample1.aiff")),
+            ("opus", include_bytes!("../tests/fixtures/sample1.opus")),
+            ("webm", include_bytes!("../tests/fixtures/sample1.webm")),
+            ("docx", include_bytes!("../tests/fixtures/sample1.docx")),
+            ("epub", include_bytes!("../tests/fixtures/sample1.epub")),
+            ("txt", include_bytes!("../tests/fixtures/sample1.txt")),
+            ("md", include_bytes!("../tests/fixtures/sample1.md")),
+            ("html", include_bytes!("../tests/fixtures/sample1.html")),
+            ("ttf", include_bytes!("../tests/fixtures/sample1.ttf")),
+            ("ts", include_bytes!("../tests/fixtures/sample1.ts")),
+            ("m2ts", include_bytes!("../tests/fixtures/sample1.m2ts")),
+            ("psd", include_bytes!("../tests/fixtures/Purple Square.psd")),
+            ("jp2", include_bytes!("../tests/fixtures/sample1.jp2")),
+            ("glb", include_bytes!("../tests/fixtures/sample1.glb")),
+            ("gltf", include_bytes!("../tests/fixtures/sample1.gltf")),
+        ];
+        let signer = temp_signer();
+
+        for (format, file_buffer) in fixtures {
+            let mut store = Store::new();
+            let claim1 = create_test_claim().unwrap();
+            store.commit_claim(claim1).unwrap();
+
+            let mut result_stream = Cursor::new(Vec::new());
+            store
+                .save_to_stream(
+                    format,
+                    &mut Cursor::new(file_buffer),
+                    &mut result_stream,
+                    signer.as_ref(),
+                )
+                .unwrap();
+
+            let result = result_stream.into_inner();
+
+            let mut report = DetailedStatusTracker::new();
+            let _new_store = Store::load_from_memory(format, &result, true, &mut report).unwrap();
+
+            let errors = report_split_errors(report.get_log_mut());
+            assert!(errors.is_empty(), "{format}");
+        }
+    }
+
+    #[test]
+    fn test_data_hash_single_jumbf_exclusion() {
+        // adjacent manifest blocks are excluded as one span, as before formats could
+        // report manifest blocks in more than one place
+        let fixtures: [(&str, &[u8]); 3] = [
+            (
+                "jpeg",
+                include_bytes!("../tests/fixtures/earth_apollo17.jpg"),
+            ),
+            ("png", include_bytes!("../tests/fixtures/libpng-test.png")),
+            ("tiff", include_bytes!("../tests/fixtures/TUSCANY.TIF")),
+        ];
+        let signer = temp_signer();
+
+        for (format, file_buffer) in fixtures {
+            // a large manifest is split over several JPEG segments
+            let mut claim1 = create_test_claim().unwrap();
+            claim1
+                .add_databox("text/plain", vec![b'x'; 100_000], None)
+                .unwrap();
+            let mut store = Store::new();
+            store.commit_claim(claim1).unwrap();
+
+            let mut result_stream = Cursor::new(Vec::new());
+            store
+                .save_to_stream(
+                    format,
+                    &mut Cursor::new(file_buffer),
+                    &mut result_stream,
+                    signer.as_ref(),
+                )
+                .unwrap();
+
+            let mut locations = get_caiwriter_handler(format)
+                .unwrap()
+                .get_object_locations_from_stream(&mut result_stream)
+                .unwrap();
+            locations.retain(|l| l.htype == HashBlockObjectType::Cai);
+            let start = locations.iter().map(|l| l.offset).min().unwrap();
+            let end = locations.iter().map(|l| l.offset + l.length).max().unwrap();
+            if format == "jpeg" {
+                assert!(locations.len() > 1);
+            }
+
+            let result = result_stream.into_inner();
+            let mut report = DetailedStatusTracker::new();
+            let new_store = Store::load_from_memory(format, &result, true, &mut report).unwrap();
+            assert!(report_split_errors(report.get_log_mut()).is_empty());
+
+            let claim = new_store.provenance_claim().unwrap();
+            let dh = DataHash::from_assertion(claim.hash_assertions()[0]).unwrap();
+            let exclusions = dh.exclusions.unwrap();
+            assert_eq!(exclusions.len(), 1, "{format}");
+            assert_eq!(exclusions[0].start(), start, "{format}");
+            assert_eq!(exclusions[0].length(), end - start, "{format}");
+        }
+    }
+
+    // splits a transport stream into HLS style segments, each starting at a PAT
+    fn ts_segments(data: &[u8], packet_len: usize) -> Vec<&[u8]> {
+        let prefix_len = packet_len - 188;
+        let pid = |packet: &[u8]| {
+            let header = &packet[prefix_len..];
+            (u16::from(header[1] & 0x1f) << 8) | u16::from(header[2])
+        };
+
+        let mut starts: Vec<usize> = data
+            .chunks(packet_len)
+            .enumerate()
+            .filter(|(_, packet)| pid(packet) == 0x0000)
+            .map(|(i, _)| i * packet_len)
+            .collect();
+        starts.push(data.len());
+
+        starts
+            .windows(2)
+            .map(|w| {
+                let segment = &data[w[0]..w[1]];
+                // the PMT follows the PAT
+                assert_eq!(pid(&segment[packet_len..]), 0x1000);
+                segment
+            })
+            .collect()
+    }
+
+    #[actix::test]
+    async fn test_ts_hls_segments_jumbf_generation_stream() {
+        // HLS segments start with a PAT and PMT and are signed individually
+        let fixtures: [(&str, &[u8], usize); 2] = [
+            ("ts", include_bytes!("../tests/fixtures/sample1.ts"), 188),
+            (
+                "m2ts",
+                include_bytes!("../tests/fixtures/sample1.m2ts"),
+                192,
+            ),
+        ];
+        let signer = temp_signer();
+
+        for (format, file_buffer, packet_len) in fixtures {
+            let segments = ts_segments(file_buffer, packet_len);
+            assert_eq!(segments.len(), 2);
+
+            let mut signed_segments = Vec::new();
+            for segment in segments {
+                let mut store = Store::new();
+                let claim1 = create_test_claim().unwrap();
+                store.commit_claim(claim1).unwrap();
+
+                let mut result_stream = Cursor::new(Vec::new());
+                store
+                    .save_to_stream(
+                        format,
+                        &mut Cursor::new(segment.to_vec()),
+                        &mut result_stream,
+                        signer.as_ref(),
+                    )
+                    .unwrap();
+                signed_segments.push(result_stream.into_inner());
+            }
+
+            for segment in &signed_segments {
+                let mut report = DetailedStatusTracker::new();
+                let _new_store =
+                    Store::load_from_memory(format, segment, true, &mut report).unwrap();
+
+                let errors = report_split_errors(report.get_log_mut());
+                assert!(errors.is_empty(), "{format}");
+            }
+
+            // changing media in one segment does not affect the other
+            let last = signed_segments[1].len() - 1;
+            signed_segments[1][last] ^= 0xff;
+
+            let mut report = DetailedStatusTracker::new();
+            Store::load_from_memory(format, &signed_segments[1], true, &mut report).unwrap();
+            let errors = report_split_errors(report.get_log_mut());
+            assert!(report_has_status(
+                &errors,
+                validation_status::ASSERTION_DATAHASH_MISMATCH
+            ));
+
+            let mut report = DetailedStatusTracker::new();
+            Store::load_from_memory(format, &signed_segments[0], true, &mut report).unwrap();
+            assert!(report_split_errors(report.get_log_mut()).is_empty());
+        }
+    }
+
+    #[actix::test]
+    async fn test_exr_multipart_jumbf_generation_stream() {
+        let file_buffer = include_bytes!("../tests/fixtures/sample1_multipart.exr").to_vec();
+        let mut buf_io = Cursor::new(file_buffer);
+
+        let mut store = Store::new();
+        let claim1 = create_test_claim().unwrap();
+        let signer = temp_signer();
+
+        store.commit_claim(claim1).unwrap();
+
+        let mut result_stream = Cursor::new(Vec::new());
+        store
+            .save_to_stream("exr", &mut buf_io, &mut result_stream, signer.as_ref())
+            .unwrap();
+
+        let result = result_stream.into_inner();
+
+        let mut report = DetailedStatusTracker::new();
+        let _new_store = Store::load_from_memory("exr", &result, true, &mut report).unwrap();
+
+        let errors = report_split_errors(report.get_log_mut());
+        assert!(errors.is_empty());
+    }
+
     #[test]
     #[cfg(feature = "file_io")]
     fn test_tiff_jumbf_generation() {
@@ -0,0 +1,424 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Identifies asset formats from their content rather than their file extension.
+
+use std::io::{Read, SeekFrom};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+
+use crate::{asset_io::CAIRead, utils::mime::format_to_mime};
+
+// enough to get past an XML prolog and to see all the ftyp brands
+const SNIFF_LEN: u64 = 4096;
+
+const DNG_VERSION_TAG: u16 = 0xc612;
+
+// MPEG transport stream packets, BDAV (m2ts) packets have a 4 byte timestamp prefix
+const TS_SYNC_BYTE: u8 = 0x47;
+const TS_PACKET_LEN: usize = 188;
+const M2TS_PACKET_LEN: usize = 192;
+
+// EBML DocType element, which names the Matroska variant
+const EBML_DOC_TYPE: [u8; 2] = [0x42, 0x82];
+
+const HEIC_BRANDS: [&[u8]; 8] = [
+    b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"hevm", b"hevs",
+];
+
+// MIME types that describe the same container, so a mix of them is not a mismatch
+const FORMAT_FAMILIES: [&[&str]; 12] = [
+    &[
+        "image/tiff",
+        "image/dng",
+        "image/x-adobe-dng",
+        "image/x-sony-arw",
+        "image/x-nikon-nef",
+    ],
+    &["image/heic", "image/heif"],
+    &["video/mp4", "audio/mp4", "application/mp4"],
+    &["audio/wav", "audio/wave", "audio/x-wav", "audio/vnd.wave"],
+    &[
+        "video/avi",
+        "video/msvideo",
+        "video/x-msvideo",
+        "application/x-troff-msvideo",
+    ],
+    &["image/svg+xml", "application/svg+xml"],
+    &["audio/aiff", "audio/x-aiff"],
+    &["audio/flac", "audio/x-flac"],
+    &["audio/ogg", "audio/opus"],
+    &[
+        "video/x-matroska",
+        "audio/x-matroska",
+        "video/webm",
+        "audio/webm",
+    ],
+    &["font/ttf", "font/otf", "font/sfnt"],
+    &[
+        "application/zip",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "application/epub+zip",
+    ],
+];
+
+/// Identifies the format of an asset from its leading bytes.
+///
+/// Returns the MIME type of the asset, or `None` if the content is not recognized.
+/// The stream is rewound before returning.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+///
+/// let mut stream = Cursor::new(include_bytes!("../../tests/fixtures/CA.jpg"));
+/// assert_eq!(
+///     c2pa::detect_format(&mut stream).as_deref(),
+///     Some("image/jpeg")
+/// );
+/// ```
+pub fn detect_format(stream: &mut dyn CAIRead) -> Option<String> {
+    let format = sniff_format(stream);
+    stream.rewind().ok()?;
+    format.map(str::to_owned)
+}
+
+// Returns true if a declared format is consistent with the detected one.
+pub(crate) fn format_matches(declared: &str, detected: &str) -> bool {
+    let declared = format_to_mime(&declared.to_lowercase());
+    let detected = format_to_mime(detected);
+
+    declared == detected
+        || FORMAT_FAMILIES.iter().any(|family| {
+            family.contains(&declared.as_str()) && family.contains(&detected.as_str())
+        })
+}
+
+fn sniff_format(stream: &mut dyn CAIRead) -> Option<&'static str> {
+    stream.rewind().ok()?;
+    let mut header = Vec::new();
+    stream.take(SNIFF_LEN).read_to_end(&mut header).ok()?;
+
+    let format = match header.as_slice() {
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, ..] => "image/png",
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, subtype @ ..] => riff_format(subtype)?,
+        [b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', b'F' | b'C', ..] => "audio/aiff",
+        [b'I', b'I', 0x2a, 0x00, ..] => tiff_format::<LittleEndian>(stream),
+        [b'M', b'M', 0x00, 0x2a, ..] => tiff_format::<BigEndian>(stream),
+        [b'I', b'I', 0x2b, 0x00, ..] | [b'M', b'M', 0x00, 0x2b, ..] => "image/tiff", // BigTIFF
+        [b'I', b'D', b'3', ..] => "audio/mpeg",
+        [b'%', b'P', b'D', b'F', ..] => "application/pdf",
+        [b'f', b'L', b'a', b'C', ..] => "audio/flac",
+        [b'O', b'g', b'g', b'S', ..] => "audio/ogg",
+        [b'8', b'B', b'P', b'S', ..] => "image/vnd.adobe.photoshop",
+        [0x76, 0x2f, 0x31, 0x01, ..] => "image/x-exr",
+        [b'g', b'l', b'T', b'F', ..] => "model/gltf-binary",
+        [0x1a, 0x45, 0xdf, 0xa3, ..] => matroska_format(&header),
+        [0x00, 0x01, 0x00, 0x00, ..] | [b't', b'r', b'u', b'e', ..] => "font/ttf",
+        [b'O', b'T', b'T', b'O', ..] => "font/otf",
+        [b'P', b'K', 0x03, 0x04, ..] => zip_format(&header),
+        [0xff, 0x0a, ..] | [0, 0, 0, 0x0c, b'J', b'X', b'L', b' ', ..] => "image/jxl",
+        [0, 0, 0, 0x0c, b'j', b'P', b' ', b' ', ..] => jp2_format(&header),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => bmff_format(&header)?,
+        [_, _, _, _, b'j', b'u', b'm', b'b', ..] => "application/c2pa",
+        h if is_mpegts(h, 0, TS_PACKET_LEN) || is_mpegts(h, 4, M2TS_PACKET_LEN) => "video/mp2t",
+        h if is_svg(h) => "image/svg+xml",
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+fn riff_format(subtype: &[u8]) -> Option<&'static str> {
+    Some(match subtype.get(0..4)? {
+        b"WAVE" => "audio/wav",
+        b"AVI " => "video/avi",
+        b"WEBP" => "image/webp",
+        _ => return None,
+    })
+}
+
+// the first two packets must start with a sync byte
+fn is_mpegts(header: &[u8], offset: usize, packet_len: usize) -> bool {
+    header.get(offset) == Some(&TS_SYNC_BYTE)
+        && header.get(offset + packet_len) == Some(&TS_SYNC_BYTE)
+}
+
+// WebM is a restricted Matroska, told apart by the DocType in the EBML header
+fn matroska_format(header: &[u8]) -> &'static str {
+    let doc_type = header
+        .windows(EBML_DOC_TYPE.len())
+        .position(|w| w == EBML_DOC_TYPE)
+        .and_then(|pos| {
+            // one byte EBML size, which is how a short DocType is written
+            let len = usize::from(header.get(pos + 2)? & 0x7f);
+            header.get(pos + 3..pos + 3 + len)
+        });
+
+    match doc_type {
+        Some(b"webm") => "video/webm",
+        _ => "video/x-matroska",
+    }
+}
+
+// the local file header names of the first entries identify the document type
+fn zip_format(header: &[u8]) -> &'static str {
+    let contains = |needle: &[u8]| header.windows(needle.len()).any(|w| w == needle);
+
+    if contains(b"mimetypeapplication/epub+zip") {
+        "application/epub+zip"
+    } else if contains(b"word/") {
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+    } else if contains(b"xl/") {
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    } else if contains(b"ppt/") {
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+    } else {
+        "application/zip"
+    }
+}
+
+// DNG is a TIFF with a DNGVersion tag in the first IFD
+fn tiff_format<B: ByteOrder>(stream: &mut dyn CAIRead) -> &'static str {
+    if has_dng_version::<B>(stream).unwrap_or(false) {
+        "image/x-adobe-dng"
+    } else {
+        "image/tiff"
+    }
+}
+
+fn has_dng_version<B: ByteOrder>(stream: &mut dyn CAIRead) -> Option<bool> {
+    stream.seek(SeekFrom::Start(4)).ok()?;
+    let ifd0 = stream.read_u32::<B>().ok()?;
+
+    stream.seek(SeekFrom::Start(u64::from(ifd0))).ok()?;
+    let entry_cnt = stream.read_u16::<B>().ok()?;
+
+    let mut entries = vec![0u8; usize::from(entry_cnt) * 12];
+    stream.read_exact(&mut entries).ok()?;
+
+    Some(
+        entries
+            .chunks_exact(12)
+            .any(|entry| B::read_u16(&entry[0..2]) == DNG_VERSION_TAG),
+    )
+}
+
+fn jp2_format(header: &[u8]) -> &'static str {
+    // the file type box follows the signature box
+    match header.get(20..24) {
+        Some(b"jpx ") => "image/jpx",
+        _ => "image/jp2",
+    }
+}
+
+fn bmff_format(header: &[u8]) -> Option<&'static str> {
+    let ftyp_len = usize::try_from(BigEndian::read_u32(header.get(0..4)?)).ok()?;
+    let major_brand = header.get(8..12)?;
+    let compatible_brands: Vec<&[u8]> = header
+        .get(16..ftyp_len.min(header.len()))
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .collect();
+
+    let is_avif = |brand: &[u8]| brand == b"avif" || brand == b"avis";
+    let is_heic = |brand: &[u8]| HEIC_BRANDS.contains(&brand);
+
+    Some(match major_brand {
+        brand if is_avif(brand) => "image/avif",
+        brand if is_heic(brand) => "image/heic",
+        b"mif1" | b"msf1" => {
+            if compatible_brands.iter().any(|brand| is_avif(brand)) {
+                "image/avif"
+            } else if compatible_brands.iter().any(|brand| is_heic(brand)) {
+                "image/heic"
+            } else {
+                "image/heif"
+            }
+        }
+        b"qt  " => "video/quicktime",
+        b"M4A " | b"M4B " | b"M4P " => "audio/mp4",
+        _ => "video/mp4",
+    })
+}
+
+// the root element must be svg, after any XML declaration, comments or doctype
+fn is_svg(header: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(header);
+    let mut rest = text.trim_start_matches('\u{feff}').trim_start();
+
+    loop {
+        let end = if rest.starts_with("<?") {
+            "?>"
+        } else if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<!") {
+            ">"
+        } else {
+            break;
+        };
+
+        match rest.find(end) {
+            Some(pos) => rest = rest[pos + end.len()..].trim_start(),
+            None => return false,
+        }
+    }
+
+    rest.starts_with("<svg")
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::io::{Cursor, Seek};
+
+    use super::*;
+    use crate::utils::test::fixture_path;
+
+    fn detect_fixture(fixture: &str) -> Option<String> {
+        let mut stream = std::fs::File::open(fixture_path(fixture)).unwrap();
+        detect_format(&mut stream)
+    }
+
+    #[test]
+    fn test_detect_fixtures() {
+        let expected = [
+            ("CA.jpg", "image/jpeg"),
+            ("sample1.png", "image/png"),
+            ("sample1.gif", "image/gif"),
+            ("sample1.wav", "audio/wav"),
+            ("test.avi", "video/avi"),
+            ("sample1.webp", "image/webp"),
+            ("sample1.avif", "image/avif"),
+            ("sample1.heic", "image/heic"),
+            ("sample1.heif", "image/heic"),
+            ("video1.mp4", "video/mp4"),
+            ("TUSCANY.TIF", "image/tiff"),
+            ("sample1.svg", "image/svg+xml"),
+            ("sample1.mp3", "audio/mpeg"),
+            ("basic.pdf", "application/pdf"),
+            ("cloud_manifest.c2pa", "application/c2pa"),
+            ("sample1.flac", "audio/flac"),
+            ("sample1.aiff", "audio/aiff"),
+            ("sample1.jxl", "image/jxl"),
+            ("sample1.jp2", "image/jp2"),
+            ("sample1.glb", "model/gltf-binary"),
+            ("sample1.exr", "image/x-exr"),
+            ("Purple Square.psd", "image/vnd.adobe.photoshop"),
+            ("sample1.opus", "audio/ogg"),
+            ("sample1.webm", "video/webm"),
+            ("sample1.ts", "video/mp2t"),
+            ("sample1.m2ts", "video/mp2t"),
+            ("sample1.ttf", "font/ttf"),
+            (
+                "sample1.docx",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            ),
+            ("sample1.epub", "application/epub+zip"),
+        ];
+
+        for (fixture, format) in expected {
+            assert_eq!(
+                detect_fixture(fixture).as_deref(),
+                Some(format),
+                "{fixture}"
+            );
+        }
+
+        assert_eq!(detect_fixture("sample1.txt"), None);
+        assert_eq!(detect_fixture("sample1.html"), None);
+    }
+
+    #[test]
+    fn test_detect_rewinds() {
+        let mut stream = std::fs::File::open(fixture_path("sample1.png")).unwrap();
+        stream.seek(SeekFrom::Start(20)).unwrap();
+
+        assert_eq!(detect_format(&mut stream).as_deref(), Some("image/png"));
+        assert_eq!(stream.stream_position().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_detect_dng() {
+        // little endian TIFF with a single DNGVersion entry in IFD0
+        let mut dng = vec![b'I', b'I', 0x2a, 0x00, 0x08, 0x00, 0x00, 0x00, 0x01, 0x00];
+        dng.extend_from_slice(&[0x12, 0xc6, 0x01, 0x00, 0x04, 0x00, 0x00, 0x00]);
+        dng.extend_from_slice(&[0x01, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+        assert_eq!(
+            detect_format(&mut Cursor::new(dng)).as_deref(),
+            Some("image/x-adobe-dng")
+        );
+    }
+
+    #[test]
+    fn test_detect_signatures() {
+        let detect = |header: &[u8]| detect_format(&mut Cursor::new(header.to_vec()));
+
+        let mkv = b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01\x42\x82\x88matroska";
+        assert_eq!(detect(mkv).as_deref(), Some("video/x-matroska"));
+
+        let mut ts = vec![0u8; TS_PACKET_LEN * 2];
+        ts[0] = TS_SYNC_BYTE;
+        ts[TS_PACKET_LEN] = TS_SYNC_BYTE;
+        assert_eq!(detect(&ts).as_deref(), Some("video/mp2t"));
+        ts[TS_PACKET_LEN] = 0;
+        assert_eq!(detect(&ts), None);
+
+        let mut m2ts = vec![0u8; M2TS_PACKET_LEN * 2];
+        m2ts[4] = TS_SYNC_BYTE;
+        m2ts[4 + M2TS_PACKET_LEN] = TS_SYNC_BYTE;
+        assert_eq!(detect(&m2ts).as_deref(), Some("video/mp2t"));
+
+        assert_eq!(
+            detect(b"\x00\x01\x00\x00\x00\x09").as_deref(),
+            Some("font/ttf")
+        );
+        assert_eq!(detect(b"true\x00\x09").as_deref(), Some("font/ttf"));
+        assert_eq!(detect(b"OTTO\x00\x09").as_deref(), Some("font/otf"));
+
+        assert_eq!(
+            detect(b"PK\x03\x04\x14\x00\x00\x00").as_deref(),
+            Some("application/zip")
+        );
+    }
+
+    #[test]
+    fn test_detect_svg_root() {
+        let svg = b"\xef\xbb\xbf<?xml version=\"1.0\"?>\n<!-- a <comment> -->\n<svg:svg/>";
+        assert!(is_svg(svg));
+
+        let html = b"<!DOCTYPE html><html><body><svg></svg></body></html>";
+        assert!(!is_svg(html));
+    }
+
+    #[test]
+    fn test_format_matches() {
+        assert!(format_matches("jpg", "image/jpeg"));
+        assert!(format_matches("IMAGE/JPEG", "image/jpeg"));
+        assert!(format_matches("dng", "image/tiff"));
+        assert!(format_matches("image/heif", "image/heic"));
+        assert!(format_matches("c2pa", "application/c2pa"));
+        assert!(format_matches("audio/opus", "audio/ogg"));
+        assert!(format_matches("mka", "video/x-matroska"));
+        assert!(format_matches("otf", "font/ttf"));
+        assert!(format_matches("docx", "application/zip"));
+        assert!(!format_matches("image/png", "image/jpeg"));
+        assert!(!format_matches("wav", "image/webp"));
+    }
+}
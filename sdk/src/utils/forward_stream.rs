@@ -0,0 +1,401 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+//! Presents a forward-only source as a seekable stream.
+//!
+//! The source is read once, front to back, and only as far as the consumer needs.
+//!
+//! JPEG and MP3 are processed in a single forward pass. Their handlers revisit only
+//! the leading segments or tags, and the data hash is computed front to back, so
+//! only the first `core.max_memory_usage` bytes are kept in memory and the rest of
+//! the source is passed straight through to the hasher or the output. The manifest
+//! store must lie within those leading bytes to be validated.
+//!
+//! Other formats are read out of order and spooled in full before any processing
+//! starts: BMFF patches its stco/co64 chunk offsets, TIFF its IFD offsets, the PNG
+//! reader checks every chunk for a second manifest store before going back to hash
+//! the image, and the RIFF parser reads chunks more than once. The spool lives in
+//! memory up to `core.max_memory_usage` bytes and in a temporary file beyond that,
+//! up to `core.max_spool_size` bytes.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use tempfile::SpooledTempFile;
+
+use crate::{
+    jumbf_io::{is_bmff_format, is_tiff_format},
+    settings::get_settings_value,
+    utils::mime::format_to_mime,
+};
+
+// default number of bytes kept in memory before the spool moves to a file
+const DEFAULT_MEMORY_LIMIT: u64 = 16 * 1024 * 1024;
+
+// largest read from the source at one time
+const PULL_SIZE: usize = 64 * 1024;
+
+// formats whose handlers read the asset front to back, revisiting only the leading bytes
+const SINGLE_PASS_FORMATS: [&str; 2] = ["image/jpeg", "audio/mpeg"];
+
+/// Returns true if the format must be spooled in full before it can be processed.
+pub(crate) fn needs_back_patching(format: &str) -> bool {
+    let format = format_to_mime(&format.to_lowercase());
+    is_bmff_format(&format)
+        || is_tiff_format(&format)
+        || !SINGLE_PASS_FORMATS.contains(&format.as_str())
+}
+
+pub(crate) struct ForwardStream<R> {
+    source: R,
+    spool: SpooledTempFile,
+    pulled: u64, // number of bytes pulled from the source
+    retain: u64, // number of leading bytes kept in the spool
+    pos: u64,
+    max_len: u64,
+    exhausted: bool,
+    missed: bool, // a read needed bytes that were not retained
+}
+
+impl<R: Read> ForwardStream<R> {
+    /// Spools everything read from the source, in a temporary file beyond `memory_limit`.
+    pub(crate) fn new(source: R, memory_limit: u64, max_len: u64) -> Self {
+        Self {
+            source,
+            spool: SpooledTempFile::new(usize::try_from(memory_limit).unwrap_or(usize::MAX)),
+            pulled: 0,
+            retain: u64::MAX,
+            pos: 0,
+            max_len,
+            exhausted: false,
+            missed: false,
+        }
+    }
+
+    /// Keeps only the first `retain` bytes of the source in memory.
+    pub(crate) fn single_pass(source: R, retain: u64) -> Self {
+        Self {
+            retain,
+            ..Self::new(source, retain, u64::MAX)
+        }
+    }
+
+    /// Wraps a source for the handler of `format`, using the spool limits from settings.
+    pub(crate) fn for_format(format: &str, source: R) -> io::Result<Self> {
+        let memory_limit =
+            get_settings_value::<u64>("core.max_memory_usage").unwrap_or(DEFAULT_MEMORY_LIMIT);
+        let max_len = get_settings_value::<u64>("core.max_spool_size").unwrap_or(u64::MAX);
+
+        if !needs_back_patching(format) {
+            return Ok(Self::single_pass(source, memory_limit));
+        }
+
+        let mut stream = Self::new(source, memory_limit, max_len);
+        stream.spool_all()?;
+        Ok(stream)
+    }
+
+    /// Fails if a consumer needed bytes that were passed through without being retained.
+    ///
+    /// Handlers may swallow read errors, for example a failed hash is reported as a
+    /// mismatch, so this is checked once processing is done.
+    pub(crate) fn check_single_pass(&self) -> io::Result<()> {
+        if self.missed {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "the asset must be read out of order beyond its first {} bytes, \
+                     increase core.max_memory_usage to read it",
+                    self.retain
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    // number of leading bytes that can be read again
+    fn spooled(&self) -> u64 {
+        self.pulled.min(self.retain)
+    }
+
+    // read the next block of the source, returns 0 at the end of the source
+    fn read_source(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.exhausted {
+            return Ok(0);
+        }
+
+        let len = loop {
+            match self.source.read(buf) {
+                Ok(len) => break len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+
+        if len == 0 {
+            self.exhausted = true;
+        }
+        self.pulled += len as u64;
+
+        Ok(len)
+    }
+
+    // Pull the next block of the source, spooling it if it is one of the retained bytes.
+    // Bytes past the retained ones are discarded, so no more than `limit` of them are pulled.
+    fn pull(&mut self, limit: u64) -> io::Result<usize> {
+        // a block never straddles the end of the retained bytes
+        let retained_left = self.retain - self.spooled();
+        let limit = match retained_left {
+            0 => limit,
+            left => left,
+        };
+        let mut buf = vec![0u8; usize::try_from(limit).map_or(PULL_SIZE, |l| l.min(PULL_SIZE))];
+
+        let start = self.pulled;
+        let len = self.read_source(&mut buf)?;
+        if retained_left == 0 {
+            return Ok(len);
+        }
+
+        if self.pulled > self.max_len {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "stream exceeds the maximum spool size",
+            ));
+        }
+
+        self.spool.seek(SeekFrom::Start(start))?;
+        self.spool.write_all(&buf[..len])?;
+
+        Ok(len)
+    }
+
+    // pull from the source until `offset` bytes are pulled or the source ends
+    fn fill_to(&mut self, offset: u64) -> io::Result<()> {
+        while self.pulled < offset && self.pull(offset - self.pulled)? > 0 {}
+        Ok(())
+    }
+
+    /// Pulls the rest of the source, returning its total length.
+    pub(crate) fn spool_all(&mut self) -> io::Result<u64> {
+        self.fill_to(u64::MAX)?;
+        Ok(self.pulled)
+    }
+}
+
+impl<R: Read> Read for ForwardStream<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.pos >= self.retain {
+            // bytes past the retained ones are read straight from the source
+            if self.pos < self.pulled {
+                self.missed = true;
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "cannot seek back past the retained bytes of a forward-only stream",
+                ));
+            }
+            self.fill_to(self.pos)?;
+            if self.pos > self.pulled {
+                return Ok(0);
+            }
+
+            let len = self.read_source(buf)?;
+            self.pos += len as u64;
+            return Ok(len);
+        }
+
+        self.fill_to(self.pos.saturating_add(1))?;
+        if self.pos >= self.spooled() {
+            return Ok(0);
+        }
+
+        let available = usize::try_from(self.spooled() - self.pos).unwrap_or(usize::MAX);
+        let len = available.min(buf.len());
+
+        self.spool.seek(SeekFrom::Start(self.pos))?;
+        let len = self.spool.read(&mut buf[..len])?;
+        self.pos += len as u64;
+
+        Ok(len)
+    }
+}
+
+impl<R: Read> Seek for ForwardStream<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        // seeking past what has been pulled is deferred until the next read
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.spool_all()?.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        self.pos = new_pos;
+        Ok(new_pos)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    // a source that can only be read, and only in small pieces
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(7);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn data() -> Vec<u8> {
+        (0..100_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_reads_lazily() {
+        let data = data();
+        let mut stream = ForwardStream::new(Trickle(&data), 1024, u64::MAX);
+
+        let mut buf = [0u8; 10];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[..10]);
+        assert!(stream.pulled < data.len() as u64);
+
+        // skip ahead without reading, then come back
+        stream.seek(SeekFrom::Start(50_000)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[50_000..50_010]);
+        assert!(stream.pulled < data.len() as u64);
+
+        stream.seek(SeekFrom::Current(-30_010)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[20_000..20_010]);
+    }
+
+    #[test]
+    fn test_seek_end() {
+        let data = data();
+        let mut stream = ForwardStream::new(Trickle(&data), 1024, u64::MAX);
+
+        assert_eq!(stream.seek(SeekFrom::End(0)).unwrap(), data.len() as u64);
+        assert_eq!(
+            stream.seek(SeekFrom::End(-4)).unwrap(),
+            data.len() as u64 - 4
+        );
+
+        let mut tail = Vec::new();
+        stream.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, data[data.len() - 4..]);
+
+        stream.rewind().unwrap();
+        let mut all = Vec::new();
+        stream.read_to_end(&mut all).unwrap();
+        assert_eq!(all, data);
+
+        assert!(stream.seek(SeekFrom::Current(-1_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_spool_limit() {
+        let data = data();
+        let mut stream = ForwardStream::new(data.as_slice(), 1024, 1000);
+
+        let mut all = Vec::new();
+        assert!(stream.read_to_end(&mut all).is_err());
+    }
+
+    #[test]
+    fn test_single_pass() {
+        let data = data();
+        let mut stream = ForwardStream::single_pass(Trickle(&data), 1024);
+
+        // reads past the retained bytes are passed through
+        let mut buf = [0u8; 10];
+        stream.seek(SeekFrom::Start(50_000)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[50_000..50_010]);
+
+        // the leading bytes can still be read again
+        stream.seek(SeekFrom::Start(100)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[100..110]);
+        assert!(stream.check_single_pass().is_ok());
+
+        // the bytes that were passed through cannot
+        stream.seek(SeekFrom::Start(20_000)).unwrap();
+        assert!(stream.read_exact(&mut buf).is_err());
+        assert!(stream.check_single_pass().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "unstable_api")]
+    fn test_single_pass_formats() {
+        use crate::{
+            utils::test::{fixture_path, temp_signer},
+            Builder, Reader,
+        };
+
+        let signer = temp_signer();
+        for (fixture, format) in [
+            ("IMG_0003.jpg", "image/jpeg"),
+            ("sample1.mp3", "audio/mpeg"),
+        ] {
+            let source = std::fs::read(fixture_path(fixture)).unwrap();
+            let mut builder = Builder::from_json(r#"{"title": "single pass"}"#).unwrap();
+
+            let mut source = ForwardStream::single_pass(source.as_slice(), 1024);
+            let mut signed = std::io::Cursor::new(Vec::new());
+            builder
+                .sign(signer.as_ref(), format, &mut source, &mut signed)
+                .unwrap();
+            assert!(source.check_single_pass().is_ok());
+            let signed = signed.into_inner();
+
+            let mut stream = ForwardStream::single_pass(signed.as_slice(), 128 * 1024);
+            let reader = Reader::from_stream(format, &mut stream).unwrap();
+            assert!(stream.check_single_pass().is_ok());
+            assert_eq!(reader.validation_status(), None);
+
+            // the manifest store extends past the retained bytes
+            let mut stream = ForwardStream::single_pass(signed.as_slice(), 1024);
+            let _ = Reader::from_stream(format, &mut stream);
+            assert!(stream.check_single_pass().is_err());
+        }
+    }
+
+    #[test]
+    fn test_back_patching_formats() {
+        assert!(needs_back_patching("video/mp4"));
+        assert!(needs_back_patching("image/heic"));
+        assert!(needs_back_patching("image/tiff"));
+        assert!(needs_back_patching("DNG"));
+        assert!(needs_back_patching("png"));
+        assert!(needs_back_patching("audio/wav"));
+        assert!(!needs_back_patching("image/jpeg"));
+        assert!(!needs_back_patching("mp3"));
+    }
+}
@@ -32,6 +32,9 @@ use crate::{Error, Result};
 
 const MAX_HASH_BUF: usize = 256 * 1024 * 1024; // cap memory usage to 256MB
 
+// end of a range that runs to the end of the data, so the data length is not needed up front
+const OPEN_END: u64 = u64::MAX;
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct HashRange {
     start: usize,
@@ -232,7 +235,6 @@ where
         }
     };
 
-    let data_len = data.seek(SeekFrom::End(0))?;
     data.rewind()?;
 
     let ranges = match hash_range {
@@ -241,22 +243,10 @@ where
             // sort the exclusions
             hr.sort_by_key(|a| a.start());
 
-            // verify structure of blocks
-            let num_blocks = hr.len();
-            let range_end = hr[num_blocks - 1].start() + hr[num_blocks - 1].length();
-            let data_end = data_len - 1;
-
-            // range extends past end of file so fail
-            if data_len < range_end as u64 {
-                return Err(Error::BadParam(
-                    "The exclusion range exceed the data length".to_string(),
-                ));
-            }
-
             if is_exclusion {
                 //build final ranges
                 let mut ranges_vec: Vec<RangeInclusive<u64>> = Vec::new();
-                let mut ranges = RangeSet::<[RangeInclusive<u64>; 1]>::from(0..=data_end);
+                let mut ranges = RangeSet::<[RangeInclusive<u64>; 1]>::from(0..=OPEN_END);
                 for exclusion in hr {
                     let end = (exclusion.start() + exclusion.length() - 1) as u64;
                     let exclusion_start = exclusion.start() as u64;
@@ -321,8 +311,7 @@ where
         }
         _ => {
             let mut ranges_vec: Vec<RangeInclusive<u64>> = Vec::new();
-            let data_end = data_len - 1;
-            ranges_vec.push(RangeInclusive::new(0_u64, data_end));
+            ranges_vec.push(RangeInclusive::new(0_u64, OPEN_END));
 
             ranges_vec
         }
@@ -333,10 +322,10 @@ where
         for r in ranges {
             let start = r.start();
             let end = r.end();
-            let mut chunk_left = end - start + 1;
+            let mut chunk_left = range_len(&r);
 
             // move to start of range
-            data.seek(SeekFrom::Start(*start))?;
+            seek_range_start(data, *start, *end)?;
 
             // check to see if this range is an BMFF V2 offset to include in the hash
             if bmff_v2_starts.contains(start) && (end - start) == 0 {
@@ -344,14 +333,11 @@ where
             }
 
             loop {
-                let mut chunk = vec![0u8; std::cmp::min(chunk_left as usize, MAX_HASH_BUF)];
-
-                data.read_exact(&mut chunk)?;
+                let chunk = read_hash_chunk(data, chunk_left)?;
 
                 hasher_enum.update(&chunk);
 
-                chunk_left -= chunk.len() as u64;
-                if chunk_left == 0 {
+                if is_last_chunk(&mut chunk_left, chunk.len()) {
                     break;
                 }
             }
@@ -361,23 +347,22 @@ where
         for r in ranges {
             let start = r.start();
             let end = r.end();
-            let mut chunk_left = end - start + 1;
+            let mut chunk_left = range_len(&r);
 
             // move to start of range
-            data.seek(SeekFrom::Start(*start))?;
+            seek_range_start(data, *start, *end)?;
 
             // check to see if this range is an BMFF V2 offset to include in the hash
             if bmff_v2_starts.contains(start) && (end - start) == 0 {
                 hasher_enum.update(&start.to_be_bytes());
             }
 
-            let mut chunk = vec![0u8; std::cmp::min(chunk_left as usize, MAX_HASH_BUF)];
-            data.read_exact(&mut chunk)?;
+            let mut chunk = read_hash_chunk(data, chunk_left)?;
 
             loop {
                 let (tx, rx) = std::sync::mpsc::channel();
 
-                chunk_left -= chunk.len() as u64;
+                let last_chunk = is_last_chunk(&mut chunk_left, chunk.len());
 
                 std::thread::spawn(move || {
                     hasher_enum.update(&chunk);
@@ -385,7 +370,7 @@ where
                 });
 
                 // are we done
-                if chunk_left == 0 {
+                if last_chunk {
                     hasher_enum = match rx.recv() {
                         Ok(hasher) => hasher,
                         Err(_) => return Err(Error::ThreadReceiveError),
@@ -394,8 +379,7 @@ where
                 }
 
                 // read next chunk while we wait for hash
-                let mut next_chunk = vec![0u8; std::cmp::min(chunk_left as usize, MAX_HASH_BUF)];
-                data.read_exact(&mut next_chunk)?;
+                let next_chunk = read_hash_chunk(data, chunk_left)?;
 
                 hasher_enum = match rx.recv() {
                     Ok(hasher) => hasher,
@@ -411,6 +395,62 @@ where
     Ok(Hasher::finalize(hasher_enum))
 }
 
+// number of bytes in a hash range, None if it runs to the end of the data
+fn range_len(range: &RangeInclusive<u64>) -> Option<u64> {
+    match *range.end() {
+        OPEN_END => None,
+        end => Some(end - range.start() + 1),
+    }
+}
+
+// Moves to the start of a hash range.  The stream length is not known up front, so
+// before the range that runs to the end of the data, the last excluded byte is read
+// to make sure the exclusions do not extend past the end of the data.
+fn seek_range_start<R>(data: &mut R, start: u64, end: u64) -> Result<()>
+where
+    R: Read + Seek + ?Sized,
+{
+    if end == OPEN_END && start > 0 {
+        data.seek(SeekFrom::Start(start - 1))?;
+        let mut last_excluded = [0u8; 1];
+        data.read_exact(&mut last_excluded).map_err(|_| {
+            Error::BadParam("The exclusion range exceed the data length".to_string())
+        })?;
+    } else {
+        data.seek(SeekFrom::Start(start))?;
+    }
+    Ok(())
+}
+
+// reads the next chunk of a hash range, reading to the end of the data for an open range
+fn read_hash_chunk<R>(data: &mut R, chunk_left: Option<u64>) -> Result<Vec<u8>>
+where
+    R: Read + ?Sized,
+{
+    let len = chunk_left.map_or(MAX_HASH_BUF, |left| {
+        std::cmp::min(left as usize, MAX_HASH_BUF)
+    });
+
+    let mut chunk = Vec::new();
+    let reader = &mut *data;
+    reader.take(len as u64).read_to_end(&mut chunk)?;
+    if chunk_left.is_some() && chunk.len() < len {
+        return Err(Error::IoError(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(chunk)
+}
+
+// accounts for a chunk read from a range, returns true when the range is complete
+fn is_last_chunk(chunk_left: &mut Option<u64>, chunk_len: usize) -> bool {
+    match chunk_left {
+        Some(left) => {
+            *left -= chunk_len as u64;
+            *left == 0
+        }
+        None => chunk_len < MAX_HASH_BUF,
+    }
+}
+
 // verify the hash using the specified algorithm
 pub fn verify_by_alg(
     alg: &str,
@@ -16,8 +16,11 @@ pub fn extension_to_mime(extension: &str) -> Option<&'static str> {
     Some(match extension {
         "jpg" | "jpeg" => "image/jpeg",
         "png" => "image/png",
+        "jxl" => "image/jxl",
+        "jp2" | "jpf" => "image/jp2",
+        "jpx" => "image/jpx",
         "gif" => "image/gif",
-        "psd" => "image/vnd.adobe.photoshop",
+        "psd" | "psb" => "image/vnd.adobe.photoshop",
         "tiff" | "tif" => "image/tiff",
         "svg" => "image/svg+xml",
         "ico" => "image/x-icon",
@@ -26,18 +29,35 @@ pub fn extension_to_mime(extension: &str) -> Option<&'static str> {
         "dng" => "image/x-adobe-dng",
         "heic" => "image/heic",
         "heif" => "image/heif",
+        "exr" => "image/x-exr",
         "mp2" | "mpa" | "mpe" | "mpeg" | "mpg" | "mpv2" => "video/mpeg",
         "mp4" => "video/mp4",
+        "ts" | "m2ts" | "mts" => "video/mp2t",
         "avi" => "video/avi",
         "avif" => "image/avif",
         "mov" | "qt" => "video/quicktime",
+        "mkv" => "video/x-matroska",
+        "mka" => "audio/x-matroska",
+        "webm" => "video/webm",
         "m4a" => "audio/mp4",
         "mid" | "rmi" => "audio/mid",
         "mp3" => "audio/mpeg",
         "wav" => "audio/wav",
+        "flac" => "audio/flac",
         "aif" | "aifc" | "aiff" => "audio/aiff",
-        "ogg" => "audio/ogg",
+        "ogg" | "oga" | "opus" => "audio/ogg",
         "pdf" => "application/pdf",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "epub" => "application/epub+zip",
+        "txt" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "otf" => "font/otf",
+        "ttf" => "font/ttf",
+        "glb" => "model/gltf-binary",
+        "gltf" => "model/gltf+json",
         "ai" => "application/postscript",
         "arw" => "image/x-sony-arw",
         "nef" => "image/x-nikon-nef",
@@ -63,8 +83,12 @@ pub fn format_to_extension(format: &str) -> Option<&'static str> {
     Some(match format {
         "jpg" | "jpeg" | "image/jpeg" => "jpg",
         "png" | "image/png" => "png",
+        "jxl" | "image/jxl" => "jxl",
+        "jp2" | "jpf" | "image/jp2" => "jp2",
+        "jpx" | "image/jpx" => "jpx",
         "gif" | "image/gif" => "gif",
         "psd" | "image/vnd.adobe.photoshop" => "psd",
+        "psb" => "psb",
         "tiff" | "tif" | "image/tiff" => "tiff",
         "svg" | "image/svg+xml" => "svg",
         "ico" | "image/x-icon" => "ico",
@@ -73,18 +97,39 @@ pub fn format_to_extension(format: &str) -> Option<&'static str> {
         "dng" | "image/dng" => "dng",
         "heic" | "image/heic" => "heic",
         "heif" | "image/heif" => "heif",
+        "exr" | "image/x-exr" => "exr",
         "mp2" | "mpa" | "mpe" | "mpeg" | "mpg" | "mpv2" | "video/mpeg" => "mp2",
         "mp4" | "video/mp4" => "mp4",
+        "ts" | "m2ts" | "mts" | "video/mp2t" => "ts",
         "avif" | "image/avif" => "avif",
         "avi" | "video/avi" => "avi",
         "mov" | "qt" | "video/quicktime" => "mov",
+        "mkv" | "video/x-matroska" => "mkv",
+        "mka" | "audio/x-matroska" => "mka",
+        "webm" | "video/webm" | "audio/webm" => "webm",
         "m4a" | "audio/mp4" => "m4a",
         "mid" | "rmi" | "audio/mid" => "mid",
         "mp3" | "audio/mpeg" => "mp3",
         "wav" | "audio/wav" | "audio/wave" | "audio.vnd.wave" => "wav",
-        "aif" | "aifc" | "aiff" | "audio/aiff" => "aif",
-        "ogg" | "audio/ogg" => "ogg",
+        "flac" | "audio/flac" | "audio/x-flac" => "flac",
+        "aif" | "aifc" | "aiff" | "audio/aiff" | "audio/x-aiff" => "aif",
+        "ogg" | "oga" | "opus" | "audio/ogg" | "audio/opus" => "ogg",
         "pdf" | "application/pdf" => "pdf",
+        "docx" | "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            "docx"
+        }
+        "xlsx" | "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => "xlsx",
+        "pptx" | "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
+            "pptx"
+        }
+        "epub" | "application/epub+zip" => "epub",
+        "txt" | "text/plain" => "txt",
+        "md" | "markdown" | "text/markdown" => "md",
+        "html" | "htm" | "text/html" => "html",
+        "otf" | "font/otf" => "otf",
+        "ttf" | "font/ttf" | "font/sfnt" => "ttf",
+        "glb" | "model/gltf-binary" => "glb",
+        "gltf" | "model/gltf+json" => "gltf",
         "ai" | "application/postscript" => "ai",
         "arw" | "image/x-sony-arw" => "arw",
         "nef" | "image/x-nikon-nef" => "nef",
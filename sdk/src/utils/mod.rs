@@ -13,6 +13,8 @@
 
 pub(crate) mod base64;
 pub(crate) mod cbor_types;
+pub(crate) mod format_detect;
+pub(crate) mod forward_stream;
 #[allow(dead_code)]
 pub(crate) mod hash_utils;
 pub(crate) mod merkle;
@@ -20,8 +20,10 @@ use fast_xml::{
 use log::error;
 
 use crate::{
-    asset_io::CAIRead, jumbf_io::get_cailoader_handler, utils::hash_utils::vec_compare, Error,
-    Result,
+    asset_io::{CAIRead, CAIReader},
+    jumbf_io::get_cailoader_handler,
+    utils::hash_utils::vec_compare,
+    Error, Result,
 };
 
 const RDF_DESCRIPTION: &[u8] = b"rdf:Description";
@@ -420,6 +420,12 @@ pub const ASSERTION_CBOR_INVALID: &str = "assertion.cbor.invalid";
 /// `ValidationStatus.url()` will point to a C2PA assertion.
 pub const ACTION_ASSERTION_INGREDIENT_MISMATCH: &str = "assertion.action.ingredientMismatch";
 
+/// An `action` assertion is missing a required field or has its
+/// actions in an order that is not allowed.
+///
+/// `ValidationStatus.url()` will point to a C2PA assertion.
+pub const ACTION_ASSERTION_MALFORMED: &str = "assertion.action.malformed";
+
 /// An `action` assertion was redacted when the ingredient's
 /// claim was created.
 ///
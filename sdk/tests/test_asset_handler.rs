@@ -0,0 +1,189 @@
+// Copyright 2024 Adobe. All rights reserved.
+// This file is licensed to you under the Apache License,
+// Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0)
+// or the MIT license (http://opensource.org/licenses/MIT),
+// at your option.
+
+// Unless required by applicable law or agreed to in writing,
+// this software is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR REPRESENTATIONS OF ANY KIND, either express or
+// implied. See the LICENSE-MIT and LICENSE-APACHE files for the
+// specific language governing permissions and limitations under
+// each license.
+
+use std::{io::Cursor, path::Path};
+
+use c2pa::{
+    format_capabilities, jumbf_io::get_supported_types, register_asset_handler, AssetIO, Builder,
+    CAIRead, CAIReadWrite, CAIReader, CAIWriter, Error, HashBlockObjectType, HashObjectPositions,
+    Ingredient, Reader, Result,
+};
+
+mod common;
+use common::{fixtures_path, test_signer};
+
+const RAW_FORMAT: &str = "application/x-raw-test";
+
+// A minimal container: a big endian u32 manifest store length, the store, then the payload.
+struct RawIO {}
+
+impl RawIO {
+    fn split(stream: &mut dyn CAIRead) -> Result<(Vec<u8>, Vec<u8>)> {
+        stream.rewind()?;
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+
+        let mut len = [0u8; 4];
+        len.copy_from_slice(
+            data.get(0..4)
+                .ok_or(Error::InvalidAsset("raw header missing".to_string()))?,
+        );
+        let store_end = 4 + u32::from_be_bytes(len) as usize;
+        if store_end > data.len() {
+            return Err(Error::InvalidAsset("raw store truncated".to_string()));
+        }
+
+        Ok((data[4..store_end].to_vec(), data[store_end..].to_vec()))
+    }
+
+    fn write(output_stream: &mut dyn CAIReadWrite, store: &[u8], payload: &[u8]) -> Result<()> {
+        output_stream.rewind()?;
+        output_stream.write_all(&(store.len() as u32).to_be_bytes())?;
+        output_stream.write_all(store)?;
+        output_stream.write_all(payload)?;
+        Ok(())
+    }
+}
+
+impl CAIReader for RawIO {
+    fn read_cai(&self, asset_reader: &mut dyn CAIRead) -> Result<Vec<u8>> {
+        let (store, _) = Self::split(asset_reader)?;
+        if store.is_empty() {
+            return Err(Error::JumbfNotFound);
+        }
+        Ok(store)
+    }
+
+    fn read_xmp(&self, _asset_reader: &mut dyn CAIRead) -> Option<String> {
+        None
+    }
+}
+
+impl CAIWriter for RawIO {
+    fn write_cai(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+        store_bytes: &[u8],
+    ) -> Result<()> {
+        let (_, payload) = Self::split(input_stream)?;
+        Self::write(output_stream, store_bytes, &payload)
+    }
+
+    fn get_object_locations_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+    ) -> Result<Vec<HashObjectPositions>> {
+        let (store, payload) = Self::split(input_stream)?;
+        Ok(vec![
+            HashObjectPositions {
+                offset: 0,
+                length: 4 + store.len(),
+                htype: HashBlockObjectType::Cai,
+            },
+            HashObjectPositions {
+                offset: 4 + store.len(),
+                length: payload.len(),
+                htype: HashBlockObjectType::Other,
+            },
+        ])
+    }
+
+    fn remove_cai_store_from_stream(
+        &self,
+        input_stream: &mut dyn CAIRead,
+        output_stream: &mut dyn CAIReadWrite,
+    ) -> Result<()> {
+        let (_, payload) = Self::split(input_stream)?;
+        Self::write(output_stream, &[], &payload)
+    }
+}
+
+impl AssetIO for RawIO {
+    fn new(_asset_type: &str) -> Self {
+        RawIO {}
+    }
+
+    fn get_handler(&self, asset_type: &str) -> Box<dyn AssetIO> {
+        Box::new(RawIO::new(asset_type))
+    }
+
+    fn get_reader(&self) -> &dyn CAIReader {
+        self
+    }
+
+    fn get_writer(&self, asset_type: &str) -> Option<Box<dyn CAIWriter>> {
+        Some(Box::new(RawIO::new(asset_type)))
+    }
+
+    fn read_cai_store(&self, asset_path: &Path) -> Result<Vec<u8>> {
+        self.read_cai(&mut std::fs::File::open(asset_path)?)
+    }
+
+    fn save_cai_store(&self, _asset_path: &Path, _store_bytes: &[u8]) -> Result<()> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn get_object_locations(&self, _asset_path: &Path) -> Result<Vec<HashObjectPositions>> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn remove_cai_store(&self, _asset_path: &Path) -> Result<()> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn supported_types(&self) -> &[&str] {
+        &["xraw", RAW_FORMAT]
+    }
+}
+
+fn raw_asset() -> Cursor<Vec<u8>> {
+    let mut asset = 0u32.to_be_bytes().to_vec();
+    asset.extend_from_slice(b"raw test payload");
+    Cursor::new(asset)
+}
+
+#[test]
+fn test_registered_handler() -> Result<()> {
+    register_asset_handler(Box::new(RawIO {}));
+
+    assert!(get_supported_types().contains(&RAW_FORMAT.to_string()));
+
+    let capabilities = format_capabilities(RAW_FORMAT);
+    assert!(capabilities.read && capabilities.write && capabilities.data_hash);
+    assert!(!capabilities.box_hash && !capabilities.remote_ref);
+
+    // sign using the registered handler
+    let manifest_def = std::fs::read_to_string(fixtures_path("simple_manifest.json"))?;
+    let mut builder = Builder::from_json(&manifest_def)?;
+
+    let mut source = raw_asset();
+    let mut dest = Cursor::new(Vec::new());
+    builder.sign(&test_signer(), RAW_FORMAT, &mut source, &mut dest)?;
+
+    // the payload is untouched
+    assert!(dest.get_ref().ends_with(b"raw test payload"));
+
+    // and read it back
+    dest.set_position(0);
+    let reader = Reader::from_stream(RAW_FORMAT, &mut dest)?;
+    assert!(reader.active_manifest().is_some());
+    assert_eq!(reader.validation_status(), None);
+
+    dest.set_position(0);
+    let ingredient = Ingredient::from_stream("xraw", &mut dest)?;
+    assert!(ingredient.active_manifest().is_some());
+    assert!(ingredient.validation_status().is_none());
+
+    Ok(())
+}
@@ -11,9 +11,9 @@
 // specific language governing permissions and limitations under
 // each license.
 
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
-use c2pa::{Builder, Result};
+use c2pa::{Builder, Reader, Result};
 
 mod common;
 use common::{compare_stream_to_known_good, fixtures_path, test_signer};
@@ -39,3 +39,26 @@ fn test_builder_ca_jpg() -> Result<()> {
     dest.set_position(0);
     compare_stream_to_known_good(&mut dest, format, "CA_test.json")
 }
+
+#[test]
+fn test_builder_sign_from_read() -> Result<()> {
+    // jpeg is signed in a single pass, heic is spooled for its chunk offsets
+    for (fixture, format) in [
+        ("IMG_0003.jpg", "image/jpeg"),
+        ("sample1.heic", "image/heic"),
+    ] {
+        let manifest_def = std::fs::read_to_string(fixtures_path("simple_manifest.json"))?;
+        let mut builder = Builder::from_json(&manifest_def)?;
+
+        // Take is not seekable
+        let source = std::fs::File::open(fixtures_path(fixture))?.take(u64::MAX);
+        let mut dest = Cursor::new(Vec::new());
+        builder.sign_from_read(&test_signer(), format, source, &mut dest)?;
+
+        dest.set_position(0);
+        let reader = Reader::from_read(format, dest)?;
+        assert!(reader.active_manifest().is_some());
+        assert_eq!(reader.validation_status(), None, "{fixture}");
+    }
+    Ok(())
+}
@@ -38,6 +38,16 @@ fn test_reader_ca_jpg() -> Result<()> {
     compare_to_known_good(&reader, "CA.json")
 }
 
+#[test]
+fn test_reader_from_read() -> Result<()> {
+    use std::io::Read;
+
+    // Take is not seekable
+    let (format, stream) = fixture_stream("CA.jpg")?;
+    let reader = Reader::from_read(&format, stream.take(u64::MAX))?;
+    compare_to_known_good(&reader, "CA.json")
+}
+
 #[test]
 fn test_reader_c_jpg() -> Result<()> {
     let (format, mut stream) = fixture_stream("C.jpg")?;
@@ -52,6 +62,33 @@ fn test_reader_xca_jpg() -> Result<()> {
     compare_to_known_good(&reader, "XCA.json")
 }
 
+#[test]
+fn test_reader_autodetect() -> Result<()> {
+    let (format, mut stream) = fixture_stream("CA.jpg")?;
+    let reader = Reader::from_stream_autodetect(None, &mut stream)?;
+    compare_to_known_good(&reader, "CA.json")?;
+
+    let reader = Reader::from_stream_autodetect(Some(&format), &mut stream)?;
+    compare_to_known_good(&reader, "CA.json")
+}
+
+#[test]
+fn test_reader_autodetect_mismatch() -> Result<()> {
+    let (_format, mut stream) = fixture_stream("CA.jpg")?;
+    let result = Reader::from_stream_autodetect(Some("image/png"), &mut stream);
+    assert_err!(result, Err(Error::FormatMismatch { .. }));
+    Ok(())
+}
+
+#[test]
+fn test_reader_autodetect_opus() -> Result<()> {
+    // opus is an ogg stream, so this is read as ogg rather than rejected as a mismatch
+    let (_format, mut stream) = fixture_stream("sample1.opus")?;
+    let result = Reader::from_stream_autodetect(Some("audio/opus"), &mut stream);
+    assert_err!(result, Err(Error::JumbfNotFound));
+    Ok(())
+}
+
 #[test]
 #[ignore]
 /// Generates the known good for the above tests